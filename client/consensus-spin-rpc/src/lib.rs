@@ -0,0 +1,365 @@
+// Copyright (C) QF Network, 2025.
+// SPDX-License-Identifier: Apache-2.0
+
+//! RPC API for SPIN.
+
+#![forbid(missing_docs, unsafe_code)]
+
+use std::{
+	fmt::Debug,
+	marker::PhantomData,
+	sync::Arc,
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use codec::Codec;
+use futures::{stream, StreamExt};
+use jsonrpsee::{
+	core::RpcResult,
+	proc_macros::rpc,
+	types::{ErrorObject, ErrorObjectOwned},
+	PendingSubscriptionSink,
+};
+use qfc_consensus_spin::{
+	standalone::{fetch_aux_data, slot_author, slot_schedule_at},
+	ClockDrift, SpinApi as SpinRuntimeApi, SpinAuxData, SpinSlotScheduleApi,
+};
+use qfp_consensus_spin::Slot;
+use sc_rpc::SubscriptionTaskExecutor;
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::crypto::Pair;
+use sp_runtime::traits::{Block as BlockT, NumberFor, SaturatedConversion, Zero};
+
+type AuthorityId<P> = <P as Pair>::Public;
+
+/// The most slots [`SpinScheduleApiServer::leader_schedule`] returns at once.
+const MAX_SCHEDULED_SLOTS: u32 = 4096;
+
+/// The base code of the errors of the SPIN RPC.
+const BASE_ERROR: i32 = 7000;
+
+/// Provides rpc methods for interacting with SPIN.
+#[rpc(client, server)]
+pub trait SpinApi {
+	/// Returns the estimated offset of the local clock from the clocks of the
+	/// block authors, or `null` until enough blocks were imported to tell.
+	#[method(name = "spin_clockOffset")]
+	fn clock_offset(&self) -> RpcResult<Option<ClockOffset>>;
+}
+
+/// The estimated offset of the local clock.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClockOffset {
+	/// Milliseconds the local clock is ahead of the clocks of the block
+	/// authors, or behind if negative.
+	pub offset: i64,
+	/// The slot duration, in milliseconds.
+	pub slot_duration: u64,
+	/// The number of imported blocks the estimate is made from.
+	pub samples: u32,
+}
+
+impl From<qfc_consensus_spin::ClockOffset> for ClockOffset {
+	fn from(offset: qfc_consensus_spin::ClockOffset) -> Self {
+		Self { offset: offset.millis, slot_duration: offset.slot_duration, samples: offset.samples }
+	}
+}
+
+/// Provides rpc methods for predicting the SPIN slot leaders.
+#[rpc(client, server)]
+pub trait SpinScheduleApi<Hash, AuthorityId> {
+	/// Returns the leader of the current slot, as told by the local clock, or
+	/// `null` if the slot has no leader.
+	#[method(name = "spin_currentLeader")]
+	fn current_leader(&self) -> RpcResult<Option<SlotLeader<AuthorityId>>>;
+
+	/// Returns the leaders of `count` slots from `from_slot` on, as scheduled
+	/// at the best block. Slots without a leader are left out.
+	///
+	/// Changes of the authorities or of their order after the best block are
+	/// not foreseen.
+	#[method(name = "spin_leaderSchedule")]
+	fn leader_schedule(
+		&self,
+		from_slot: u64,
+		count: u32,
+	) -> RpcResult<Vec<SlotLeader<AuthorityId>>>;
+
+	/// Returns the number of consecutive slots every authority leads.
+	#[method(name = "spin_sessionLength")]
+	fn session_length(&self) -> RpcResult<u64>;
+
+	/// Returns the duration of the current slots, in milliseconds.
+	#[method(name = "spin_slotDuration")]
+	fn slot_duration(&self) -> RpcResult<u64>;
+
+	/// Returns the authorities at the given block, or at the best block.
+	#[method(name = "spin_authorities")]
+	fn authorities(&self, at: Option<Hash>) -> RpcResult<Vec<AuthorityId>>;
+
+	/// Subscribes to the leader of the current slot, notified whenever another
+	/// authority takes over.
+	#[subscription(
+		name = "spin_subscribeLeaderChanges" => "spin_leaderChanges",
+		unsubscribe = "spin_unsubscribeLeaderChanges",
+		item = SlotLeader<AuthorityId>
+	)]
+	fn subscribe_leader_changes(&self);
+}
+
+/// The leader of a slot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlotLeader<AuthorityId> {
+	/// The slot.
+	pub slot: u64,
+	/// The authority leading the slot.
+	pub authority: AuthorityId,
+}
+
+/// Errors of the SPIN RPC.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+	/// Calling the runtime failed.
+	#[error(transparent)]
+	Blockchain(#[from] sp_blockchain::Error),
+	/// Reading the state of SPIN failed.
+	#[error(transparent)]
+	Consensus(#[from] sp_consensus::Error),
+	/// More slots were requested than are scheduled at once.
+	#[error("Requested {0} slots, at most {MAX_SCHEDULED_SLOTS} are scheduled at once")]
+	TooManySlots(u32),
+}
+
+impl From<Error> for ErrorObjectOwned {
+	fn from(error: Error) -> Self {
+		let code = match error {
+			Error::Blockchain(_) => BASE_ERROR + 1,
+			Error::Consensus(_) => BASE_ERROR + 2,
+			Error::TooManySlots(_) => BASE_ERROR + 3,
+		};
+		ErrorObject::owned(code, error.to_string(), None::<()>)
+	}
+}
+
+/// Provides RPC methods for interacting with SPIN.
+pub struct Spin {
+	clock_drift: ClockDrift,
+}
+
+impl Spin {
+	/// Creates a new instance of the SPIN RPC handler.
+	pub fn new(clock_drift: ClockDrift) -> Self {
+		Self { clock_drift }
+	}
+}
+
+impl SpinApiServer for Spin {
+	fn clock_offset(&self) -> RpcResult<Option<ClockOffset>> {
+		Ok(self.clock_drift.offset().map(Into::into))
+	}
+}
+
+/// Provides RPC methods for predicting the SPIN slot leaders.
+pub struct SpinSchedule<C, B, P> {
+	client: Arc<C>,
+	executor: SubscriptionTaskExecutor,
+	_phantom: PhantomData<fn() -> (B, P)>,
+}
+
+impl<C, B, P> SpinSchedule<C, B, P> {
+	/// Creates a new instance of the SPIN schedule RPC handler.
+	pub fn new(client: Arc<C>, executor: SubscriptionTaskExecutor) -> Self {
+		Self { client, executor, _phantom: PhantomData }
+	}
+}
+
+/// The leaders of `count` slots from `from_slot` on, as scheduled by
+/// `aux_data`.
+fn leaders<B: BlockT, P: Pair>(
+	aux_data: &SpinAuxData<AuthorityId<P>, NumberFor<B>>,
+	from_slot: u64,
+	count: u32,
+) -> Vec<SlotLeader<AuthorityId<P>>> {
+	if aux_data.session_length.is_zero() {
+		return Vec::new();
+	}
+
+	(0..u64::from(count))
+		.map_while(|offset| from_slot.checked_add(offset))
+		.filter_map(|slot| {
+			slot_author::<B, P>(
+				Slot::from(slot),
+				aux_data.session_length,
+				&aux_data.authorities,
+				&aux_data.leader_order,
+				&aux_data.disabled,
+			)
+			.map(|authority| SlotLeader { slot, authority: authority.clone() })
+		})
+		.collect()
+}
+
+/// The milliseconds since the Unix epoch.
+fn now_millis() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.unwrap_or_default()
+		.as_millis()
+		.saturated_into()
+}
+
+/// The leader of the current slot at the best block, and the time until the
+/// next slot.
+fn current_leader<C, B, P>(
+	client: &C,
+) -> Result<(Option<SlotLeader<AuthorityId<P>>>, Duration), Error>
+where
+	B: BlockT,
+	P: Pair,
+	P::Public: Codec + Debug,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B>,
+	C::Api: SpinRuntimeApi<B, AuthorityId<P>, NumberFor<B>> + SpinSlotScheduleApi<B>,
+{
+	let best_hash = client.info().best_hash;
+	let now = now_millis();
+
+	let schedule = slot_schedule_at::<AuthorityId<P>, B, C>(client, best_hash, now)?;
+	let slot = schedule.slot_at(now);
+	let aux_data = fetch_aux_data::<AuthorityId<P>, B, C>(client, best_hash)?;
+	let next_slot_in = schedule.slot_start(slot + 1).saturating_sub(now).max(1);
+
+	Ok((leaders::<B, P>(&aux_data, *slot, 1).pop(), Duration::from_millis(next_slot_in)))
+}
+
+impl<C, B, P> SpinScheduleApiServer<B::Hash, AuthorityId<P>> for SpinSchedule<C, B, P>
+where
+	B: BlockT,
+	P: Pair + 'static,
+	P::Public: Codec + Debug + Serialize,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B> + Send + Sync + 'static,
+	C::Api: SpinRuntimeApi<B, AuthorityId<P>, NumberFor<B>> + SpinSlotScheduleApi<B>,
+{
+	fn current_leader(&self) -> RpcResult<Option<SlotLeader<AuthorityId<P>>>> {
+		let (leader, _) = current_leader::<C, B, P>(&*self.client)?;
+		Ok(leader)
+	}
+
+	fn leader_schedule(
+		&self,
+		from_slot: u64,
+		count: u32,
+	) -> RpcResult<Vec<SlotLeader<AuthorityId<P>>>> {
+		if count > MAX_SCHEDULED_SLOTS {
+			return Err(Error::TooManySlots(count).into());
+		}
+
+		let best_hash = self.client.info().best_hash;
+		let aux_data = fetch_aux_data::<AuthorityId<P>, B, C>(&*self.client, best_hash)
+			.map_err(Error::from)?;
+		Ok(leaders::<B, P>(&aux_data, from_slot, count))
+	}
+
+	fn session_length(&self) -> RpcResult<u64> {
+		let best_hash = self.client.info().best_hash;
+		let aux_data = fetch_aux_data::<AuthorityId<P>, B, C>(&*self.client, best_hash)
+			.map_err(Error::from)?;
+		Ok(aux_data.session_length.saturated_into())
+	}
+
+	fn slot_duration(&self) -> RpcResult<u64> {
+		let best_hash = self.client.info().best_hash;
+		let schedule =
+			slot_schedule_at::<AuthorityId<P>, B, C>(&*self.client, best_hash, now_millis())
+				.map_err(Error::from)?;
+		Ok(schedule.slot_duration)
+	}
+
+	fn authorities(&self, at: Option<B::Hash>) -> RpcResult<Vec<AuthorityId<P>>> {
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+		let aux_data =
+			fetch_aux_data::<AuthorityId<P>, B, C>(&*self.client, at).map_err(Error::from)?;
+		Ok(aux_data.authorities)
+	}
+
+	fn subscribe_leader_changes(&self, pending: PendingSubscriptionSink) {
+		let client = self.client.clone();
+		let changes = stream::unfold(None, move |last: Option<AuthorityId<P>>| {
+			let client = client.clone();
+			async move {
+				loop {
+					// the subscription ends once the leader can not be told.
+					let (leader, next_slot_in) = current_leader::<C, B, P>(&*client).ok()?;
+					match leader {
+						Some(leader) if last.as_ref() != Some(&leader.authority) => {
+							let authority = leader.authority.clone();
+							return Some((leader, Some(authority)));
+						},
+						_ => futures_timer::Delay::new(next_slot_in).await,
+					}
+				}
+			}
+		});
+
+		sc_rpc::utils::spawn_subscription_task(
+			&self.executor,
+			sc_rpc::utils::pipe_from_stream(pending, changes.boxed()),
+		);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use jsonrpsee::core::EmptyServerParams as EmptyParams;
+	use qfp_consensus_spin::sr25519::AuthorityPair;
+	use sp_keyring::Sr25519Keyring;
+	use sp_runtime::{generic, traits::BlakeTwo256, OpaqueExtrinsic};
+
+	type Block = generic::Block<generic::Header<u32, BlakeTwo256>, OpaqueExtrinsic>;
+
+	#[tokio::test]
+	async fn clock_offset_is_null_without_samples() {
+		let api = Spin::new(ClockDrift::default()).into_rpc();
+
+		let offset: Option<ClockOffset> =
+			api.call("spin_clockOffset", EmptyParams::new()).await.unwrap();
+		assert_eq!(offset, None);
+	}
+
+	#[test]
+	fn leaders_follow_the_tenures_of_the_enabled_authorities() {
+		let [alice, bob, charlie] =
+			[Sr25519Keyring::Alice, Sr25519Keyring::Bob, Sr25519Keyring::Charlie]
+				.map(|keyring| AuthorityId::<AuthorityPair>::from(keyring.public()));
+		let aux_data = SpinAuxData {
+			authorities: vec![alice.clone(), bob, charlie.clone()],
+			session_length: 2,
+			session_index: 0,
+			leader_order: Vec::new(),
+			disabled: vec![1],
+			next_authorities: Vec::new(),
+		};
+
+		let schedule = leaders::<Block, AuthorityPair>(&aux_data, 2, 6)
+			.into_iter()
+			.map(|leader| (leader.slot, leader.authority))
+			.collect::<Vec<_>>();
+		// the tenure of the disabled authority falls through to the next one.
+		assert_eq!(
+			schedule,
+			vec![
+				(2, charlie.clone()),
+				(3, charlie.clone()),
+				(4, charlie.clone()),
+				(5, charlie),
+				(6, alice.clone()),
+				(7, alice),
+			],
+		);
+		assert_eq!(leaders::<Block, AuthorityPair>(&aux_data, u64::MAX - 1, 6).len(), 2);
+	}
+}
@@ -0,0 +1,40 @@
+// Copyright (C) QF Network, 2025.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Schema for SPIN on-disk storage.
+
+use codec::{Decode, Encode};
+use sc_client_api::backend::AuxStore;
+use sp_blockchain::{Error as ClientError, Result as ClientResult};
+
+const BLOCK_WEIGHT_KEY: &[u8] = b"spin_block_weight";
+
+/// The weight of a chain: the number of its blocks authored by slot leaders.
+pub type SpinBlockWeight = u64;
+
+fn block_weight_key<H: Encode>(block_hash: H) -> Vec<u8> {
+	(BLOCK_WEIGHT_KEY, block_hash).encode()
+}
+
+/// The auxiliary storage entry of the cumulative chain weight of a block.
+pub(crate) fn block_weight_aux<H: Encode>(
+	block_hash: H,
+	block_weight: SpinBlockWeight,
+) -> (Vec<u8>, Option<Vec<u8>>) {
+	(block_weight_key(block_hash), Some(block_weight.encode()))
+}
+
+/// Load the cumulative chain weight associated with a block.
+pub fn load_block_weight<H: Encode, B: AuxStore>(
+	backend: &B,
+	block_hash: H,
+) -> ClientResult<Option<SpinBlockWeight>> {
+	backend
+		.get_aux(&block_weight_key(block_hash))?
+		.map(|weight| {
+			SpinBlockWeight::decode(&mut &weight[..]).map_err(|e| {
+				ClientError::Backend(format!("SPIN DB is corrupted. Decode error: {}", e))
+			})
+		})
+		.transpose()
+}
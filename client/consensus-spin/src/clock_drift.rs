@@ -0,0 +1,184 @@
+// Copyright (C) QF Network, 2025.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Estimation of the offset of the local clock.
+//!
+//! SPIN relies on its authorities roughly agreeing on time, as every node
+//! tells the current slot from its own clock. The import queue samples how
+//! long after the start of their slot blocks near the tip of the chain are
+//! imported. With synced clocks that is the time their author spent proposing
+//! and the time they spent propagating, a local clock that is off shifts every
+//! sample by its offset.
+//!
+//! Like the clock filter of NTP, the estimate favours the samples with the
+//! least delay: it is the lower quartile of the recent samples, so that a
+//! single author with a bad clock of its own does not skew it.
+
+use std::{
+	collections::VecDeque,
+	sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use log::{info, warn};
+use sc_consensus_slots::SlotProportion;
+
+use crate::LOG_TARGET;
+
+/// Number of the most recent samples the offset is estimated from.
+const WINDOW: usize = 64;
+
+/// Number of samples needed before there is an estimate.
+const MIN_SAMPLES: usize = 8;
+
+/// How far off the local clock may be, as proportions of the slot duration.
+#[derive(Debug, Clone, Copy)]
+pub struct ClockDriftLimits {
+	/// Warn once the estimated offset exceeds this proportion of the slot
+	/// duration.
+	pub warn_at: SlotProportion,
+	/// Refuse to author blocks while the estimated offset exceeds this
+	/// proportion of the slot duration, or author regardless if `None`.
+	pub refuse_at: Option<SlotProportion>,
+}
+
+impl Default for ClockDriftLimits {
+	fn default() -> Self {
+		Self { warn_at: SlotProportion::new(0.25), refuse_at: None }
+	}
+}
+
+/// An estimate of the offset of the local clock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockOffset {
+	/// Milliseconds the local clock is ahead of the clocks of the block
+	/// authors, or behind if negative.
+	///
+	/// This includes the delay of blocks after the start of their slot, which
+	/// is short compared to the slot duration on a healthy network.
+	pub millis: i64,
+	/// The slot duration at the most recent sample, in milliseconds.
+	pub slot_duration: u64,
+	/// The number of samples the estimate is made from.
+	pub samples: u32,
+}
+
+impl ClockOffset {
+	/// Whether the offset exceeds `proportion` of the slot duration.
+	pub fn exceeds(&self, proportion: SlotProportion) -> bool {
+		self.millis.unsigned_abs() as f64 > self.slot_duration as f64 * proportion.get() as f64
+	}
+}
+
+/// The estimated offset of the local clock.
+///
+/// Clones share the estimate, so that the import queue can sample it while
+/// the slot worker and the RPC read it.
+#[derive(Clone)]
+pub struct ClockDrift {
+	limits: ClockDriftLimits,
+	state: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+	samples: VecDeque<i64>,
+	estimate: Option<ClockOffset>,
+}
+
+impl ClockDrift {
+	/// Create an estimate without samples, acting on `limits`.
+	pub fn new(limits: ClockDriftLimits) -> Self {
+		Self { limits, state: Default::default() }
+	}
+
+	/// The current estimate, if there are enough samples for one.
+	pub fn offset(&self) -> Option<ClockOffset> {
+		self.state().estimate
+	}
+
+	/// The estimated offset, if the local clock is too far off to author
+	/// blocks.
+	pub(crate) fn refusing_offset(&self) -> Option<ClockOffset> {
+		let refuse_at = self.limits.refuse_at?;
+		self.offset().filter(|offset| offset.exceeds(refuse_at))
+	}
+
+	/// Note that a block was imported `millis` after the start of its slot, or
+	/// before it if negative, and return the new estimate.
+	pub(crate) fn note(&self, millis: i64, slot_duration: u64) -> Option<ClockOffset> {
+		let mut state = self.state();
+		if state.samples.len() == WINDOW {
+			state.samples.pop_front();
+		}
+		state.samples.push_back(millis);
+		if state.samples.len() < MIN_SAMPLES {
+			return None;
+		}
+
+		let mut samples = state.samples.iter().copied().collect::<Vec<_>>();
+		samples.sort_unstable();
+		let estimate = ClockOffset {
+			millis: samples[samples.len() / 4],
+			slot_duration,
+			samples: samples.len() as u32,
+		};
+
+		let warn_at = self.limits.warn_at;
+		match (state.estimate.is_some_and(|e| e.exceeds(warn_at)), estimate.exceeds(warn_at)) {
+			(false, true) => warn!(
+				target: LOG_TARGET,
+				"The local clock seems {}ms {} the clocks of the block authors, more than {:.0}% \
+				of the slot duration. Check the time synchronisation of this node.",
+				estimate.millis.unsigned_abs(),
+				if estimate.millis > 0 { "ahead of" } else { "behind" },
+				warn_at.get() * 100.0,
+			),
+			(true, false) => info!(
+				target: LOG_TARGET,
+				"The local clock is back in sync with the clocks of the block authors.",
+			),
+			_ => {},
+		}
+
+		state.estimate = Some(estimate);
+		Some(estimate)
+	}
+
+	fn state(&self) -> MutexGuard<'_, State> {
+		self.state.lock().unwrap_or_else(PoisonError::into_inner)
+	}
+}
+
+impl Default for ClockDrift {
+	fn default() -> Self {
+		Self::new(Default::default())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn offset_is_estimated_from_the_least_delayed_samples() {
+		let drift = ClockDrift::new(ClockDriftLimits {
+			warn_at: SlotProportion::new(0.25),
+			refuse_at: Some(SlotProportion::new(0.5)),
+		});
+
+		for _ in 1..MIN_SAMPLES {
+			assert_eq!(drift.note(-3_000, 6_000), None);
+		}
+		// slow blocks do not add to the offset.
+		assert_eq!(drift.note(5_000, 6_000).map(|offset| offset.millis), Some(-3_000));
+		assert!(drift.refusing_offset().is_some());
+
+		for _ in 0..WINDOW {
+			drift.note(200, 6_000);
+		}
+		let offset = drift.offset().unwrap();
+		assert_eq!((offset.millis, offset.samples), (200, WINDOW as u32));
+		assert!(!offset.exceeds(SlotProportion::new(0.25)));
+		assert_eq!(drift.refusing_offset(), None);
+	}
+}
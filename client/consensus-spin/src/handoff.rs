@@ -0,0 +1,667 @@
+// Copyright (C) QF Network, 2025.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Pre-announcement of the hand-off between slot leaders.
+//!
+//! The leader of the first slot of a tenure may not have imported the block
+//! the previous leader authored in the last slot of its tenure yet, and would
+//! fork the chain by building next to it. Authors therefore announce the blocks
+//! they author in the last slot of a tenure on a notification protocol between
+//! the authorities, and the slot worker waits a bounded time for an announced
+//! block it did not import yet before authoring in the slot after it.
+//!
+//! Announcements are signed by the author of the block. Every authority
+//! forwards the announcements it accepts to its other peers once, so that
+//! authorities that are not connected to the author hear of them too.
+//!
+//! Only announcements of the slots around the current one are checked against
+//! the runtime, and at most [`MAX_ANNOUNCEMENTS_PER_SLOT`] of them per peer and
+//! slot. Peers sending invalid announcements lose reputation, and the
+//! substreams of banned peers are rejected.
+
+use std::{
+	collections::HashMap,
+	sync::{Arc, Mutex, MutexGuard, PoisonError},
+	time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use codec::{Codec, Decode, Encode};
+use log::{debug, trace};
+use sc_consensus_slots::{SlotInfo, SlotProportion, SlotResult, SlotWorker};
+use sc_network::{
+	config::{NonReservedPeerMode, SetConfig},
+	peer_store::PeerStoreProvider,
+	service::{
+		traits::{MessageSink, NotificationEvent, NotificationService, ValidationResult},
+		NotificationMetrics,
+	},
+	NetworkBackend, PeerId, ProtocolName, ReputationChange,
+};
+use sp_api::ProvideRuntimeApi;
+use sp_application_crypto::{AppCrypto, AppPublic};
+use sp_blockchain::HeaderBackend;
+use sp_consensus::Error as ConsensusError;
+use sp_consensus_slots::Slot;
+use sp_core::{
+	crypto::{ByteArray, Pair},
+	hexdisplay::HexDisplay,
+};
+use sp_keystore::KeystorePtr;
+use sp_runtime::traits::{Block as BlockT, Header, NumberFor, One, SaturatedConversion};
+
+use crate::{
+	standalone::{expected_author, SlotAuthors},
+	AuthorityId, AuthorityIndex, CompatibilityMode, PreDigest, SlotSchedule, SpinApi,
+	SpinFallbackApi, SpinScheduledChangeApi, SpinSlotScheduleApi, LOG_TARGET,
+};
+
+/// The largest announcement accepted, in bytes.
+const MAX_ANNOUNCEMENT_SIZE: u64 = 1024;
+
+/// The number of inbound and of outbound peers of the protocol.
+const PEERS: u32 = 25;
+
+/// How often the slot worker checks whether the announced block was imported.
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// The number of announcements of a peer checked per slot. Authorities forward
+/// every accepted announcement once, and the leader announces every block it
+/// authors in the last slot of its tenure.
+const MAX_ANNOUNCEMENTS_PER_SLOT: u32 = 8;
+
+mod rep {
+	use sc_network::ReputationChange as Rep;
+
+	/// An announcement that does not decode.
+	pub const UNDECODABLE: Rep = Rep::new_fatal("SPIN hand-off: undecodable announcement");
+	/// An announcement of a slot that is neither the current one nor next to
+	/// it.
+	pub const SLOT_OUT_OF_RANGE: Rep = Rep::new(-(1 << 10), "SPIN hand-off: slot out of range");
+	/// More announcements in a slot than honest peers send.
+	pub const FLOODING: Rep = Rep::new(-(1 << 10), "SPIN hand-off: too many announcements");
+	/// An announcement of a slot that does not end a tenure, or not signed by
+	/// the author of the slot.
+	pub const INVALID: Rep = Rep::new(-(1 << 16), "SPIN hand-off: invalid announcement");
+}
+
+/// Parameters of the hand-off between slot leaders.
+pub struct LeaderHandoffParams {
+	/// The notification service of the protocol configured by
+	/// [`handoff_peers_set_config`].
+	pub notification_service: Box<dyn NotificationService>,
+	/// The peer store of the network, to report the peers sending invalid
+	/// announcements to.
+	pub peer_store: Arc<dyn PeerStoreProvider>,
+	/// Whether to announce the blocks authored in the last slot of a tenure.
+	pub announce: bool,
+	/// The proportion of the slot after an announced block to wait for it
+	/// before authoring, if it was not imported yet.
+	pub wait: SlotProportion,
+}
+
+/// The name of the hand-off protocol of the chain with `genesis_hash`.
+pub fn handoff_protocol_name<Hash: AsRef<[u8]>>(
+	genesis_hash: &Hash,
+	fork_id: Option<&str>,
+) -> ProtocolName {
+	let genesis_hash = HexDisplay::from(&genesis_hash.as_ref());
+	match fork_id {
+		Some(fork_id) => format!("/{}/{}/spin/handoff/1", genesis_hash, fork_id),
+		None => format!("/{}/spin/handoff/1", genesis_hash),
+	}
+	.into()
+}
+
+/// The configuration of the hand-off protocol, to add to the network
+/// configuration of authorities.
+pub fn handoff_peers_set_config<B: BlockT, N: NetworkBackend<B, B::Hash>>(
+	protocol_name: ProtocolName,
+	metrics: NotificationMetrics,
+	peer_store_handle: Arc<dyn PeerStoreProvider>,
+) -> (N::NotificationProtocolConfig, Box<dyn NotificationService>) {
+	N::notification_config(
+		protocol_name,
+		Vec::new(),
+		MAX_ANNOUNCEMENT_SIZE,
+		None,
+		SetConfig {
+			in_peers: PEERS,
+			out_peers: PEERS,
+			reserved_nodes: Vec::new(),
+			non_reserved_mode: NonReservedPeerMode::Accept,
+		},
+		metrics,
+		peer_store_handle,
+	)
+}
+
+/// An announcement of a block authored in the last slot of a tenure.
+#[derive(Debug, Encode, Decode)]
+struct Announcement<Hash, Number, Signature> {
+	slot: Slot,
+	/// The index of the author if it authored the block as secondary author.
+	secondary: Option<AuthorityIndex>,
+	number: Number,
+	hash: Hash,
+	signature: Signature,
+}
+
+/// The payload the author of an announced block signs.
+fn signing_payload<Hash: Encode, Number: Encode>(
+	pre_digest: &PreDigest,
+	number: &Number,
+	hash: &Hash,
+) -> Vec<u8> {
+	let secondary = match *pre_digest {
+		PreDigest::Primary(_) => None,
+		PreDigest::Secondary { authority_index, .. } => Some(authority_index),
+	};
+	(b"spin-handoff", pre_digest.slot(), secondary, number, hash).encode()
+}
+
+/// Whether `slot` is the last slot of a tenure of `session_length` slots.
+pub(crate) fn last_slot_of_tenure(slot: Slot, session_length: u64) -> bool {
+	session_length != 0 && (*slot + 1) % session_length == 0
+}
+
+/// The latest announced block, shared between the slot worker and the task
+/// driving the protocol.
+pub(crate) struct Handoff<Hash, Number> {
+	state: Arc<Mutex<State<Hash, Number>>>,
+}
+
+struct State<Hash, Number> {
+	peers: HashMap<PeerId, Box<dyn MessageSink>>,
+	announced: Option<(Slot, Number, Hash)>,
+}
+
+impl<Hash, Number> Clone for Handoff<Hash, Number> {
+	fn clone(&self) -> Self {
+		Self { state: self.state.clone() }
+	}
+}
+
+impl<Hash, Number> Handoff<Hash, Number>
+where
+	Hash: Codec + Clone,
+	Number: Codec + Copy + PartialOrd,
+{
+	pub(crate) fn new() -> Self {
+		Self { state: Arc::new(Mutex::new(State { peers: HashMap::new(), announced: None })) }
+	}
+
+	/// The slot and hash of the latest announced block.
+	pub(crate) fn announced(&self) -> Option<(Slot, Hash)> {
+		self.state().announced.as_ref().map(|(slot, _, hash)| (*slot, hash.clone()))
+	}
+
+	/// Whether a block is announced later than the latest announced block.
+	fn is_newer(&self, slot: Slot, number: Number) -> bool {
+		self.state().announced.as_ref().is_none_or(|(latest_slot, latest_number, _)| {
+			(slot, number) > (*latest_slot, *latest_number)
+		})
+	}
+
+	/// Note an announced block, if it is announced later than the latest.
+	fn note(&self, slot: Slot, number: Number, hash: Hash) -> bool {
+		if !self.is_newer(slot, number) {
+			return false;
+		}
+		self.state().announced = Some((slot, number, hash));
+		true
+	}
+
+	/// Send `announcement` to every peer but `except`.
+	fn broadcast(&self, announcement: &[u8], except: Option<&PeerId>) {
+		for (peer, sink) in &self.state().peers {
+			if Some(peer) != except {
+				sink.send_sync_notification(announcement.to_vec());
+			}
+		}
+	}
+
+	/// Announce a block authored by `public` with `pre_digest`.
+	pub(crate) fn announce<P>(
+		&self,
+		keystore: &KeystorePtr,
+		public: &P::Public,
+		pre_digest: &PreDigest,
+		number: Number,
+		hash: Hash,
+	) -> Result<(), ConsensusError>
+	where
+		P: Pair,
+		P::Public: AppPublic,
+		P::Signature: Codec + TryFrom<Vec<u8>>,
+	{
+		let payload = signing_payload(pre_digest, &number, &hash);
+		let signature = keystore
+			.sign_with(
+				<AuthorityId<P> as AppCrypto>::ID,
+				<AuthorityId<P> as AppCrypto>::CRYPTO_ID,
+				public.as_slice(),
+				&payload,
+			)
+			.map_err(|e| ConsensusError::CannotSign(format!("{}. Key: {:?}", e, public)))?
+			.ok_or_else(|| {
+				ConsensusError::CannotSign(format!(
+					"Could not find key in keystore. Key: {:?}",
+					public
+				))
+			})?;
+		let signature = P::Signature::try_from(signature.clone())
+			.map_err(|_| ConsensusError::InvalidSignature(signature, public.to_raw_vec()))?;
+
+		let slot = pre_digest.slot();
+		let secondary = match *pre_digest {
+			PreDigest::Primary(_) => None,
+			PreDigest::Secondary { authority_index, .. } => Some(authority_index),
+		};
+		let announcement =
+			Announcement { slot, secondary, number, hash: hash.clone(), signature }.encode();
+		self.note(slot, number, hash);
+		self.broadcast(&announcement, None);
+		Ok(())
+	}
+
+	fn state(&self) -> MutexGuard<'_, State<Hash, Number>> {
+		self.state.lock().unwrap_or_else(PoisonError::into_inner)
+	}
+}
+
+/// A rejected announcement.
+#[derive(Debug)]
+struct Rejection {
+	/// The cost to the reputation of the peer that sent the announcement, if
+	/// it is to blame.
+	cost: Option<ReputationChange>,
+	reason: String,
+}
+
+impl Rejection {
+	fn new(cost: ReputationChange, reason: String) -> Self {
+		Self { cost: Some(cost), reason }
+	}
+}
+
+/// Decode an announcement and return it if it is of a slot next to `now` and
+/// later than the latest announced block, before any runtime call.
+fn precheck_announcement<Hash, Number, Signature>(
+	handoff: &Handoff<Hash, Number>,
+	announcement: &[u8],
+	now: Slot,
+) -> Result<Option<Announcement<Hash, Number, Signature>>, Rejection>
+where
+	Hash: Codec + Clone,
+	Number: Codec + Copy + PartialOrd,
+	Signature: Decode,
+{
+	let announcement = Announcement::<Hash, Number, Signature>::decode(&mut &announcement[..])
+		.map_err(|e| Rejection::new(rep::UNDECODABLE, format!("undecodable: {}", e)))?;
+
+	// the leader announces its blocks within the last slot of its tenure and
+	// authorities forward them right away.
+	let slot = *announcement.slot;
+	if slot < (*now).saturating_sub(1) || slot > (*now).saturating_add(1) {
+		return Err(Rejection::new(
+			rep::SLOT_OUT_OF_RANGE,
+			format!("slot {} announced in slot {}", slot, now),
+		));
+	}
+
+	Ok(handoff.is_newer(announcement.slot, announcement.number).then_some(announcement))
+}
+
+/// Check that `announcement` is of the last slot of a tenure and signed by the
+/// author of its block according to `slot_authors`.
+fn verify_announcement<P, B>(
+	announcement: &Announcement<B::Hash, NumberFor<B>, P::Signature>,
+	slot_authors: &SlotAuthors<AuthorityId<P>, NumberFor<B>>,
+) -> Result<(), String>
+where
+	P: Pair,
+	B: BlockT,
+{
+	let Announcement { slot, secondary, number, hash, signature } = announcement;
+	if !last_slot_of_tenure(*slot, slot_authors.session_length.saturated_into()) {
+		return Err(format!("slot {} is not the last slot of a tenure", slot));
+	}
+
+	let pre_digest = match *secondary {
+		None => PreDigest::Primary(*slot),
+		Some(authority_index) => PreDigest::Secondary { slot: *slot, authority_index },
+	};
+	let author = expected_author::<B, P>(&pre_digest, slot_authors)
+		.ok_or_else(|| format!("no author of slot {}", slot))?;
+	if !P::verify(signature, signing_payload(&pre_digest, number, hash), author) {
+		return Err(format!("bad signature of block {}", hash));
+	}
+
+	Ok(())
+}
+
+/// Check an announcement against the authorities at the best block, and
+/// return the slot, number and hash of the announced block.
+fn check_announcement<P, B, C>(
+	client: &C,
+	handoff: &Handoff<B::Hash, NumberFor<B>>,
+	announcement: &[u8],
+	now: Slot,
+	compatibility_mode: &CompatibilityMode<NumberFor<B>>,
+) -> Result<Option<(Slot, NumberFor<B>, B::Hash)>, Rejection>
+where
+	P: Pair,
+	P::Public: Codec + std::fmt::Debug,
+	P::Signature: Codec,
+	B: BlockT,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B>,
+	C::Api: SpinApi<B, AuthorityId<P>, NumberFor<B>>
+		+ SpinFallbackApi<B>
+		+ SpinScheduledChangeApi<B, AuthorityId<P>, NumberFor<B>>,
+{
+	let Some(announcement) =
+		precheck_announcement::<_, _, P::Signature>(handoff, announcement, now)?
+	else {
+		return Ok(None);
+	};
+
+	let local = |reason: String| Rejection { cost: None, reason };
+	let best_hash = client.info().best_hash;
+	let best = client
+		.header(best_hash)
+		.map_err(|e| local(e.to_string()))?
+		.ok_or_else(|| local(format!("best block {} unknown", best_hash)))?;
+	let slot_authors = crate::slot_authors::<P, B, C>(
+		client,
+		&best,
+		*best.number() + One::one(),
+		compatibility_mode,
+	)
+	.map_err(|e| local(e.to_string()))?;
+	verify_announcement::<P, B>(&announcement, &slot_authors)
+		.map_err(|reason| Rejection::new(rep::INVALID, reason))?;
+
+	Ok(Some((announcement.slot, announcement.number, announcement.hash)))
+}
+
+/// The current slot, according to the slot schedule at the best block.
+///
+/// The schedule is only read again once the best block changed, so that
+/// announcements do not cost a runtime call each.
+fn current_slot<P, B, C>(client: &C, schedule: &mut Option<(B::Hash, SlotSchedule)>) -> Option<Slot>
+where
+	P: Pair,
+	P::Public: Codec,
+	B: BlockT,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B>,
+	C::Api: SpinApi<B, AuthorityId<P>, NumberFor<B>> + SpinSlotScheduleApi<B>,
+{
+	let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_millis().try_into().ok()?;
+	let best_hash = client.info().best_hash;
+	if schedule.as_ref().is_none_or(|(hash, _)| *hash != best_hash) {
+		let at_best =
+			crate::standalone::slot_schedule_at::<AuthorityId<P>, B, C>(client, best_hash, now)
+				.ok()?;
+		*schedule = Some((best_hash, at_best));
+	}
+	schedule.as_ref().map(|(_, schedule)| schedule.slot_at(now))
+}
+
+/// Count an announcement `peer` sent in slot `now`, and return whether it is
+/// within the announcements checked per slot.
+fn note_received(received: &mut HashMap<PeerId, (Slot, u32)>, peer: PeerId, now: Slot) -> bool {
+	let (slot, count) = received.entry(peer).or_insert((now, 0));
+	if *slot != now {
+		*slot = now;
+		*count = 0;
+	}
+	*count += 1;
+	*count <= MAX_ANNOUNCEMENTS_PER_SLOT
+}
+
+/// Drive the hand-off protocol: track the peers and note and forward the valid
+/// announcements.
+pub(crate) async fn run<P, B, C>(
+	handoff: Handoff<B::Hash, NumberFor<B>>,
+	mut notification_service: Box<dyn NotificationService>,
+	peer_store: Arc<dyn PeerStoreProvider>,
+	client: Arc<C>,
+	compatibility_mode: CompatibilityMode<NumberFor<B>>,
+) where
+	P: Pair,
+	P::Public: Codec + std::fmt::Debug,
+	P::Signature: Codec,
+	B: BlockT,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B>,
+	C::Api: SpinApi<B, AuthorityId<P>, NumberFor<B>>
+		+ SpinFallbackApi<B>
+		+ SpinScheduledChangeApi<B, AuthorityId<P>, NumberFor<B>>
+		+ SpinSlotScheduleApi<B>,
+{
+	let mut schedule = None;
+	let mut received = HashMap::new();
+	while let Some(event) = notification_service.next_event().await {
+		match event {
+			NotificationEvent::ValidateInboundSubstream { peer, result_tx, .. } => {
+				let result = if peer_store.is_banned(&peer) {
+					ValidationResult::Reject
+				} else {
+					ValidationResult::Accept
+				};
+				let _ = result_tx.send(result);
+			},
+			NotificationEvent::NotificationStreamOpened { peer, .. } => {
+				if let Some(sink) = notification_service.message_sink(&peer) {
+					handoff.state().peers.insert(peer, sink);
+				}
+			},
+			NotificationEvent::NotificationStreamClosed { peer } => {
+				handoff.state().peers.remove(&peer);
+				received.remove(&peer);
+			},
+			NotificationEvent::NotificationReceived { peer, notification } => {
+				let Some(now) = current_slot::<P, B, C>(&*client, &mut schedule) else {
+					continue;
+				};
+				if !note_received(&mut received, peer, now) {
+					peer_store.report_peer(peer, rep::FLOODING);
+					continue;
+				}
+
+				match check_announcement::<P, B, C>(
+					&*client,
+					&handoff,
+					&notification,
+					now,
+					&compatibility_mode,
+				) {
+					Ok(Some((slot, number, hash))) =>
+						if handoff.note(slot, number, hash) {
+							trace!(
+								target: LOG_TARGET,
+								"Block {} announced for the last slot {} of a tenure",
+								hash,
+								slot,
+							);
+							handoff.broadcast(&notification, Some(&peer));
+						},
+					Ok(None) => {},
+					Err(Rejection { cost, reason }) => {
+						debug!(target: LOG_TARGET, "Invalid hand-off from {}: {}", peer, reason);
+						if let Some(cost) = cost {
+							peer_store.report_peer(peer, cost);
+						}
+					},
+				}
+			},
+		}
+	}
+}
+
+/// A slot worker waiting for the block announced for the previous slot before
+/// authoring with the wrapped [`SlotWorker`], if it was not imported yet.
+///
+/// Only the leader of the slot after the last slot of a tenure may author in
+/// it, so that the others wait at no cost.
+pub(crate) struct HandoffSlotWorker<W, C, Hash, Number> {
+	worker: W,
+	client: Arc<C>,
+	handoff: Option<(Handoff<Hash, Number>, SlotProportion)>,
+}
+
+impl<W, C, Hash, Number> HandoffSlotWorker<W, C, Hash, Number> {
+	pub(crate) fn new(
+		worker: W,
+		client: Arc<C>,
+		handoff: Option<(Handoff<Hash, Number>, SlotProportion)>,
+	) -> Self {
+		Self { worker, client, handoff }
+	}
+}
+
+/// The header of the block announced for the slot before `slot_info`, once it
+/// is imported, if it was not imported before.
+async fn announced_head<B: BlockT, C: HeaderBackend<B>>(
+	client: &C,
+	handoff: &Handoff<B::Hash, NumberFor<B>>,
+	wait: SlotProportion,
+	slot_info: &SlotInfo<B>,
+) -> Option<B::Header> {
+	let (slot, hash) = handoff.announced()?;
+	if slot + 1 != slot_info.slot || client.header(hash).ok().flatten().is_some() {
+		return None;
+	}
+
+	let started = Instant::now();
+	let deadline = (started + slot_info.duration.mul_f32(wait.get())).min(slot_info.ends_at);
+	while Instant::now() < deadline {
+		futures_timer::Delay::new(POLL_INTERVAL).await;
+		if let Ok(Some(header)) = client.header(hash) {
+			debug!(
+				target: LOG_TARGET,
+				"Waited {:?} for block {} of the previous leader before slot {}",
+				started.elapsed(),
+				hash,
+				slot_info.slot,
+			);
+			return Some(header);
+		}
+	}
+
+	debug!(
+		target: LOG_TARGET,
+		"Block {} of the previous leader not imported before slot {}",
+		hash,
+		slot_info.slot,
+	);
+	None
+}
+
+#[async_trait::async_trait]
+impl<B, W, C, Proof> SlotWorker<B, Proof> for HandoffSlotWorker<W, C, B::Hash, NumberFor<B>>
+where
+	B: BlockT,
+	W: SlotWorker<B, Proof> + Send,
+	C: HeaderBackend<B> + Send + Sync,
+{
+	async fn on_slot(&mut self, mut slot_info: SlotInfo<B>) -> Option<SlotResult<B, Proof>> {
+		if let Some((handoff, wait)) = &self.handoff {
+			if let Some(header) = announced_head(&*self.client, handoff, *wait, &slot_info).await {
+				slot_info.chain_head = header;
+			}
+		}
+		self.worker.on_slot(slot_info).await
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use qfp_consensus_spin::sr25519::{AuthorityPair, AuthoritySignature};
+	use sp_core::H256;
+	use sp_keyring::sr25519::Keyring;
+	use sp_runtime::testing::{Block as TestBlock, MockCallU64, TestXt};
+
+	type TestBlockType = TestBlock<TestXt<MockCallU64, ()>>;
+
+	fn announcement(
+		author: Keyring,
+		slot: u64,
+		number: u64,
+		hash: H256,
+	) -> Announcement<H256, u64, AuthoritySignature> {
+		let slot = Slot::from(slot);
+		let payload = signing_payload(&PreDigest::Primary(slot), &number, &hash);
+		let signature = AuthorityPair::from(author.pair()).sign(&payload);
+		Announcement { slot, secondary: None, number, hash, signature }
+	}
+
+	#[test]
+	fn later_announcements_replace_earlier_ones() {
+		assert!(last_slot_of_tenure(Slot::from(9), 10));
+		assert!(!last_slot_of_tenure(Slot::from(10), 10));
+		assert!(!last_slot_of_tenure(Slot::from(9), 0));
+
+		let handoff = Handoff::<H256, u32>::new();
+		assert_eq!(handoff.announced(), None);
+		assert!(handoff.note(Slot::from(9), 9, H256::repeat_byte(1)));
+		// a later block of the same slot replaces the announced one.
+		assert!(handoff.note(Slot::from(9), 10, H256::repeat_byte(2)));
+		assert!(!handoff.note(Slot::from(9), 10, H256::repeat_byte(3)));
+		assert!(!handoff.note(Slot::from(9), 8, H256::repeat_byte(4)));
+		assert_eq!(handoff.announced(), Some((Slot::from(9), H256::repeat_byte(2))));
+		assert!(handoff.note(Slot::from(19), 11, H256::repeat_byte(5)));
+	}
+
+	#[test]
+	fn announcements_of_slots_far_from_now_are_rejected_before_runtime_calls() {
+		let handoff = Handoff::<H256, u64>::new();
+		let now = Slot::from(100);
+		let precheck = |announcement: &[u8]| {
+			precheck_announcement::<_, _, AuthoritySignature>(&handoff, announcement, now)
+		};
+
+		let far_future = announcement(Keyring::Alice, 1_000_000, 10, H256::repeat_byte(1));
+		let rejection = precheck(&far_future.encode()).unwrap_err();
+		assert_eq!(rejection.cost, Some(rep::SLOT_OUT_OF_RANGE));
+		let past = announcement(Keyring::Alice, 98, 10, H256::repeat_byte(1));
+		assert_eq!(precheck(&past.encode()).unwrap_err().cost, Some(rep::SLOT_OUT_OF_RANGE));
+		assert_eq!(precheck(&[1, 2, 3]).unwrap_err().cost, Some(rep::UNDECODABLE));
+		// a far-future announcement never got to pin the announced block.
+		assert_eq!(handoff.announced(), None);
+
+		for slot in [99, 100, 101] {
+			let near = announcement(Keyring::Alice, slot, 10, H256::repeat_byte(1));
+			assert!(precheck(&near.encode()).unwrap().is_some());
+		}
+		assert!(handoff.note(Slot::from(101), 10, H256::repeat_byte(1)));
+		let earlier = announcement(Keyring::Alice, 100, 10, H256::repeat_byte(2));
+		assert!(precheck(&earlier.encode()).unwrap().is_none());
+	}
+
+	#[test]
+	fn announcements_must_be_signed_by_the_author_of_the_slot() {
+		let slot_authors = SlotAuthors {
+			authorities: vec![Keyring::Alice.public().into()],
+			session_length: 10,
+			leader_order: Vec::new(),
+			disabled: Vec::new(),
+			fallback_after: 0,
+			parent: PreDigest::Primary(Slot::from(8)),
+		};
+		let verify = |announcement: &Announcement<H256, u64, AuthoritySignature>| {
+			verify_announcement::<AuthorityPair, TestBlockType>(announcement, &slot_authors)
+		};
+
+		assert!(verify(&announcement(Keyring::Alice, 9, 10, H256::repeat_byte(1))).is_ok());
+		// signed by another key.
+		let forged = announcement(Keyring::Bob, 9, 10, H256::repeat_byte(1));
+		assert!(verify(&forged).unwrap_err().starts_with("bad signature"));
+		// signed for another block.
+		let mut tampered = announcement(Keyring::Alice, 9, 10, H256::repeat_byte(1));
+		tampered.hash = H256::repeat_byte(2);
+		assert!(verify(&tampered).unwrap_err().starts_with("bad signature"));
+		// not the last slot of a tenure.
+		assert!(verify(&announcement(Keyring::Alice, 8, 10, H256::repeat_byte(1))).is_err());
+	}
+}
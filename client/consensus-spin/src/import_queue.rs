@@ -5,104 +5,109 @@
 //! Module implementing the logic for verifying and importing SPIN blocks.
 
 use crate::{
-	aux_data, standalone::SealVerificationError, AuthorityId, CompatibilityMode, Error,
-	SpinAuxData, LOG_TARGET,
+	apply_fork_choice, clock_drift::ClockDrift, metrics::ImportMetrics, slot_authors,
+	standalone::SealVerificationError, AuthorityId, CompatibilityMode, Error, PreDigest,
+	SlotAuthors, SlotSchedule, LOG_TARGET,
 };
 use codec::Codec;
-use log::{debug, info, trace};
+use futures::{future, FutureExt};
+use futures_timer::Delay;
+use log::{debug, info, trace, warn};
 use prometheus_endpoint::Registry;
-use qfp_consensus_spin::{inherents::SpinInherentData, SpinApi};
+use qfp_consensus_spin::{
+	inherents::SpinInherentData, SpinApi, SpinEquivocationApi, SpinFallbackApi,
+	SpinScheduledChangeApi, SpinSlotScheduleApi,
+};
 use sc_client_api::{backend::AuxStore, BlockOf, UsageProvider};
 use sc_consensus::{
-	block_import::{BlockImport, BlockImportParams, ForkChoiceStrategy},
-	import_queue::{BasicQueue, BoxJustificationImport, DefaultImportQueue, Verifier},
+	block_import::{BlockImport, BlockImportParams, ForkChoiceStrategy, StateAction},
+	import_queue::{
+		BasicQueue, BlockImportError, BlockImportResult, BoxJustificationImport, ImportQueue,
+		ImportQueueService, IncomingBlock, Link, RuntimeOrigin, Verifier,
+	},
 };
 use sc_consensus_slots::{check_equivocation, CheckedHeader, InherentDataProviderExt};
 use sc_telemetry::{telemetry, TelemetryHandle, CONSENSUS_DEBUG, CONSENSUS_TRACE};
+use sc_transaction_pool_api::OffchainTransactionPoolFactory;
 use sp_api::{ApiExt, ProvideRuntimeApi};
 use sp_block_builder::BlockBuilder as BlockBuilderApi;
 use sp_blockchain::HeaderBackend;
-use sp_consensus::Error as ConsensusError;
+use sp_consensus::{BlockOrigin, Error as ConsensusError};
 use sp_consensus_slots::Slot;
 use sp_core::crypto::Pair;
 use sp_inherents::{CreateInherentDataProviders, InherentDataProvider as _};
 use sp_runtime::{
-	traits::{Block as BlockT, Header, NumberFor},
+	traits::{Block as BlockT, Header, NumberFor, SaturatedConversion, Zero},
 	DigestItem,
 };
-use std::{fmt::Debug, marker::PhantomData, sync::Arc};
+use std::{
+	collections::HashSet,
+	fmt::Debug,
+	marker::PhantomData,
+	mem,
+	sync::{Arc, Mutex, MutexGuard, PoisonError},
+	task::{Context, Poll, Waker},
+	time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 
 /// check a header has been signed by the right key. If the slot is too far in
 /// the future, an error will be returned. If it's successful, returns the
 /// pre-header and the digest item containing the seal.
 ///
 /// This digest item will always return `Some` when used with `as_spin_seal`.
-fn check_header<C, B: BlockT, P: Pair>(
-	client: &C,
+fn check_header<B: BlockT, P: Pair>(
 	slot_now: Slot,
 	header: B::Header,
 	hash: B::Hash,
-	aux_data: &SpinAuxData<AuthorityId<P>, NumberFor<B>>,
-	check_for_equivocation: CheckForEquivocation,
-) -> Result<CheckedHeader<B::Header, (Slot, DigestItem)>, Error<B>>
+	slot_authors: &SlotAuthors<AuthorityId<P>, NumberFor<B>>,
+) -> Result<CheckedHeader<B::Header, (PreDigest, DigestItem)>, Error<B>>
 where
 	P::Public: Codec,
 	P::Signature: Codec,
-	C: sc_client_api::backend::AuxStore,
 {
 	let check_result =
-		crate::standalone::check_header_slot_and_seal::<B, P>(slot_now, header, aux_data);
+		crate::standalone::check_header_slot_and_seal::<B, P>(slot_now, header, slot_authors);
 
 	match check_result {
-		Ok((header, slot, seal)) => {
-			let (authorities, session_index) = aux_data;
-			let expected_author =
-				crate::standalone::slot_author::<B, P>(slot, *session_index, &authorities);
-			let should_equiv_check = check_for_equivocation.check_for_equivocation();
-			if let (true, Some(expected)) = (should_equiv_check, expected_author) {
-				if let Some(equivocation_proof) =
-					check_equivocation(client, slot_now, slot, &header, expected)
-						.map_err(Error::Client)?
-				{
-					info!(
-						target: LOG_TARGET,
-						"Slot author is equivocating at slot {} with headers {:?} and {:?}",
-						slot,
-						equivocation_proof.first_header.hash(),
-						equivocation_proof.second_header.hash(),
-					);
-				}
-			}
-
-			Ok(CheckedHeader::Checked(header, (slot, seal)))
-		},
+		Ok((header, pre_digest, seal)) => Ok(CheckedHeader::Checked(header, (pre_digest, seal))),
 		Err(SealVerificationError::Deferred(header, slot)) =>
 			Ok(CheckedHeader::Deferred(header, slot)),
 		Err(SealVerificationError::Unsealed) => Err(Error::HeaderUnsealed(hash)),
 		Err(SealVerificationError::BadSeal) => Err(Error::HeaderBadSeal(hash)),
 		Err(SealVerificationError::BadSignature) => Err(Error::BadSignature(hash)),
 		Err(SealVerificationError::SlotAuthorNotFound) => Err(Error::SlotAuthorNotFound),
+		Err(SealVerificationError::SecondaryNotAllowed) => Err(Error::SecondaryNotAllowed(hash)),
 		Err(SealVerificationError::InvalidPreDigest(e)) => Err(Error::from(e)),
 	}
 }
 
 /// A verifier for SPIN blocks.
-pub struct SpinVerifier<C, P, CIDP, N> {
+pub struct SpinVerifier<B: BlockT, C, P, CIDP> {
 	client: Arc<C>,
 	create_inherent_data_providers: CIDP,
 	check_for_equivocation: CheckForEquivocation,
 	telemetry: Option<TelemetryHandle>,
-	compatibility_mode: CompatibilityMode<N>,
+	compatibility_mode: CompatibilityMode<NumberFor<B>>,
+	offchain_tx_pool_factory: OffchainTransactionPoolFactory<B>,
+	future_blocks: Option<Arc<FutureBlocks<B>>>,
+	clock_drift: ClockDrift,
+	max_blocks_per_slot: u32,
+	metrics: Option<ImportMetrics>,
 	_phantom: PhantomData<fn() -> P>,
 }
 
-impl<C, P, CIDP, N> SpinVerifier<C, P, CIDP, N> {
+impl<B: BlockT, C, P, CIDP> SpinVerifier<B, C, P, CIDP> {
 	pub(crate) fn new(
 		client: Arc<C>,
 		create_inherent_data_providers: CIDP,
 		check_for_equivocation: CheckForEquivocation,
 		telemetry: Option<TelemetryHandle>,
-		compatibility_mode: CompatibilityMode<N>,
+		compatibility_mode: CompatibilityMode<NumberFor<B>>,
+		offchain_tx_pool_factory: OffchainTransactionPoolFactory<B>,
+		future_blocks: Option<Arc<FutureBlocks<B>>>,
+		clock_drift: ClockDrift,
+		max_blocks_per_slot: u32,
+		metrics: Option<ImportMetrics>,
 	) -> Self {
 		Self {
 			client,
@@ -110,16 +115,21 @@ impl<C, P, CIDP, N> SpinVerifier<C, P, CIDP, N> {
 			check_for_equivocation,
 			telemetry,
 			compatibility_mode,
+			offchain_tx_pool_factory,
+			future_blocks,
+			clock_drift,
+			max_blocks_per_slot: max_blocks_per_slot.max(1),
+			metrics,
 			_phantom: PhantomData,
 		}
 	}
 }
 
-impl<C, P, CIDP, N> SpinVerifier<C, P, CIDP, N>
+impl<B: BlockT, C, P, CIDP> SpinVerifier<B, C, P, CIDP>
 where
 	CIDP: Send,
 {
-	async fn check_inherents<B: BlockT>(
+	async fn check_inherents(
 		&self,
 		block: B,
 		at_hash: B::Hash,
@@ -148,13 +158,161 @@ where
 
 		Ok(())
 	}
+
+	/// Check whether `author` already authored another header at `slot`. If so,
+	/// report the equivocation to the runtime, which submits an unsigned
+	/// extrinsic on top of our best block.
+	fn check_and_report_equivocation(
+		&self,
+		slot_now: Slot,
+		slot: Slot,
+		header: &B::Header,
+		author: &AuthorityId<P>,
+		origin: &BlockOrigin,
+	) -> Result<(), Error<B>>
+	where
+		C: ProvideRuntimeApi<B> + HeaderBackend<B> + AuxStore,
+		C::Api: SpinEquivocationApi<B, AuthorityId<P>>,
+		P: Pair,
+		P::Public: Codec + Debug,
+	{
+		// don't report any equivocations during initial sync
+		// as they are most likely stale.
+		if !self.check_for_equivocation.check_for_equivocation() ||
+			*origin == BlockOrigin::NetworkInitialSync
+		{
+			return Ok(());
+		}
+
+		// check if authorship of this header is an equivocation and return a proof if so.
+		let Some(equivocation_proof) =
+			check_equivocation(&*self.client, slot_now, slot, header, author)
+				.map_err(Error::Client)?
+		else {
+			return Ok(());
+		};
+
+		// blocks an author built on top of each other in its slot are fine.
+		if self.max_blocks_per_slot > 1 &&
+			builds_on::<B>(
+				|hash| self.client.header(hash),
+				&equivocation_proof.second_header,
+				&equivocation_proof.first_header,
+				self.max_blocks_per_slot,
+			)
+			.map_err(Error::Client)?
+		{
+			return Ok(());
+		}
+
+		if let Some(metrics) = &self.metrics {
+			metrics.note_equivocation();
+		}
+
+		info!(
+			target: LOG_TARGET,
+			"Slot author {:?} is equivocating at slot {} with headers {:?} and {:?}",
+			author,
+			slot,
+			equivocation_proof.first_header.hash(),
+			equivocation_proof.second_header.hash(),
+		);
+
+		// the key ownership proof is generated for the current session, so the
+		// report has to be built on top of our best block.
+		let best_hash = self.client.info().best_hash;
+		let mut runtime_api = self.client.runtime_api();
+
+		if !runtime_api
+			.has_api::<dyn SpinEquivocationApi<B, AuthorityId<P>>>(best_hash)
+			.map_err(|e| Error::Client(e.into()))?
+		{
+			debug!(target: LOG_TARGET, "Runtime does not support equivocation reports.");
+			return Ok(());
+		}
+
+		// Register the offchain tx pool to be able to use it from the runtime.
+		runtime_api
+			.register_extension(self.offchain_tx_pool_factory.offchain_transaction_pool(best_hash));
+
+		let Some(key_owner_proof) = runtime_api
+			.generate_key_ownership_proof(best_hash, slot, equivocation_proof.offender.clone())
+			.map_err(|e| Error::Client(e.into()))?
+		else {
+			debug!(target: LOG_TARGET, "Equivocation offender is not part of the authority set.");
+			return Ok(());
+		};
+
+		let submitted = runtime_api
+			.submit_report_equivocation_unsigned_extrinsic(
+				best_hash,
+				equivocation_proof,
+				key_owner_proof,
+			)
+			.map_err(|e| Error::Client(e.into()))?;
+
+		if submitted.is_some() {
+			info!(target: LOG_TARGET, "Submitted equivocation report for author {:?}", author);
+		} else {
+			warn!(target: LOG_TARGET, "Runtime refused equivocation report for author {:?}", author);
+		}
+
+		Ok(())
+	}
+
+	/// Note the import of a block of `slot` in the import latency and the
+	/// clock offset, according to the slot schedule on top of `parent_hash`.
+	fn note_slot_timing(&self, parent_hash: B::Hash, slot: Slot)
+	where
+		C: ProvideRuntimeApi<B>,
+		C::Api: SpinApi<B, AuthorityId<P>, NumberFor<B>> + SpinSlotScheduleApi<B>,
+		P: Pair,
+	{
+		let Ok(now) = SystemTime::now().duration_since(UNIX_EPOCH) else { return };
+		let Ok(schedule) = crate::standalone::slot_schedule_at::<AuthorityId<P>, B, C>(
+			self.client.as_ref(),
+			parent_hash,
+			now.as_millis().saturated_into(),
+		) else {
+			return;
+		};
+
+		sample_slot_timing(&self.clock_drift, self.metrics.as_ref(), &schedule, slot, now);
+	}
+
+	/// Time until a header of `slot` is no longer too far in the future,
+	/// according to the slot schedule on top of `parent_hash`.
+	fn until_acceptable(&self, parent_hash: B::Hash, slot: Slot) -> Option<Duration>
+	where
+		C: ProvideRuntimeApi<B>,
+		C::Api: SpinApi<B, AuthorityId<P>, NumberFor<B>> + SpinSlotScheduleApi<B>,
+		P: Pair,
+	{
+		let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?;
+		let schedule = crate::standalone::slot_schedule_at::<AuthorityId<P>, B, C>(
+			self.client.as_ref(),
+			parent_hash,
+			now.as_millis().try_into().ok()?,
+		)
+		.ok()?;
+
+		// a header is accepted from the slot before its own on.
+		let acceptable_at = Duration::from_millis(schedule.slot_start(slot.saturating_sub(1u64)));
+		Some(acceptable_at.saturating_sub(now))
+	}
 }
 
 #[async_trait::async_trait]
-impl<B: BlockT, C, P, CIDP> Verifier<B> for SpinVerifier<C, P, CIDP, NumberFor<B>>
+impl<B: BlockT, C, P, CIDP> Verifier<B> for SpinVerifier<B, C, P, CIDP>
 where
-	C: ProvideRuntimeApi<B> + Send + Sync + sc_client_api::backend::AuxStore,
-	C::Api: BlockBuilderApi<B> + SpinApi<B, AuthorityId<P>, NumberFor<B>> + ApiExt<B>,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B> + Send + Sync + AuxStore,
+	C::Api: BlockBuilderApi<B>
+		+ SpinApi<B, AuthorityId<P>, NumberFor<B>>
+		+ SpinFallbackApi<B>
+		+ SpinScheduledChangeApi<B, AuthorityId<P>, NumberFor<B>>
+		+ SpinSlotScheduleApi<B>
+		+ SpinEquivocationApi<B, AuthorityId<P>>
+		+ ApiExt<B>,
 	P: Pair,
 	P::Public: Codec + Debug,
 	P::Signature: Codec,
@@ -181,9 +339,17 @@ where
 
 		let hash = block.header.hash();
 		let parent_hash = *block.header.parent_hash();
-		let aux_data = aux_data(
+		// a block imported again after it was held back is not held back twice.
+		let released =
+			self.future_blocks.as_ref().is_some_and(|blocks| blocks.take_released(&hash));
+		let parent_header = self
+			.client
+			.header(parent_hash)
+			.map_err(|e| e.to_string())?
+			.ok_or_else(|| format!("Parent header {:?} not found", parent_hash))?;
+		let slot_authors = slot_authors::<P, _, _>(
 			self.client.as_ref(),
-			parent_hash,
+			&parent_header,
 			*block.header.number(),
 			&self.compatibility_mode,
 		)
@@ -203,19 +369,48 @@ where
 		let slot_now = create_inherent_data_providers.slot();
 
 		// we add one to allow for some small drift.
-		// FIXME #1019 in the future, alter this queue to allow deferring of
-		// headers
-		let checked_header = check_header::<C, B, P>(
-			&self.client,
-			slot_now + 1,
-			block.header,
-			hash,
-			&aux_data,
-			self.check_for_equivocation,
-		)
-		.map_err(|e| e.to_string())?;
+		let checked_header = check_header::<B, P>(slot_now + 1, block.header, hash, &slot_authors)
+			.map_err(|e| e.to_string())?;
 		match checked_header {
-			CheckedHeader::Checked(pre_header, (slot, seal)) => {
+			CheckedHeader::Checked(pre_header, (pre_digest, seal)) => {
+				let slot = pre_digest.slot();
+
+				// the author of a slot may build on its own blocks of the slot, but
+				// only on so many of them.
+				let built = blocks_in_slot::<B, P::Signature>(
+					|hash| self.client.header(hash),
+					parent_hash,
+					&pre_digest,
+					self.max_blocks_per_slot,
+				)
+				.map_err(|e| e.to_string())?;
+				if built >= self.max_blocks_per_slot {
+					return Err(format!(
+						"Rejecting block {:?}: its author built more than {} blocks in slot {}",
+						hash, self.max_blocks_per_slot, slot,
+					));
+				}
+
+				// the header is valid but let's check if there was something else already
+				// proposed at the same slot by the given author. if there was, we will
+				// report the equivocation to the runtime.
+				if let Some(author) =
+					crate::standalone::expected_author::<B, P>(&pre_digest, &slot_authors)
+				{
+					if let Err(err) = self.check_and_report_equivocation(
+						slot_now + 1,
+						slot,
+						&pre_header,
+						author,
+						&block.origin,
+					) {
+						warn!(
+							target: LOG_TARGET,
+							"Error checking/reporting SPIN equivocation: {}", err
+						);
+					}
+				}
+
 				// if the body is passed through, we need to use the runtime
 				// to check that the internally-set timestamp in the inherents
 				// actually matches the slot set in the seal.
@@ -256,13 +451,38 @@ where
 
 				block.header = pre_header;
 				block.post_digests.push(seal);
-				block.fork_choice = Some(ForkChoiceStrategy::LongestChain);
 				block.post_hash = Some(hash);
+				apply_fork_choice(self.client.as_ref(), &mut block, pre_digest.is_primary())
+					.map_err(|e| e.to_string())?;
+
+				// blocks of the initial sync were sealed long ago.
+				if block.origin != BlockOrigin::NetworkInitialSync {
+					self.note_slot_timing(parent_hash, slot);
+				}
 
 				Ok(block)
 			},
 			CheckedHeader::Deferred(a, b) => {
 				debug!(target: LOG_TARGET, "Checking {:?} failed; {:?}, {:?}.", hash, a, b);
+				if let Some(metrics) = &self.metrics {
+					metrics.note_deferred();
+				}
+
+				// blocks within the drift window are held back until local time
+				// catches up and imported again, without holding up the queue.
+				if let Some(future_blocks) = self.future_blocks.as_ref().filter(|_| !released) {
+					let max_drift = future_blocks.limits.max_drift_slots;
+					let delay = (b <= slot_now.saturating_add(1u64).saturating_add(max_drift))
+						.then(|| self.until_acceptable(parent_hash, b))
+						.flatten();
+					if let Some(delay) = delay {
+						block.header = a.clone();
+						if future_blocks.hold(hash, b, delay, block) {
+							return Err(format!("Header {:?} held back until slot {}", hash, b));
+						}
+					}
+				}
+
 				telemetry!(
 					self.telemetry;
 					CONSENSUS_DEBUG;
@@ -277,6 +497,352 @@ where
 	}
 }
 
+/// Number of blocks claimed with `pre_digest` on top of each other, up to and
+/// including `parent_hash`, counting at most `max` of them.
+fn blocks_in_slot<B: BlockT, Signature: Codec>(
+	header: impl Fn(B::Hash) -> sp_blockchain::Result<Option<B::Header>>,
+	parent_hash: B::Hash,
+	pre_digest: &PreDigest,
+	max: u32,
+) -> sp_blockchain::Result<u32> {
+	let mut blocks = 0;
+	let mut hash = parent_hash;
+	while blocks < max {
+		let parent = header(hash)?
+			.ok_or_else(|| sp_blockchain::Error::UnknownBlock(format!("{:?}", hash)))?;
+		if parent.number().is_zero() {
+			break;
+		}
+		match crate::standalone::find_full_pre_digest::<B, Signature>(&parent) {
+			Ok(claim) if claim == *pre_digest => blocks += 1,
+			_ => break,
+		}
+		hash = *parent.parent_hash();
+	}
+
+	Ok(blocks)
+}
+
+/// Note that a block of `slot` was imported at `now`, since the Unix epoch, in
+/// the estimate of the clock offset and the import latency.
+pub(crate) fn sample_slot_timing(
+	clock_drift: &ClockDrift,
+	metrics: Option<&ImportMetrics>,
+	schedule: &SlotSchedule,
+	slot: Slot,
+	now: Duration,
+) {
+	let slot_start = Duration::from_millis(schedule.slot_start(slot));
+	let since_slot_start = match now.checked_sub(slot_start) {
+		Some(latency) => latency.as_millis().saturated_into(),
+		None => -(slot_start - now).as_millis().saturated_into::<i64>(),
+	};
+	let offset = clock_drift.note(since_slot_start, schedule.slot_duration);
+
+	if let Some(metrics) = metrics {
+		if let Some(latency) = now.checked_sub(slot_start) {
+			metrics.note_import(latency);
+		}
+		if let Some(offset) = offset {
+			metrics.note_clock_offset(offset.millis);
+		}
+	}
+}
+
+/// Whether the unsealed `ancestor` is one of the `max` ancestors of `child`.
+fn builds_on<B: BlockT>(
+	header: impl Fn(B::Hash) -> sp_blockchain::Result<Option<B::Header>>,
+	child: &B::Header,
+	ancestor: &B::Header,
+	max: u32,
+) -> sp_blockchain::Result<bool> {
+	let mut hash = *child.parent_hash();
+	for _ in 0..max {
+		let Some(mut parent) = header(hash)? else { return Ok(false) };
+		if parent.number() <= ancestor.number() {
+			// headers of equivocation proofs are compared without their seal.
+			parent.digest_mut().pop();
+			return Ok(parent.hash() == ancestor.hash());
+		}
+		hash = *parent.parent_hash();
+	}
+
+	Ok(false)
+}
+
+/// Limits of the buffer that holds back blocks of future slots.
+#[derive(Debug, Clone, Copy)]
+pub struct FutureHeaderLimits {
+	/// Number of slots past the next one the slot of a header may be ahead of
+	/// local time and still be held back rather than rejected.
+	pub max_drift_slots: u64,
+	/// Maximum number of blocks held back at once. Zero rejects every header
+	/// of a future slot.
+	pub capacity: usize,
+}
+
+impl Default for FutureHeaderLimits {
+	fn default() -> Self {
+		Self { max_drift_slots: 5, capacity: 16 }
+	}
+}
+
+/// A block held back until its slot starts.
+struct FutureBlock<B: BlockT> {
+	slot: Slot,
+	ready_at: Instant,
+	origin: BlockOrigin,
+	block: IncomingBlock<B>,
+}
+
+/// Blocks of future slots held back until their slot starts.
+///
+/// The verifier fails the verification of a block it holds back, and
+/// [`SpinImportQueue`] keeps that failure from the network and imports the
+/// block again once it is ready.
+struct FutureBlocks<B: BlockT> {
+	limits: FutureHeaderLimits,
+	state: Mutex<FutureBlocksState<B>>,
+	metrics: Option<ImportMetrics>,
+}
+
+struct FutureBlocksState<B: BlockT> {
+	held: Vec<FutureBlock<B>>,
+	/// Held back blocks whose failed verification is not reported.
+	deferred: HashSet<B::Hash>,
+	/// Blocks evicted from the buffer whose failure is still to be reported.
+	evicted: Vec<B::Hash>,
+	/// Blocks imported again, which are not held back a second time.
+	released: HashSet<B::Hash>,
+	/// Wakes the import queue once a block is held back.
+	waker: Option<Waker>,
+}
+
+impl<B: BlockT> FutureBlocks<B> {
+	fn new(limits: FutureHeaderLimits, metrics: Option<ImportMetrics>) -> Self {
+		Self {
+			limits,
+			state: Mutex::new(FutureBlocksState {
+				held: Vec::new(),
+				deferred: HashSet::new(),
+				evicted: Vec::new(),
+				released: HashSet::new(),
+				waker: None,
+			}),
+			metrics,
+		}
+	}
+
+	fn state(&self) -> MutexGuard<'_, FutureBlocksState<B>> {
+		self.state.lock().unwrap_or_else(PoisonError::into_inner)
+	}
+
+	/// Hold back `block` of `slot` for `delay`.
+	///
+	/// If the buffer is full, the block of the furthest slot is evicted.
+	/// Returns whether the block is held back.
+	fn hold(
+		&self,
+		hash: B::Hash,
+		slot: Slot,
+		delay: Duration,
+		block: BlockImportParams<B>,
+	) -> bool {
+		let mut state = self.state();
+		if state.held.len() >= self.limits.capacity {
+			if let Some(metrics) = &self.metrics {
+				metrics.note_future_header_evicted();
+			}
+
+			let furthest = state.held.iter().enumerate().max_by_key(|(_, held)| held.slot);
+			match furthest.map(|(index, held)| (index, held.slot)) {
+				Some((index, furthest)) if furthest > slot => {
+					let evicted = state.held.swap_remove(index);
+					state.evicted.push(evicted.block.hash);
+				},
+				_ => return false,
+			}
+		}
+
+		state.held.push(FutureBlock {
+			slot,
+			ready_at: Instant::now() + delay,
+			origin: block.origin,
+			block: IncomingBlock {
+				hash,
+				header: Some(block.header),
+				body: block.body,
+				indexed_body: block.indexed_body,
+				justifications: block.justifications,
+				origin: None,
+				allow_missing_state: matches!(block.state_action, StateAction::ExecuteIfPossible),
+				skip_execution: false,
+				import_existing: block.import_existing,
+				state: None,
+			},
+		});
+		state.deferred.insert(hash);
+		self.note_held(&state);
+		if let Some(waker) = state.waker.take() {
+			waker.wake();
+		}
+
+		true
+	}
+
+	/// Take the blocks that are ready to be imported again at `now`, and the
+	/// time the next block will be ready at. `waker` is woken once another
+	/// block is held back.
+	fn take_ready(&self, now: Instant, waker: &Waker) -> (Vec<FutureBlock<B>>, Option<Instant>) {
+		let mut state = self.state();
+		state.waker = Some(waker.clone());
+
+		let (ready, held): (Vec<_>, _) =
+			mem::take(&mut state.held).into_iter().partition(|held| held.ready_at <= now);
+		state.held = held;
+		state.released.extend(ready.iter().map(|ready| ready.block.hash));
+		self.note_held(&state);
+
+		(ready, state.held.iter().map(|held| held.ready_at).min())
+	}
+
+	/// Whether `hash` was imported again, forgetting about it.
+	fn take_released(&self, hash: &B::Hash) -> bool {
+		self.state().released.remove(hash)
+	}
+
+	/// The import results of evicted blocks.
+	fn take_evicted(&self) -> Vec<(BlockImportResult<B>, B::Hash)> {
+		mem::take(&mut self.state().evicted)
+			.into_iter()
+			.map(|hash| {
+				let error = format!("Header {:?} rejected: too many headers of future slots", hash);
+				(Err(BlockImportError::VerificationFailed(None, error)), hash)
+			})
+			.collect()
+	}
+
+	/// Drop the failed verifications of held back blocks from `results`.
+	fn filter_results(
+		&self,
+		results: Vec<(BlockImportResult<B>, B::Hash)>,
+	) -> Vec<(BlockImportResult<B>, B::Hash)> {
+		let mut state = self.state();
+		if state.deferred.is_empty() {
+			return results;
+		}
+
+		results
+			.into_iter()
+			.filter(|(result, hash)| {
+				!(matches!(result, Err(BlockImportError::VerificationFailed(..))) &&
+					state.deferred.remove(hash))
+			})
+			.collect()
+	}
+
+	fn note_held(&self, state: &FutureBlocksState<B>) {
+		if let Some(metrics) = &self.metrics {
+			metrics.note_future_headers(state.held.len());
+		}
+	}
+}
+
+/// A [`Link`] that hides the failed verifications of held back blocks.
+struct FutureBlocksLink<'a, B: BlockT> {
+	link: &'a dyn Link<B>,
+	future_blocks: &'a FutureBlocks<B>,
+}
+
+impl<B: BlockT> Link<B> for FutureBlocksLink<'_, B> {
+	fn blocks_processed(
+		&self,
+		imported: usize,
+		_count: usize,
+		results: Vec<(BlockImportResult<B>, B::Hash)>,
+	) {
+		let results = self.future_blocks.filter_results(results);
+		if !results.is_empty() {
+			self.link.blocks_processed(imported, results.len(), results);
+		}
+	}
+
+	fn justification_imported(
+		&self,
+		who: RuntimeOrigin,
+		hash: &B::Hash,
+		number: NumberFor<B>,
+		success: bool,
+	) {
+		self.link.justification_imported(who, hash, number, success);
+	}
+
+	fn request_justification(&self, hash: &B::Hash, number: NumberFor<B>) {
+		self.link.request_justification(hash, number);
+	}
+}
+
+/// The import queue of SPIN.
+///
+/// A [`BasicQueue`] that imports blocks of future slots again once their
+/// slot starts, rather than rejecting them.
+pub struct SpinImportQueue<B: BlockT> {
+	inner: BasicQueue<B>,
+	future_blocks: Arc<FutureBlocks<B>>,
+	next_ready: Option<Delay>,
+}
+
+impl<B: BlockT> SpinImportQueue<B> {
+	/// Import the held back blocks that are ready again.
+	fn import_ready(&mut self, cx: &mut Context) {
+		loop {
+			let (ready, next_ready) = self.future_blocks.take_ready(Instant::now(), cx.waker());
+			for FutureBlock { origin, block, .. } in ready {
+				trace!(target: LOG_TARGET, "Importing held back block {:?}.", block.hash);
+				self.inner.service_ref().import_blocks(origin, vec![block]);
+			}
+
+			self.next_ready = next_ready
+				.map(|ready_at| Delay::new(ready_at.saturating_duration_since(Instant::now())));
+			match &mut self.next_ready {
+				Some(delay) if delay.poll_unpin(cx).is_ready() => continue,
+				_ => break,
+			}
+		}
+	}
+}
+
+#[async_trait::async_trait]
+impl<B: BlockT> ImportQueue<B> for SpinImportQueue<B> {
+	fn service(&self) -> Box<dyn ImportQueueService<B>> {
+		self.inner.service()
+	}
+
+	fn service_ref(&mut self) -> &mut dyn ImportQueueService<B> {
+		self.inner.service_ref()
+	}
+
+	fn poll_actions(&mut self, cx: &mut Context, link: &dyn Link<B>) {
+		self.import_ready(cx);
+
+		let evicted = self.future_blocks.take_evicted();
+		if !evicted.is_empty() {
+			link.blocks_processed(0, evicted.len(), evicted);
+		}
+
+		let link = FutureBlocksLink { link, future_blocks: &self.future_blocks };
+		self.inner.poll_actions(cx, &link);
+	}
+
+	async fn run(mut self, link: &dyn Link<B>) {
+		future::poll_fn(|cx| {
+			self.poll_actions(cx, link);
+			Poll::<()>::Pending
+		})
+		.await
+	}
+}
+
 /// Should we check for equivocation of a block author?
 #[derive(Debug, Clone, Copy)]
 pub enum CheckForEquivocation {
@@ -313,7 +879,7 @@ pub struct ImportQueueParams<'a, Block: BlockT, I, C, S, CIDP> {
 	pub create_inherent_data_providers: CIDP,
 	/// The spawner to spawn background tasks.
 	pub spawner: &'a S,
-	/// The prometheus registry.
+	/// The prometheus registry of the import queue and the SPIN verifier.
 	pub registry: Option<&'a Registry>,
 	/// Should we check for equivocation?
 	pub check_for_equivocation: CheckForEquivocation,
@@ -323,6 +889,22 @@ pub struct ImportQueueParams<'a, Block: BlockT, I, C, S, CIDP> {
 	///
 	/// If in doubt, use `Default::default()`.
 	pub compatibility_mode: CompatibilityMode<NumberFor<Block>>,
+	/// The offchain transaction pool factory.
+	///
+	/// Will be used when sending equivocation reports.
+	pub offchain_tx_pool_factory: OffchainTransactionPoolFactory<Block>,
+	/// Limits of holding back blocks of future slots.
+	///
+	/// If in doubt, use `Default::default()`.
+	pub future_headers: FutureHeaderLimits,
+	/// The estimated offset of the local clock, sampled from the imported
+	/// blocks. Share it with the slot worker to act on it.
+	pub clock_drift: ClockDrift,
+	/// The most blocks an author may build on top of each other in its slot.
+	///
+	/// More than one requires a runtime that allows multiple blocks per slot.
+	/// If in doubt, use `1`.
+	pub max_blocks_per_slot: u32,
 }
 
 /// Start an import queue for the SPIN consensus algorithm.
@@ -337,12 +919,21 @@ pub fn import_queue<P, Block, I, C, S, CIDP>(
 		check_for_equivocation,
 		telemetry,
 		compatibility_mode,
+		offchain_tx_pool_factory,
+		future_headers,
+		clock_drift,
+		max_blocks_per_slot,
 	}: ImportQueueParams<Block, I, C, S, CIDP>,
-) -> Result<DefaultImportQueue<Block>, sp_consensus::Error>
+) -> Result<SpinImportQueue<Block>, sp_consensus::Error>
 where
 	Block: BlockT,
-	C::Api:
-		BlockBuilderApi<Block> + SpinApi<Block, AuthorityId<P>, NumberFor<Block>> + ApiExt<Block>,
+	C::Api: BlockBuilderApi<Block>
+		+ SpinApi<Block, AuthorityId<P>, NumberFor<Block>>
+		+ SpinFallbackApi<Block>
+		+ SpinScheduledChangeApi<Block, AuthorityId<P>, NumberFor<Block>>
+		+ SpinSlotScheduleApi<Block>
+		+ SpinEquivocationApi<Block, AuthorityId<P>>
+		+ ApiExt<Block>,
 	C: 'static
 		+ ProvideRuntimeApi<Block>
 		+ BlockOf
@@ -359,19 +950,36 @@ where
 	CIDP: CreateInherentDataProviders<Block, ()> + Sync + Send + 'static,
 	CIDP::InherentDataProviders: InherentDataProviderExt + Send + Sync,
 {
-	let verifier = build_verifier::<P, _, _, _>(BuildVerifierParams {
+	let metrics = ImportMetrics::register_optional(registry);
+	let future_blocks = Arc::new(FutureBlocks::new(future_headers, metrics.clone()));
+	let verifier = SpinVerifier::<_, _, P, _>::new(
 		client,
 		create_inherent_data_providers,
 		check_for_equivocation,
 		telemetry,
 		compatibility_mode,
-	});
+		offchain_tx_pool_factory,
+		Some(future_blocks.clone()),
+		clock_drift,
+		max_blocks_per_slot,
+		metrics,
+	);
 
-	Ok(BasicQueue::new(verifier, Box::new(block_import), justification_import, spawner, registry))
+	Ok(SpinImportQueue {
+		inner: BasicQueue::new(
+			verifier,
+			Box::new(block_import),
+			justification_import,
+			spawner,
+			registry,
+		),
+		future_blocks,
+		next_ready: None,
+	})
 }
 
 /// Parameters of [`build_verifier`].
-pub struct BuildVerifierParams<C, CIDP, N> {
+pub struct BuildVerifierParams<B: BlockT, C, CIDP> {
 	/// The client to interact with the chain.
 	pub client: Arc<C>,
 	/// Something that can create the inherent data providers.
@@ -383,24 +991,177 @@ pub struct BuildVerifierParams<C, CIDP, N> {
 	/// Compatibility mode that should be used.
 	///
 	/// If in doubt, use `Default::default()`.
-	pub compatibility_mode: CompatibilityMode<N>,
+	pub compatibility_mode: CompatibilityMode<NumberFor<B>>,
+	/// The offchain transaction pool factory.
+	///
+	/// Will be used when sending equivocation reports.
+	pub offchain_tx_pool_factory: OffchainTransactionPoolFactory<B>,
+	/// The estimated offset of the local clock, sampled from the imported
+	/// blocks.
+	pub clock_drift: ClockDrift,
+	/// The most blocks an author may build on top of each other in its slot.
+	pub max_blocks_per_slot: u32,
+	/// The prometheus registry to register the metrics of the verifier on.
+	pub registry: Option<Registry>,
 }
 
 /// Build the [`SpinVerifier`]
-pub fn build_verifier<P, C, CIDP, N>(
+///
+/// Only the verifier of [`import_queue`] holds back blocks of future slots,
+/// this one rejects them.
+pub fn build_verifier<P, B: BlockT, C, CIDP>(
 	BuildVerifierParams {
 		client,
 		create_inherent_data_providers,
 		check_for_equivocation,
 		telemetry,
 		compatibility_mode,
-	}: BuildVerifierParams<C, CIDP, N>,
-) -> SpinVerifier<C, P, CIDP, N> {
-	SpinVerifier::<_, P, _, _>::new(
+		offchain_tx_pool_factory,
+		clock_drift,
+		max_blocks_per_slot,
+		registry,
+	}: BuildVerifierParams<B, C, CIDP>,
+) -> SpinVerifier<B, C, P, CIDP> {
+	SpinVerifier::<_, _, P, _>::new(
 		client,
 		create_inherent_data_providers,
 		check_for_equivocation,
 		telemetry,
 		compatibility_mode,
+		offchain_tx_pool_factory,
+		None,
+		clock_drift,
+		max_blocks_per_slot,
+		ImportMetrics::register_optional(registry.as_ref()),
 	)
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::standalone::{pre_digest, secondary_pre_digest};
+	use qfp_consensus_spin::{
+		sr25519::{AuthorityPair, AuthoritySignature},
+		SPIN_ENGINE_ID,
+	};
+	use sp_core::H256;
+	use sp_runtime::{
+		testing::{Block as TestBlock, Header as TestHeader, MockCallU64, TestXt},
+		Digest,
+	};
+	use std::collections::HashMap;
+
+	type TestBlockType = TestBlock<TestXt<MockCallU64, ()>>;
+
+	fn primary(slot: u64) -> DigestItem {
+		pre_digest::<AuthorityPair>(slot.into())
+	}
+
+	fn sealed_header(number: u64, parent_hash: H256, claim: DigestItem, root: u8) -> TestHeader {
+		let digest = Digest { logs: vec![claim] };
+		let mut header =
+			TestHeader::new(number, [root; 32].into(), Default::default(), parent_hash, digest);
+		header.digest_mut().push(DigestItem::Seal(SPIN_ENGINE_ID, vec![root]));
+		header
+	}
+
+	#[test]
+	fn blocks_of_a_slot_are_counted_on_their_chain() {
+		let secondary = |slot: u64| secondary_pre_digest::<AuthorityPair>(slot.into(), 1);
+
+		let genesis = TestHeader::new(
+			0,
+			Default::default(),
+			Default::default(),
+			Default::default(),
+			Default::default(),
+		);
+		let first = sealed_header(1, genesis.hash(), primary(3), 1);
+		let second = sealed_header(2, first.hash(), primary(4), 2);
+		let third = sealed_header(3, second.hash(), primary(4), 3);
+		let fourth = sealed_header(4, third.hash(), secondary(4), 4);
+		let sibling = sealed_header(2, first.hash(), primary(4), 5);
+
+		let headers = [&genesis, &first, &second, &third, &fourth, &sibling]
+			.into_iter()
+			.map(|header| (header.hash(), header.clone()))
+			.collect::<HashMap<_, _>>();
+		let lookup = |hash: H256| -> sp_blockchain::Result<_> { Ok(headers.get(&hash).cloned()) };
+		let count = |parent: &TestHeader, claim: PreDigest, max: u32| {
+			blocks_in_slot::<TestBlockType, AuthoritySignature>(lookup, parent.hash(), &claim, max)
+				.unwrap()
+		};
+
+		let leader = PreDigest::Primary(4.into());
+		assert_eq!(count(&third, leader, 3), 2);
+		// counting stops at the limit.
+		assert_eq!(count(&third, leader, 1), 1);
+		// blocks of another author of the slot do not count.
+		assert_eq!(count(&fourth, leader, 3), 0);
+		let stand_in = PreDigest::Secondary { slot: 4.into(), authority_index: 1 };
+		assert_eq!(count(&fourth, stand_in, 3), 1);
+
+		// an earlier block of the slot is no equivocation, as long as it is
+		// within the limit.
+		let mut unsealed = second.clone();
+		unsealed.digest_mut().pop();
+		assert!(builds_on::<TestBlockType>(lookup, &fourth, &unsealed, 3).unwrap());
+		assert!(!builds_on::<TestBlockType>(lookup, &fourth, &unsealed, 1).unwrap());
+
+		// but a block of the slot on another fork is.
+		let mut unsealed = sibling.clone();
+		unsealed.digest_mut().pop();
+		assert!(!builds_on::<TestBlockType>(lookup, &fourth, &unsealed, 3).unwrap());
+	}
+
+	#[test]
+	fn held_back_blocks_are_imported_again_once_ready() {
+		let blocks = FutureBlocks::<TestBlockType>::new(
+			FutureHeaderLimits { max_drift_slots: 10, capacity: 2 },
+			None,
+		);
+		let hold = |root: u8, slot: u64, delay: Duration| {
+			let header = sealed_header(1, Default::default(), primary(slot), root);
+			let hash = header.hash();
+			let block = BlockImportParams::new(BlockOrigin::NetworkBroadcast, header);
+			(hash, blocks.hold(hash, slot.into(), delay, block))
+		};
+		let failed =
+			|hash: H256| (Err(BlockImportError::VerificationFailed(None, "".into())), hash);
+		let hashes = |results: Vec<(BlockImportResult<TestBlockType>, H256)>| {
+			results.into_iter().map(|(_, hash)| hash).collect::<Vec<_>>()
+		};
+
+		let (near, held) = hold(1, 5, Duration::ZERO);
+		assert!(held);
+		let (far, held) = hold(2, 10, Duration::from_secs(60));
+		assert!(held);
+
+		// a further block does not fit.
+		assert!(!hold(3, 15, Duration::ZERO).1);
+		// a nearer block evicts the furthest one, whose failure is reported.
+		let (nearer, held) = hold(4, 4, Duration::ZERO);
+		assert!(held);
+		assert_eq!(hashes(blocks.take_evicted()), vec![far]);
+
+		// the failed verifications of held back blocks are not reported.
+		let other = H256::repeat_byte(9);
+		let results = vec![failed(near), failed(far), failed(nearer), failed(other)];
+		assert_eq!(hashes(blocks.filter_results(results)), vec![other]);
+
+		// the held back blocks are imported again once ready, but only held
+		// back once.
+		let (ready, next) = blocks.take_ready(Instant::now(), futures::task::noop_waker_ref());
+		assert_eq!(
+			ready.iter().map(|ready| ready.block.hash).collect::<Vec<_>>(),
+			vec![near, nearer]
+		);
+		assert!(ready.iter().all(|ready| ready.origin == BlockOrigin::NetworkBroadcast));
+		assert_eq!(next, None);
+		assert!(blocks.take_released(&near));
+		assert!(!blocks.take_released(&near));
+
+		// a failure of the block imported again is reported.
+		assert_eq!(hashes(blocks.filter_results(vec![failed(near)])), vec![near]);
+	}
+}
@@ -7,27 +7,50 @@
 //! SPIN works by having a list of authorities A who are expected to roughly
 //! agree on the current time. Time is divided up into discrete slots of t
 //! seconds each. And slots are grouped into sessions of n slots each. For a
-//! given session s, the author of that slot is A[s % |A|].
+//! given session s, the author of that slot is A[o[s % |o|]], where o is the
+//! order of authority indices the runtime selected for its current session,
+//! by default every index of A in turn. If that authority is disabled, the
+//! next authority in A that is not disabled authors instead.
+//!
+//! The runtime schedules the authorities and the order of the next session a
+//! session ahead. Blocks from the block the change is effective at on are
+//! authored by the next authorities, as read from the state of their parent.
+//!
+//! If the author misses enough slots in a row, the next authority in A may
+//! author the remaining slots of the session as a secondary author. Chains with
+//! more blocks of primary authors are preferred over longer chains.
 //!
 //! The author is allowed to issue one block but not more during that slot and
-//! exactly n blocks are expected to be produced during each session.
+//! exactly n blocks are expected to be produced during each session, unless
+//! the node and the runtime allow an author to build several blocks on top of
+//! each other in its slot.
 //! Blocks will be built upon the longest valid chain that has been seen.
+//! Authors may announce the blocks of the last slot of a tenure to the other
+//! authorities, so that the next leader waits a bounded time for them instead
+//! of building next to them.
 //!
 //! Blocks from future steps will be either deferred or rejected depending on
 //! how far in the future they are.
 //!
 //! NOTE: SPIN and AuRa are designed to be generic over the crypto used.
 #![forbid(missing_docs, unsafe_code)]
-use std::{fmt::Debug, marker::PhantomData, pin::Pin, sync::Arc};
+use std::{
+	fmt::Debug,
+	marker::PhantomData,
+	pin::Pin,
+	sync::{Arc, Mutex},
+	time::{Duration, Instant},
+};
 
 use codec::Codec;
 use futures::prelude::*;
+use log::warn;
+use prometheus_endpoint::Registry;
 
 use sc_client_api::{backend::AuxStore, BlockOf};
 use sc_consensus::{BlockImport, BlockImportParams, ForkChoiceStrategy, StateAction};
 use sc_consensus_slots::{
-	BackoffAuthoringBlocksStrategy, InherentDataProviderExt, SimpleSlotWorkerToSlotWorker,
-	SlotInfo, StorageChanges,
+	BackoffAuthoringBlocksStrategy, InherentDataProviderExt, SlotInfo, StorageChanges,
 };
 use sc_telemetry::TelemetryHandle;
 use sp_api::{Core, ProvideRuntimeApi};
@@ -38,20 +61,33 @@ use sp_consensus_slots::Slot;
 use sp_core::crypto::Pair;
 use sp_inherents::CreateInherentDataProviders;
 use sp_keystore::KeystorePtr;
-use sp_runtime::traits::{Block as BlockT, Header, Member, NumberFor};
+use sp_runtime::traits::{Block as BlockT, Header, Member, NumberFor, One, SaturatedConversion};
 
+pub mod aux_schema;
+mod clock_drift;
+mod handoff;
 mod import_queue;
+mod metrics;
+mod multi_block;
+mod slots;
 pub mod standalone;
 
-pub use crate::standalone::{find_pre_digest, slot_duration};
+pub use crate::{
+	clock_drift::{ClockDrift, ClockDriftLimits, ClockOffset},
+	handoff::{handoff_peers_set_config, handoff_protocol_name, LeaderHandoffParams},
+	standalone::{find_pre_digest, slot_duration, SlotAuthors},
+};
 pub use import_queue::{
-	build_verifier, import_queue, BuildVerifierParams, CheckForEquivocation, ImportQueueParams,
-	SpinVerifier,
+	build_verifier, import_queue, BuildVerifierParams, CheckForEquivocation, FutureHeaderLimits,
+	ImportQueueParams, SpinImportQueue, SpinVerifier,
 };
 pub use qfp_consensus_spin::{
 	digests::CompatibleDigestItem,
 	inherents::{InherentDataProvider, InherentType as SpinInherent, INHERENT_IDENTIFIER},
-	ConsensusLog, SessionLength, SlotDuration, SpinApi, SpinAuxData, SPIN_ENGINE_ID,
+	AuthorityIndex, ConsensusLog, PendingChange, PreDigest, ScheduledChange, SessionLength,
+	SlotDuration, SlotSchedule, SpinApi, SpinAuxData, SpinEquivocationApi, SpinEquivocationProof,
+	SpinFallbackApi, SpinMultiBlockApi, SpinScheduledChangeApi, SpinSlotScheduleApi,
+	SPIN_ENGINE_ID,
 };
 pub use sc_consensus_slots::SlotProportion;
 pub use sp_consensus::SyncOracle;
@@ -102,8 +138,6 @@ impl<N> Default for CompatibilityMode<N> {
 
 /// Parameters of [`start_spin`].
 pub struct StartSpinParams<C, SC, I, PF, SO, L, CIDP, BS, N> {
-	/// The duration of a slot.
-	pub slot_duration: SlotDuration,
 	/// The client to interact with the chain.
 	pub client: Arc<C>,
 	/// A select chain implementation to select the best block.
@@ -140,13 +174,26 @@ pub struct StartSpinParams<C, SC, I, PF, SO, L, CIDP, BS, N> {
 	///
 	/// If in doubt, use `Default::default()`.
 	pub compatibility_mode: CompatibilityMode<N>,
+	/// The prometheus registry to register the metrics of the worker on.
+	pub registry: Option<Registry>,
+	/// The estimated offset of the local clock, as shared with the import
+	/// queue. No blocks are authored while it exceeds its limits.
+	pub clock_drift: ClockDrift,
+	/// Up to how many blocks the slot leader builds on top of each other in
+	/// its slot.
+	///
+	/// More than one requires a runtime that allows multiple blocks per slot
+	/// and importing nodes that accept as many. If in doubt, use `1`.
+	pub blocks_per_slot: u32,
+	/// The hand-off between slot leaders, if the hand-off protocol was added
+	/// to the network configuration.
+	pub handoff: Option<LeaderHandoffParams>,
 }
 
 /// Start the spin worker. The returned future should be run in a futures
 /// executor.
 pub fn start_spin<P, B, C, SC, I, PF, SO, L, CIDP, BS, Error>(
 	StartSpinParams {
-		slot_duration,
 		client,
 		select_chain,
 		block_import,
@@ -161,6 +208,10 @@ pub fn start_spin<P, B, C, SC, I, PF, SO, L, CIDP, BS, Error>(
 		max_block_proposal_slot_portion,
 		telemetry,
 		compatibility_mode,
+		registry,
+		clock_drift,
+		blocks_per_slot,
+		handoff,
 	}: StartSpinParams<C, SC, I, PF, SO, L, CIDP, BS, NumberFor<B>>,
 ) -> Result<impl Future<Output = ()>, ConsensusError>
 where
@@ -169,40 +220,83 @@ where
 	P::Signature: TryFrom<Vec<u8>> + Member + Codec,
 	B: BlockT,
 	C: ProvideRuntimeApi<B> + BlockOf + AuxStore + HeaderBackend<B> + Send + Sync,
-	C::Api: SpinApi<B, AuthorityId<P>, NumberFor<B>>,
+	C::Api: SpinApi<B, AuthorityId<P>, NumberFor<B>>
+		+ SpinFallbackApi<B>
+		+ SpinScheduledChangeApi<B, AuthorityId<P>, NumberFor<B>>
+		+ SpinSlotScheduleApi<B>,
 	SC: SelectChain<B>,
 	I: BlockImport<B> + Send + Sync + 'static,
 	PF: Environment<B, Error = Error> + Send + Sync + 'static,
 	PF::Proposer: Proposer<B, Error = Error>,
 	SO: SyncOracle + Send + Sync + Clone,
 	L: sc_consensus::JustificationSyncLink<B>,
-	CIDP: CreateInherentDataProviders<B, ()> + Send + 'static,
-	CIDP::InherentDataProviders: InherentDataProviderExt + Send,
+	CIDP: CreateInherentDataProviders<B, ()> + Clone + Send + 'static,
+	CIDP::InherentDataProviders: InherentDataProviderExt + Send + 'static,
 	BS: BackoffAuthoringBlocksStrategy<NumberFor<B>> + Send + Sync + 'static,
 	Error: std::error::Error + Send + From<ConsensusError> + 'static,
 {
-	let worker = build_spin_worker::<P, _, _, _, _, _, _, _, _>(BuildSpinWorkerParams {
+	let (handoff_task, announcer, waiter) = match handoff {
+		Some(LeaderHandoffParams { notification_service, peer_store, announce, wait }) => {
+			let handoff = handoff::Handoff::new();
+			let task = handoff::run::<P, B, C>(
+				handoff.clone(),
+				notification_service,
+				peer_store,
+				client.clone(),
+				compatibility_mode.clone(),
+			);
+			(Some(task), announce.then(|| handoff.clone()), Some((handoff, wait)))
+		},
+		None => (None, None, None),
+	};
+
+	let worker = SpinWorker::<_, _, _, P, _, _, _, _, B::Hash>::new(
+		BuildSpinWorkerParams {
+			client: client.clone(),
+			block_import,
+			proposer_factory,
+			keystore,
+			sync_oracle: sync_oracle.clone(),
+			justification_sync_link,
+			force_authoring,
+			backoff_authoring_blocks,
+			telemetry,
+			block_proposal_slot_portion,
+			max_block_proposal_slot_portion,
+			compatibility_mode,
+			registry,
+			clock_drift,
+		},
+		announcer,
+	);
+	let worker = handoff::HandoffSlotWorker::new(
+		multi_block::MultiBlockSlotWorker::new(
+			sc_consensus_slots::SimpleSlotWorkerToSlotWorker(worker),
+			blocks_per_slot,
+			create_inherent_data_providers.clone(),
+		),
+		client.clone(),
+		waiter,
+	);
+
+	// slots are numbered by `create_inherent_data_providers`, and timed by the
+	// slot schedule at the best block.
+	let slot_worker = slots::start_slot_worker::<AuthorityId<P>, _, _, _, _, _, _, _>(
 		client,
-		block_import,
-		proposer_factory,
-		keystore,
-		sync_oracle: sync_oracle.clone(),
-		justification_sync_link,
-		force_authoring,
-		backoff_authoring_blocks,
-		telemetry,
-		block_proposal_slot_portion,
-		max_block_proposal_slot_portion,
-		compatibility_mode,
-	});
-
-	Ok(sc_consensus_slots::start_slot_worker(
-		slot_duration,
 		select_chain,
-		SimpleSlotWorkerToSlotWorker(worker),
+		worker,
 		sync_oracle,
 		create_inherent_data_providers,
-	))
+	);
+
+	Ok(async move {
+		match handoff_task {
+			Some(handoff_task) => {
+				future::join(slot_worker, handoff_task).await;
+			},
+			None => slot_worker.await,
+		}
+	})
 }
 
 /// Parameters of [`build_spin_worker`].
@@ -239,6 +333,11 @@ pub struct BuildSpinWorkerParams<C, I, PF, SO, L, BS, N> {
 	///
 	/// If in doubt, use `Default::default()`.
 	pub compatibility_mode: CompatibilityMode<N>,
+	/// The prometheus registry to register the metrics of the worker on.
+	pub registry: Option<Registry>,
+	/// The estimated offset of the local clock, as shared with the import
+	/// queue. No blocks are authored while it exceeds its limits.
+	pub clock_drift: ClockDrift,
 }
 
 /// Build the SPIN worker.
@@ -246,33 +345,22 @@ pub struct BuildSpinWorkerParams<C, I, PF, SO, L, BS, N> {
 /// The caller is responsible for running this worker, otherwise it will do
 /// nothing.
 pub fn build_spin_worker<P, B, C, PF, I, SO, L, BS, Error>(
-	BuildSpinWorkerParams {
-		client,
-		block_import,
-		proposer_factory,
-		sync_oracle,
-		justification_sync_link,
-		backoff_authoring_blocks,
-		keystore,
-		block_proposal_slot_portion,
-		max_block_proposal_slot_portion,
-		telemetry,
-		force_authoring,
-		compatibility_mode,
-	}: BuildSpinWorkerParams<C, I, PF, SO, L, BS, NumberFor<B>>,
+	params: BuildSpinWorkerParams<C, I, PF, SO, L, BS, NumberFor<B>>,
 ) -> impl sc_consensus_slots::SimpleSlotWorker<
 	B,
 	Proposer = PF::Proposer,
 	BlockImport = I,
 	SyncOracle = SO,
 	JustificationSyncLink = L,
-	Claim = P::Public,
-	AuxData = (Vec<AuthorityId<P>>, SessionLength<NumberFor<B>>),
+	Claim = (P::Public, PreDigest),
+	AuxData = SlotAuthors<AuthorityId<P>, NumberFor<B>>,
 >
 where
 	B: BlockT,
 	C: ProvideRuntimeApi<B> + BlockOf + AuxStore + HeaderBackend<B> + Send + Sync,
-	C::Api: SpinApi<B, AuthorityId<P>, NumberFor<B>>,
+	C::Api: SpinApi<B, AuthorityId<P>, NumberFor<B>>
+		+ SpinFallbackApi<B>
+		+ SpinScheduledChangeApi<B, AuthorityId<P>, NumberFor<B>>,
 	PF: Environment<B, Error = Error> + Send + Sync + 'static,
 	PF::Proposer: Proposer<B, Error = Error>,
 	P: Pair,
@@ -284,24 +372,10 @@ where
 	L: sc_consensus::JustificationSyncLink<B>,
 	BS: BackoffAuthoringBlocksStrategy<NumberFor<B>> + Send + Sync + 'static,
 {
-	SpinWorker {
-		client,
-		block_import,
-		env: proposer_factory,
-		keystore,
-		sync_oracle,
-		justification_sync_link,
-		force_authoring,
-		backoff_authoring_blocks,
-		telemetry,
-		block_proposal_slot_portion,
-		max_block_proposal_slot_portion,
-		compatibility_mode,
-		_phantom: PhantomData::<fn() -> P>,
-	}
+	SpinWorker::<_, _, _, P, _, _, _, _, B::Hash>::new(params, None)
 }
 
-struct SpinWorker<C, E, I, P, SO, L, BS, N> {
+struct SpinWorker<C, E, I, P, SO, L, BS, N, H> {
 	client: Arc<C>,
 	block_import: I,
 	env: E,
@@ -314,16 +388,66 @@ struct SpinWorker<C, E, I, P, SO, L, BS, N> {
 	max_block_proposal_slot_portion: Option<SlotProportion>,
 	telemetry: Option<TelemetryHandle>,
 	compatibility_mode: CompatibilityMode<N>,
+	metrics: Option<metrics::WorkerMetrics>,
+	/// When proposing the current block started and how long it may take.
+	proposal_budget: Mutex<Option<(Instant, Duration)>>,
+	clock_drift: ClockDrift,
+	/// Where to announce the blocks authored in the last slot of a tenure.
+	handoff: Option<handoff::Handoff<H, N>>,
 	_phantom: PhantomData<fn() -> P>,
 }
 
+impl<C, E, I, P, SO, L, BS, N, H> SpinWorker<C, E, I, P, SO, L, BS, N, H> {
+	fn new(
+		BuildSpinWorkerParams {
+			client,
+			block_import,
+			proposer_factory,
+			sync_oracle,
+			justification_sync_link,
+			backoff_authoring_blocks,
+			keystore,
+			block_proposal_slot_portion,
+			max_block_proposal_slot_portion,
+			telemetry,
+			force_authoring,
+			compatibility_mode,
+			registry,
+			clock_drift,
+		}: BuildSpinWorkerParams<C, I, E, SO, L, BS, N>,
+		handoff: Option<handoff::Handoff<H, N>>,
+	) -> Self {
+		SpinWorker {
+			client,
+			block_import,
+			env: proposer_factory,
+			keystore,
+			sync_oracle,
+			justification_sync_link,
+			force_authoring,
+			backoff_authoring_blocks,
+			telemetry,
+			block_proposal_slot_portion,
+			max_block_proposal_slot_portion,
+			compatibility_mode,
+			metrics: metrics::WorkerMetrics::register_optional(registry.as_ref()),
+			proposal_budget: Mutex::new(None),
+			clock_drift,
+			handoff,
+			_phantom: PhantomData,
+		}
+	}
+}
+
 #[async_trait::async_trait]
 impl<B, C, E, I, P, Error, SO, L, BS> sc_consensus_slots::SimpleSlotWorker<B>
-	for SpinWorker<C, E, I, P, SO, L, BS, NumberFor<B>>
+	for SpinWorker<C, E, I, P, SO, L, BS, NumberFor<B>, B::Hash>
 where
 	B: BlockT,
-	C: ProvideRuntimeApi<B> + BlockOf + HeaderBackend<B> + Sync,
-	C::Api: SpinApi<B, AuthorityId<P>, NumberFor<B>>,
+	C: ProvideRuntimeApi<B> + BlockOf + AuxStore + HeaderBackend<B> + Sync,
+	C::Api: SpinApi<B, AuthorityId<P>, NumberFor<B>>
+		+ SpinFallbackApi<B>
+		+ SpinScheduledChangeApi<B, AuthorityId<P>, NumberFor<B>>,
 	E: Environment<B, Error = Error> + Send + Sync,
 	E::Proposer: Proposer<B, Error = Error>,
 	I: BlockImport<B> + Send + Sync + 'static,
@@ -341,8 +465,8 @@ where
 	type CreateProposer =
 		Pin<Box<dyn Future<Output = Result<E::Proposer, ConsensusError>> + Send + 'static>>;
 	type Proposer = E::Proposer;
-	type Claim = P::Public;
-	type AuxData = SpinAuxData<AuthorityId<P>, NumberFor<B>>;
+	type Claim = (P::Public, PreDigest);
+	type AuxData = SlotAuthors<AuthorityId<P>, NumberFor<B>>;
 
 	fn logging_target(&self) -> &'static str {
 		"spin"
@@ -353,29 +477,66 @@ where
 	}
 
 	fn aux_data(&self, header: &B::Header, _slot: Slot) -> Result<Self::AuxData, ConsensusError> {
-		aux_data(
+		slot_authors::<P, _, _>(
 			self.client.as_ref(),
-			header.hash(),
+			header,
 			*header.number() + 1u32.into(),
 			&self.compatibility_mode,
 		)
 	}
 
-	fn authorities_len(&self, aux_data: &Self::AuxData) -> Option<usize> {
-		Some(aux_data.0.len())
+	fn authorities_len(&self, slot_authors: &Self::AuxData) -> Option<usize> {
+		Some(slot_authors.authorities.len())
 	}
 
 	async fn claim_slot(
 		&mut self,
 		_header: &B::Header,
 		slot: Slot,
-		aux_data: &Self::AuxData,
+		slot_authors: &Self::AuxData,
 	) -> Option<Self::Claim> {
-		crate::standalone::claim_slot::<B, P>(slot, aux_data, &self.keystore).await
+		let mut claim =
+			crate::standalone::claim_slot::<B, P>(slot, slot_authors, &self.keystore).await;
+
+		// blocks authored on a bad clock are likely rejected by the other
+		// authorities, or make them reject the blocks of the next leader.
+		if let Some(offset) = claim.as_ref().and(self.clock_drift.refusing_offset()) {
+			warn!(
+				target: LOG_TARGET,
+				"Not authoring in slot {}: the local clock is {}ms off.",
+				slot,
+				offset.millis,
+			);
+			claim = None;
+		}
+
+		if let Some(metrics) = &self.metrics {
+			let leader = |slot| {
+				qfp_consensus_spin::slot_leader(
+					slot,
+					slot_authors.session_length.saturated_into(),
+					&slot_authors.leader_order,
+					slot_authors.authorities.len() as u32,
+					&slot_authors.disabled,
+				)
+			};
+			metrics.note_slot(
+				slot,
+				&slot_authors.parent,
+				claim.as_ref().map(|(_, pre_digest)| pre_digest),
+				leader,
+			);
+		}
+
+		claim
 	}
 
-	fn pre_digest_data(&self, slot: Slot, _claim: &Self::Claim) -> Vec<sp_runtime::DigestItem> {
-		vec![crate::standalone::pre_digest::<P>(slot)]
+	fn pre_digest_data(&self, slot: Slot, claim: &Self::Claim) -> Vec<sp_runtime::DigestItem> {
+		match claim.1 {
+			PreDigest::Primary(_) => vec![crate::standalone::pre_digest::<P>(slot)],
+			PreDigest::Secondary { authority_index, .. } =>
+				vec![crate::standalone::secondary_pre_digest::<P>(slot, authority_index)],
+		}
 	}
 
 	async fn block_import_params(
@@ -384,18 +545,41 @@ where
 		header_hash: &B::Hash,
 		body: Vec<B::Extrinsic>,
 		storage_changes: StorageChanges<B>,
-		public: Self::Claim,
-		_authorities: Self::AuxData,
+		(public, pre_digest): Self::Claim,
+		slot_authors: Self::AuxData,
 	) -> Result<sc_consensus::BlockImportParams<B>, ConsensusError> {
 		let signature_digest_item =
 			crate::standalone::seal::<_, P>(header_hash, &public, &self.keystore)?;
 
+		let proposal_budget = self.proposal_budget.lock().ok().and_then(|mut budget| budget.take());
+		if let (Some(metrics), Some((started, budget))) = (&self.metrics, proposal_budget) {
+			metrics.note_proposal(started.elapsed(), budget);
+		}
+
 		let mut import_block = BlockImportParams::new(BlockOrigin::Own, header);
 		import_block.post_digests.push(signature_digest_item);
 		import_block.body = Some(body);
 		import_block.state_action =
 			StateAction::ApplyChanges(sc_consensus::StorageChanges::Changes(storage_changes));
-		import_block.fork_choice = Some(ForkChoiceStrategy::LongestChain);
+		apply_fork_choice(self.client.as_ref(), &mut import_block, pre_digest.is_primary())?;
+
+		let session_length = slot_authors.session_length.saturated_into::<u64>();
+		if let Some(handoff) = self
+			.handoff
+			.as_ref()
+			.filter(|_| handoff::last_slot_of_tenure(pre_digest.slot(), session_length))
+		{
+			let number = *import_block.header.number();
+			if let Err(e) = handoff.announce::<P>(
+				&self.keystore,
+				&public,
+				&pre_digest,
+				number,
+				import_block.post_hash(),
+			) {
+				warn!(target: LOG_TARGET, "Unable to announce block #{}: {}", number, e);
+			}
+		}
 
 		Ok(import_block)
 	}
@@ -441,14 +625,20 @@ where
 	fn proposing_remaining_duration(&self, slot_info: &SlotInfo<B>) -> std::time::Duration {
 		let parent_slot = find_pre_digest::<B, P::Signature>(&slot_info.chain_head).ok();
 
-		sc_consensus_slots::proposing_remaining_duration(
+		let budget = sc_consensus_slots::proposing_remaining_duration(
 			parent_slot,
 			slot_info,
 			&self.block_proposal_slot_portion,
 			self.max_block_proposal_slot_portion.as_ref(),
 			sc_consensus_slots::SlotLenienceType::Exponential,
 			self.logging_target(),
-		)
+		);
+
+		if let Ok(mut proposal_budget) = self.proposal_budget.lock() {
+			*proposal_budget = Some((Instant::now(), budget));
+		}
+
+		budget
 	}
 }
 
@@ -470,6 +660,9 @@ pub enum Error<B: BlockT> {
 	/// Slot Author not found
 	#[error("Slot Author not found")]
 	SlotAuthorNotFound,
+	/// Secondary author is not allowed to author the slot
+	#[error("Secondary author of {0:?} is not allowed to author the slot")]
+	SecondaryNotAllowed(B::Hash),
 	/// Bad signature
 	#[error("Bad signature on {0:?}")]
 	BadSignature(B::Hash),
@@ -499,44 +692,89 @@ impl<B: BlockT> From<crate::standalone::PreDigestLookupError> for Error<B> {
 	}
 }
 
-fn aux_data<A, B, C>(
+fn slot_authors<P, B, C>(
 	client: &C,
-	parent_hash: B::Hash,
+	parent: &B::Header,
 	context_block_number: NumberFor<B>,
 	compatibility_mode: &CompatibilityMode<NumberFor<B>>,
-) -> Result<SpinAuxData<A, NumberFor<B>>, ConsensusError>
+) -> Result<SlotAuthors<AuthorityId<P>, NumberFor<B>>, ConsensusError>
 where
-	A: Codec + Debug,
+	P: Pair,
+	P::Public: Codec + Debug,
+	P::Signature: Codec,
 	B: BlockT,
 	C: ProvideRuntimeApi<B>,
-	C::Api: SpinApi<B, A, NumberFor<B>>,
+	C::Api: SpinApi<B, AuthorityId<P>, NumberFor<B>>
+		+ SpinFallbackApi<B>
+		+ SpinScheduledChangeApi<B, AuthorityId<P>, NumberFor<B>>,
 {
-	let runtime_api = client.runtime_api();
-
-	match compatibility_mode {
-		CompatibilityMode::None => {},
-		// Use `initialize_block` until we hit the block that should disable the mode.
-		CompatibilityMode::UseInitializeBlock { until } =>
-			if *until > context_block_number {
-				runtime_api
-					.initialize_block(
-						parent_hash,
-						&B::Header::new(
-							context_block_number,
-							Default::default(),
-							Default::default(),
-							parent_hash,
-							Default::default(),
-						),
-					)
-					.map_err(|_| ConsensusError::InvalidAuthoritiesSet)?;
-			},
+	let parent_hash = parent.hash();
+	let SpinAuxData { mut authorities, session_length, mut leader_order, mut disabled, .. } =
+		crate::standalone::fetch_authorities_with_compatibility_mode(
+			client,
+			parent_hash,
+			context_block_number,
+			compatibility_mode,
+		)?;
+
+	// the runtime enacts a scheduled change at the start of the block it is
+	// effective at, after the state of the parent was left.
+	let pending = crate::standalone::fetch_pending_change(client, parent_hash)?;
+	if let Some(change) = pending.filter(|change| change.effective_at <= context_block_number) {
+		authorities = change.next_authorities;
+		leader_order = change.leader_order;
+		// validators are only disabled within a session.
+		disabled = Vec::new();
 	}
 
-	runtime_api
-		.aux_data(parent_hash)
-		.ok()
-		.ok_or(ConsensusError::InvalidAuthoritiesSet)
+	Ok(SlotAuthors {
+		authorities,
+		session_length,
+		leader_order,
+		disabled,
+		fallback_after: crate::standalone::fetch_fallback_after(client, parent_hash)?,
+		parent: crate::standalone::find_full_pre_digest::<B, P::Signature>(parent)
+			.map_err(|e| ConsensusError::ClientImport(e.to_string()))?,
+	})
+}
+
+/// Weigh the block by the number of blocks authored by slot leaders in its
+/// chain and make it the new best block if it outweighs the current one.
+///
+/// Chains of equal weight are compared by length. Blocks imported before
+/// blocks were weighed were all authored by slot leaders, so their weight is
+/// their number.
+fn apply_fork_choice<B, C>(
+	client: &C,
+	import_block: &mut BlockImportParams<B>,
+	primary: bool,
+) -> Result<(), ConsensusError>
+where
+	B: BlockT,
+	C: AuxStore + HeaderBackend<B>,
+{
+	let weight_of = |hash: B::Hash, number: NumberFor<B>| {
+		aux_schema::load_block_weight(client, hash)
+			.map(|weight| weight.unwrap_or_else(|| number.saturated_into()))
+			.map_err(|e| ConsensusError::ClientImport(e.to_string()))
+	};
+
+	let number = *import_block.header.number();
+	let parent_weight =
+		weight_of(*import_block.header.parent_hash(), number.saturating_sub(One::one()))?;
+	let weight = parent_weight.saturating_add(primary.into());
+
+	let info = client.info();
+	let best_weight = weight_of(info.best_hash, info.best_number)?;
+	let is_new_best =
+		if weight == best_weight { number > info.best_number } else { weight > best_weight };
+
+	import_block
+		.auxiliary
+		.push(aux_schema::block_weight_aux(import_block.post_hash(), weight));
+	import_block.fork_choice = Some(ForkChoiceStrategy::Custom(is_new_best));
+
+	Ok(())
 }
 
 // TODO: uncomment and make tests pass: https://github.com/QuantumFusion-network/spec/issues/216
@@ -550,7 +788,8 @@ where
 //     use sc_consensus_slots::{BackoffAuthoringOnFinalizedHeadLagging,
 // SimpleSlotWorker};     use sc_keystore::LocalKeystore;
 //     use sc_network_test::{Block as TestBlock, *};
-//     use sp_application_crypto::{AppCrypto, key_types::AURA};
+//     use sp_application_crypto::AppCrypto;
+//     use qfp_consensus_spin::KEY_TYPE;
 //     use sp_consensus::{DisableProofRecording, NoNetwork as DummyOracle,
 // Proposal};     use sp_inherents::InherentData;
 //     use sp_keyring::sr25519::Keyring;
@@ -717,7 +956,7 @@ where
 // None).expect("Creates keystore."),             );
 
 //             keystore
-//                 .sr25519_generate_new(AURA, Some(&key.to_seed()))
+//                 .sr25519_generate_new(KEY_TYPE, Some(&key.to_seed()))
 //                 .expect("Creates authority key");
 //             keystore_paths.push(keystore_path);
 
@@ -0,0 +1,314 @@
+// Copyright (C) QF Network, 2025.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Prometheus metrics of SPIN.
+//!
+//! The slot worker and the import queue register their metrics on their own
+//! registries, which may be the same.
+
+use std::time::Duration;
+
+use log::warn;
+use prometheus_endpoint::{
+	exponential_buckets, register, Counter, CounterVec, Gauge, Histogram, HistogramOpts, Opts,
+	PrometheusError, Registry, I64, U64,
+};
+use qfp_consensus_spin::{AuthorityIndex, PreDigest, Slot};
+
+use crate::LOG_TARGET;
+
+/// Metrics of the SPIN slot worker.
+#[derive(Clone)]
+pub(crate) struct WorkerMetrics {
+	slots_claimed: CounterVec<U64>,
+	slots_missed: CounterVec<U64>,
+	proposal_budget_used: Histogram,
+	current_leader: Gauge<U64>,
+}
+
+impl WorkerMetrics {
+	/// Register the metrics of the slot worker on `registry`.
+	pub(crate) fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			slots_claimed: register(
+				CounterVec::new(
+					Opts::new(
+						"spin_slots_claimed_total",
+						"Number of slots claimed by the local authorities",
+					),
+					&["authority"],
+				)?,
+				registry,
+			)?,
+			slots_missed: register(
+				CounterVec::new(
+					Opts::new(
+						"spin_slots_missed_total",
+						"Number of slots their leader did not author a block in on the best chain",
+					),
+					&["authority"],
+				)?,
+				registry,
+			)?,
+			proposal_budget_used: register(
+				Histogram::with_opts(
+					HistogramOpts::new(
+						"spin_proposal_budget_used",
+						"Time spent proposing and sealing a block, as a share of the \
+						proposal budget of its slot",
+					)
+					.buckets(vec![0.1, 0.25, 0.5, 0.75, 0.9, 1.0, 1.25, 1.5, 2.0]),
+				)?,
+				registry,
+			)?,
+			current_leader: register(
+				Gauge::new(
+					"spin_current_leader",
+					"Authority index of the leader of the current slot",
+				)?,
+				registry,
+			)?,
+		})
+	}
+
+	/// Register the metrics of the slot worker if there is a registry.
+	pub(crate) fn register_optional(registry: Option<&Registry>) -> Option<Self> {
+		registry.and_then(|registry| {
+			Self::register(registry)
+				.map_err(|e| warn!(target: LOG_TARGET, "Failed to register SPIN metrics: {}", e))
+				.ok()
+		})
+	}
+
+	/// Note the leader of `slot`, whether the leader of the previous slot
+	/// missed it, and the authority that claimed `slot` with `claim` on top of
+	/// a block with the `parent` pre-digest.
+	pub(crate) fn note_slot(
+		&self,
+		slot: Slot,
+		parent: &PreDigest,
+		claim: Option<&PreDigest>,
+		leader: impl Fn(Slot) -> Option<AuthorityIndex>,
+	) {
+		if let Some(leader) = leader(slot) {
+			self.note_leader(leader);
+		}
+
+		// the leader of the previous slot missed it unless it authored the
+		// head of our chain in it.
+		let previous = slot.saturating_sub(1u64);
+		if parent.slot() < previous || (parent.slot() == previous && !parent.is_primary()) {
+			if let Some(leader) = leader(previous) {
+				self.note_missed(leader);
+			}
+		}
+
+		let claimed = match claim {
+			Some(PreDigest::Primary(_)) => leader(slot),
+			Some(PreDigest::Secondary { authority_index, .. }) => Some(*authority_index),
+			None => None,
+		};
+		// further blocks of a slot do not claim it again.
+		if let Some(authority) = claimed.filter(|_| parent.slot() < slot) {
+			self.note_claimed(authority);
+		}
+	}
+
+	fn note_claimed(&self, authority: AuthorityIndex) {
+		self.slots_claimed.with_label_values(&[&authority.to_string()]).inc();
+	}
+
+	fn note_missed(&self, authority: AuthorityIndex) {
+		self.slots_missed.with_label_values(&[&authority.to_string()]).inc();
+	}
+
+	fn note_leader(&self, authority: AuthorityIndex) {
+		self.current_leader.set(authority.into());
+	}
+
+	pub(crate) fn note_proposal(&self, elapsed: Duration, budget: Duration) {
+		if !budget.is_zero() {
+			self.proposal_budget_used.observe(elapsed.as_secs_f64() / budget.as_secs_f64());
+		}
+	}
+}
+
+/// Metrics of the SPIN import queue.
+#[derive(Clone)]
+pub(crate) struct ImportMetrics {
+	seal_to_import: Histogram,
+	deferred_headers: Counter<U64>,
+	equivocations: Counter<U64>,
+	future_headers: Gauge<U64>,
+	future_header_evictions: Counter<U64>,
+	clock_offset: Gauge<I64>,
+}
+
+impl ImportMetrics {
+	/// Register the metrics of the import queue on `registry`.
+	pub(crate) fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			seal_to_import: register(
+				Histogram::with_opts(
+					HistogramOpts::new(
+						"spin_seal_to_import_seconds",
+						"Time from the start of the slot a block was sealed in to its import",
+					)
+					.buckets(exponential_buckets(0.005, 2.0, 12)?),
+				)?,
+				registry,
+			)?,
+			deferred_headers: register(
+				Counter::new(
+					"spin_deferred_headers_total",
+					"Number of headers received for a slot in the future",
+				)?,
+				registry,
+			)?,
+			equivocations: register(
+				Counter::new("spin_equivocations_total", "Number of equivocations seen on import")?,
+				registry,
+			)?,
+			future_headers: register(
+				Gauge::new(
+					"spin_future_headers",
+					"Number of headers of future slots held back until their slot starts",
+				)?,
+				registry,
+			)?,
+			future_header_evictions: register(
+				Counter::new(
+					"spin_future_header_evictions_total",
+					"Number of headers of future slots rejected because the buffer was full",
+				)?,
+				registry,
+			)?,
+			clock_offset: register(
+				Gauge::new(
+					"spin_clock_offset_milliseconds",
+					"Estimated offset of the local clock from the clocks of the block authors",
+				)?,
+				registry,
+			)?,
+		})
+	}
+
+	/// Register the metrics of the import queue if there is a registry.
+	pub(crate) fn register_optional(registry: Option<&Registry>) -> Option<Self> {
+		registry.and_then(|registry| {
+			Self::register(registry)
+				.map_err(|e| warn!(target: LOG_TARGET, "Failed to register SPIN metrics: {}", e))
+				.ok()
+		})
+	}
+
+	pub(crate) fn note_import(&self, since_slot_start: Duration) {
+		self.seal_to_import.observe(since_slot_start.as_secs_f64());
+	}
+
+	pub(crate) fn note_deferred(&self) {
+		self.deferred_headers.inc();
+	}
+
+	pub(crate) fn note_equivocation(&self) {
+		self.equivocations.inc();
+	}
+
+	pub(crate) fn note_future_headers(&self, held: usize) {
+		self.future_headers.set(held as u64);
+	}
+
+	pub(crate) fn note_future_header_evicted(&self) {
+		self.future_header_evictions.inc();
+	}
+
+	pub(crate) fn note_clock_offset(&self, millis: i64) {
+		self.clock_offset.set(millis);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{clock_drift::ClockDrift, import_queue::sample_slot_timing, SlotSchedule};
+
+	#[test]
+	fn worker_and_import_metrics_share_a_registry() {
+		let registry = Registry::new();
+		let worker = WorkerMetrics::register(&registry).unwrap();
+		let import = ImportMetrics::register(&registry).unwrap();
+
+		worker.note_claimed(1);
+		worker.note_proposal(Duration::from_millis(30), Duration::from_millis(60));
+		import.note_deferred();
+
+		assert_eq!(worker.slots_claimed.with_label_values(&["1"]).get(), 1);
+		assert_eq!(worker.proposal_budget_used.get_sample_sum(), 0.5);
+		assert_eq!(import.deferred_headers.get(), 1);
+		// metrics can only be registered once.
+		assert!(WorkerMetrics::register(&registry).is_err());
+	}
+
+	#[test]
+	fn slots_are_accounted_to_their_leaders() {
+		let registry = Registry::new();
+		let metrics = WorkerMetrics::register(&registry).unwrap();
+		// three authorities lead a slot each in turn.
+		let leader = |slot: Slot| Some((*slot % 3) as AuthorityIndex);
+		let claimed = |authority: AuthorityIndex| {
+			metrics.slots_claimed.with_label_values(&[&authority.to_string()]).get()
+		};
+		let missed = |authority: AuthorityIndex| {
+			metrics.slots_missed.with_label_values(&[&authority.to_string()]).get()
+		};
+
+		// the leader of slot 4 authored nothing on top of slot 3, we lead slot 5.
+		let parent = PreDigest::Primary(3.into());
+		let claim = PreDigest::Primary(5.into());
+		metrics.note_slot(5.into(), &parent, Some(&claim), leader);
+		assert_eq!(metrics.current_leader.get(), 2);
+		assert_eq!(missed(1), 1);
+		assert_eq!(claimed(2), 1);
+
+		// further blocks of the slot do not claim it again.
+		metrics.note_slot(5.into(), &claim, Some(&claim), leader);
+		assert_eq!(claimed(2), 1);
+
+		// the leader of slot 6 missed it to a secondary author.
+		let secondary = PreDigest::Secondary { slot: 6.into(), authority_index: 2 };
+		metrics.note_slot(7.into(), &secondary, None, leader);
+		assert_eq!(metrics.current_leader.get(), 1);
+		assert_eq!(missed(0), 1);
+		assert_eq!(claimed(1), 0);
+	}
+
+	#[test]
+	fn imports_are_timed_from_the_start_of_their_slot() {
+		let registry = Registry::new();
+		let metrics = ImportMetrics::register(&registry).unwrap();
+		let clock_drift = ClockDrift::default();
+		let schedule =
+			SlotSchedule { start_slot: 0.into(), start_timestamp: 0, slot_duration: 1_000 };
+		let import = |slot: u64, now: u64| {
+			sample_slot_timing(
+				&clock_drift,
+				Some(&metrics),
+				&schedule,
+				slot.into(),
+				Duration::from_millis(now),
+			)
+		};
+
+		// blocks are imported 200ms into their slot.
+		for slot in 1..=8 {
+			import(slot, slot * 1_000 + 200);
+		}
+		assert_eq!(metrics.seal_to_import.get_sample_count(), 8);
+		assert!((metrics.seal_to_import.get_sample_sum() - 1.6).abs() < 1e-9);
+		assert_eq!(metrics.clock_offset.get(), 200);
+
+		// blocks of a slot that did not start yet are not timed.
+		import(20, 9_000);
+		assert_eq!(metrics.seal_to_import.get_sample_count(), 8);
+	}
+}
@@ -0,0 +1,228 @@
+// Copyright (C) QF Network, 2025.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Authoring more than one block per slot.
+//!
+//! The slot leader builds up to a number of blocks on top of each other in
+//! its slot, every block with an even share of the time left in the slot.
+//! The runtime must allow multiple blocks per slot, and importing nodes must
+//! accept as many blocks per slot.
+
+use std::time::{Duration, Instant};
+
+use log::warn;
+use sc_consensus_slots::{InherentDataProviderExt, SlotInfo, SlotResult, SlotWorker};
+use sp_inherents::CreateInherentDataProviders;
+use sp_runtime::traits::{Block as BlockT, Header};
+
+use crate::LOG_TARGET;
+
+/// A slot worker building up to `blocks_per_slot` blocks per slot with the
+/// wrapped [`SlotWorker`].
+pub(crate) struct MultiBlockSlotWorker<W, CIDP> {
+	worker: W,
+	blocks_per_slot: u32,
+	create_inherent_data_providers: CIDP,
+}
+
+impl<W, CIDP> MultiBlockSlotWorker<W, CIDP> {
+	pub(crate) fn new(
+		worker: W,
+		blocks_per_slot: u32,
+		create_inherent_data_providers: CIDP,
+	) -> Self {
+		Self { worker, blocks_per_slot: blocks_per_slot.max(1), create_inherent_data_providers }
+	}
+}
+
+/// The share of the time until `ends_at` of each of the `blocks` left to build.
+fn block_share(ends_at: Instant, now: Instant, blocks: u32) -> Duration {
+	ends_at.saturating_duration_since(now) / blocks.max(1)
+}
+
+#[async_trait::async_trait]
+impl<B, W, CIDP, Proof> SlotWorker<B, Proof> for MultiBlockSlotWorker<W, CIDP>
+where
+	B: BlockT,
+	W: SlotWorker<B, Proof> + Send,
+	CIDP: CreateInherentDataProviders<B, ()> + Send + Sync,
+	CIDP::InherentDataProviders: InherentDataProviderExt + 'static,
+	Proof: Send,
+{
+	async fn on_slot(&mut self, slot_info: SlotInfo<B>) -> Option<SlotResult<B, Proof>> {
+		if self.blocks_per_slot == 1 {
+			return self.worker.on_slot(slot_info).await;
+		}
+
+		let slot = slot_info.slot;
+		let ends_at = slot_info.ends_at;
+		let block_size_limit = slot_info.block_size_limit;
+
+		let now = Instant::now();
+		let duration = block_share(ends_at, now, self.blocks_per_slot);
+		let slot_info = SlotInfo { ends_at: now + duration, duration, ..slot_info };
+		let mut result = self.worker.on_slot(slot_info).await?;
+
+		for built in 1..self.blocks_per_slot {
+			let chain_head = result.block.header().clone();
+			let inherent_data_providers = match self
+				.create_inherent_data_providers
+				.create_inherent_data_providers(chain_head.hash(), ())
+				.await
+			{
+				Ok(inherent_data_providers) => inherent_data_providers,
+				Err(e) => {
+					warn!(
+						target: LOG_TARGET,
+						"Unable to author another block in slot {}: {}",
+						slot,
+						e,
+					);
+					break;
+				},
+			};
+
+			// the slot may be over before every block was built.
+			if inherent_data_providers.slot() != slot {
+				break;
+			}
+
+			let now = Instant::now();
+			let duration = block_share(ends_at, now, self.blocks_per_slot - built);
+			let slot_info = SlotInfo {
+				slot,
+				ends_at: now + duration,
+				create_inherent_data: Box::new(inherent_data_providers),
+				duration,
+				chain_head,
+				block_size_limit,
+			};
+			match self.worker.on_slot(slot_info).await {
+				Some(next) => result = next,
+				None => break,
+			}
+		}
+
+		Some(result)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use qfp_consensus_spin::inherents::InherentDataProvider;
+	use sp_consensus_slots::Slot;
+	use sp_core::H256;
+	use sp_runtime::testing::{Block as TestBlock, Header as TestHeader, MockCallU64, TestXt};
+	use std::sync::{
+		atomic::{AtomicU64, Ordering},
+		Arc,
+	};
+
+	type TestBlockType = TestBlock<TestXt<MockCallU64, ()>>;
+
+	/// A worker building a block on the chain head of every slot, and moving
+	/// time on to the slot `slot_after[n]` once it built `n + 1` blocks.
+	struct Author {
+		now: Arc<AtomicU64>,
+		slot_after: Vec<u64>,
+		built: Vec<SlotInfo<TestBlockType>>,
+	}
+
+	#[async_trait::async_trait]
+	impl SlotWorker<TestBlockType, ()> for Author {
+		async fn on_slot(
+			&mut self,
+			slot_info: SlotInfo<TestBlockType>,
+		) -> Option<SlotResult<TestBlockType, ()>> {
+			let header = TestHeader::new(
+				slot_info.chain_head.number() + 1,
+				Default::default(),
+				Default::default(),
+				slot_info.chain_head.hash(),
+				Default::default(),
+			);
+			if let Some(slot) = self.slot_after.get(self.built.len()) {
+				self.now.store(*slot, Ordering::SeqCst);
+			}
+			self.built.push(slot_info);
+			Some(SlotResult {
+				block: TestBlock { header, extrinsics: Vec::new() },
+				storage_proof: (),
+			})
+		}
+	}
+
+	fn build_blocks(
+		blocks_per_slot: u32,
+		slot_after: Vec<u64>,
+	) -> (u64, Vec<SlotInfo<TestBlockType>>) {
+		let now = Arc::new(AtomicU64::new(5));
+		let author = Author { now: now.clone(), slot_after, built: Vec::new() };
+		let create_inherent_data_providers = move |_: H256, ()| {
+			let slot = Slot::from(now.load(Ordering::SeqCst));
+			async move { Ok((InherentDataProvider::new(slot),)) }
+		};
+		let mut worker =
+			MultiBlockSlotWorker::new(author, blocks_per_slot, create_inherent_data_providers);
+
+		let start = Instant::now();
+		let slot_info = SlotInfo {
+			slot: 5.into(),
+			ends_at: start + Duration::from_millis(600),
+			create_inherent_data: Box::new((InherentDataProvider::new(5.into()),)),
+			duration: Duration::from_millis(600),
+			chain_head: TestHeader::new(
+				7,
+				Default::default(),
+				Default::default(),
+				Default::default(),
+				Default::default(),
+			),
+			block_size_limit: None,
+		};
+		let result = futures::executor::block_on(worker.on_slot(slot_info)).unwrap();
+
+		let built = worker.worker.built;
+		// every block is built within the slot, on top of the previous one.
+		for (slot_info, number) in built.iter().zip(7..) {
+			assert_eq!(slot_info.slot, Slot::from(5));
+			assert_eq!(*slot_info.chain_head.number(), number);
+			assert!(slot_info.ends_at <= start + Duration::from_millis(600));
+			assert!(slot_info.duration <= Duration::from_millis(600));
+		}
+		(*result.block.header.number(), built)
+	}
+
+	#[test]
+	fn leader_builds_blocks_on_each_other_in_its_slot() {
+		let (head, built) = build_blocks(3, Vec::new());
+		assert_eq!((head, built.len()), (10, 3));
+		// the first block gets a third of the slot.
+		assert!(built[0].duration <= Duration::from_millis(200));
+
+		let (head, built) = build_blocks(1, Vec::new());
+		assert_eq!((head, built.len()), (8, 1));
+	}
+
+	#[test]
+	fn leader_stops_building_once_its_slot_is_over() {
+		// the slot is over once the second block is built.
+		let (head, built) = build_blocks(4, vec![5, 6]);
+		assert_eq!((head, built.len()), (9, 2));
+	}
+
+	#[test]
+	fn blocks_share_the_rest_of_the_slot() {
+		let now = Instant::now();
+		let ends_at = now + Duration::from_millis(600);
+
+		assert_eq!(block_share(ends_at, now, 3), Duration::from_millis(200));
+		let later = now + Duration::from_millis(200);
+		assert_eq!(block_share(ends_at, later, 2), Duration::from_millis(200));
+		// once the slot is over there is no time left.
+		let over = now + Duration::from_millis(700);
+		assert_eq!(block_share(ends_at, over, 1), Duration::ZERO);
+		assert_eq!(block_share(ends_at, now, 0), Duration::from_millis(600));
+	}
+}
@@ -0,0 +1,225 @@
+// Copyright (C) QF Network, 2025.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The slot loop of the SPIN worker.
+//!
+//! Unlike the loop of `sc_consensus_slots`, which ticks at the slot duration it
+//! was started with, slots are timed by the slot schedule at the best block, so
+//! that the worker and the budget of its proposals follow the changes of the
+//! slot duration.
+
+use std::{
+	sync::Arc,
+	time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use codec::Codec;
+use futures_timer::Delay;
+use log::{debug, warn};
+use sc_consensus_slots::{InherentDataProviderExt, SlotInfo, SlotWorker};
+use sp_api::ProvideRuntimeApi;
+use sp_consensus::{SelectChain, SyncOracle};
+use sp_consensus_slots::Slot;
+use sp_inherents::CreateInherentDataProviders;
+use sp_runtime::traits::{Block as BlockT, Header, NumberFor};
+
+use crate::{SlotSchedule, SpinApi, SpinSlotScheduleApi, LOG_TARGET};
+
+/// How long to wait before trying again when the slot schedule is unknown.
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// The time from `now` to the start of the slot after the one `now` falls
+/// into, unless the slots of `schedule` have no duration.
+fn until_next_slot(schedule: &SlotSchedule, now: u64) -> Option<Duration> {
+	if schedule.slot_duration == 0 {
+		return None;
+	}
+	let next = schedule.slot_start(schedule.slot_at(now) + 1);
+	Some(Duration::from_millis(next.saturating_sub(now)))
+}
+
+/// The time from `now` to the end of `slot`.
+fn until_slot_end(schedule: &SlotSchedule, slot: Slot, now: u64) -> Duration {
+	Duration::from_millis(schedule.slot_start(slot + 1).saturating_sub(now))
+}
+
+fn now_millis() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|now| now.as_millis() as u64)
+		.unwrap_or_default()
+}
+
+/// A stream of the slots, as scheduled at the best block.
+struct Slots<B: BlockT, C, SC, CIDP> {
+	last_slot: Slot,
+	schedule: Option<SlotSchedule>,
+	client: Arc<C>,
+	select_chain: SC,
+	create_inherent_data_providers: CIDP,
+	_phantom: std::marker::PhantomData<B>,
+}
+
+impl<B, C, SC, CIDP> Slots<B, C, SC, CIDP>
+where
+	B: BlockT,
+	C: ProvideRuntimeApi<B>,
+	SC: SelectChain<B>,
+	CIDP: CreateInherentDataProviders<B, ()> + Send + 'static,
+	CIDP::InherentDataProviders: InherentDataProviderExt + Send,
+{
+	/// The slot schedule at `chain_head`, or the last one read if it cannot be
+	/// read.
+	fn schedule_at<A>(&mut self, chain_head: &B::Header, now: u64) -> Option<SlotSchedule>
+	where
+		A: Codec,
+		C::Api: SpinApi<B, A, NumberFor<B>> + SpinSlotScheduleApi<B>,
+	{
+		match crate::standalone::slot_schedule_at::<A, B, C>(&*self.client, chain_head.hash(), now)
+		{
+			Ok(schedule) => self.schedule = Some(schedule),
+			Err(e) => warn!(
+				target: LOG_TARGET,
+				"Unable to read the slot schedule at {}: {}",
+				chain_head.hash(),
+				e,
+			),
+		}
+		self.schedule
+	}
+
+	/// Wait for the next slot and return it.
+	async fn next_slot<A>(&mut self) -> SlotInfo<B>
+	where
+		A: Codec,
+		C::Api: SpinApi<B, A, NumberFor<B>> + SpinSlotScheduleApi<B>,
+	{
+		loop {
+			// wait for the start of the next slot, as scheduled at the best block.
+			let wait = match self.select_chain.best_chain().await {
+				Ok(chain_head) => {
+					let now = now_millis();
+					self.schedule_at::<A>(&chain_head, now)
+						.and_then(|schedule| until_next_slot(&schedule, now))
+				},
+				Err(e) => {
+					warn!(target: LOG_TARGET, "Unable to read the best block: {}", e);
+					None
+				},
+			};
+			Delay::new(wait.unwrap_or(RETRY_DELAY)).await;
+
+			let chain_head = match self.select_chain.best_chain().await {
+				Ok(chain_head) => chain_head,
+				Err(e) => {
+					warn!(
+						target: LOG_TARGET,
+						"Unable to author block in slot. No best block header: {}",
+						e,
+					);
+					continue;
+				},
+			};
+
+			let inherent_data_providers = match self
+				.create_inherent_data_providers
+				.create_inherent_data_providers(chain_head.hash(), ())
+				.await
+			{
+				Ok(inherent_data_providers) => inherent_data_providers,
+				Err(e) => {
+					warn!(
+						target: LOG_TARGET,
+						"Unable to author block in slot. Failure creating inherent data provider: {}",
+						e,
+					);
+					continue;
+				},
+			};
+
+			// never yield the same slot twice.
+			let slot = inherent_data_providers.slot();
+			if slot <= self.last_slot {
+				continue;
+			}
+			let now = now_millis();
+			let Some(schedule) = self.schedule_at::<A>(&chain_head, now) else {
+				continue;
+			};
+			self.last_slot = slot;
+
+			break SlotInfo {
+				slot,
+				ends_at: Instant::now() + until_slot_end(&schedule, slot, now),
+				create_inherent_data: Box::new(inherent_data_providers),
+				duration: Duration::from_millis(schedule.slot_duration),
+				chain_head,
+				block_size_limit: None,
+			};
+		}
+	}
+}
+
+/// Start a slot worker that authors with `worker` in the slots scheduled at the
+/// best block, as long as the node is not major syncing.
+pub(crate) async fn start_slot_worker<A, B, C, SC, W, SO, CIDP, Proof>(
+	client: Arc<C>,
+	select_chain: SC,
+	mut worker: W,
+	sync_oracle: SO,
+	create_inherent_data_providers: CIDP,
+) where
+	A: Codec,
+	B: BlockT,
+	C: ProvideRuntimeApi<B>,
+	C::Api: SpinApi<B, A, NumberFor<B>> + SpinSlotScheduleApi<B>,
+	SC: SelectChain<B>,
+	W: SlotWorker<B, Proof>,
+	SO: SyncOracle + Send,
+	CIDP: CreateInherentDataProviders<B, ()> + Send + 'static,
+	CIDP::InherentDataProviders: InherentDataProviderExt + Send,
+{
+	let mut slots = Slots {
+		last_slot: Slot::from(0),
+		schedule: None,
+		client,
+		select_chain,
+		create_inherent_data_providers,
+		_phantom: std::marker::PhantomData,
+	};
+
+	loop {
+		let slot_info = slots.next_slot::<A>().await;
+
+		if sync_oracle.is_major_syncing() {
+			debug!(target: LOG_TARGET, "Skipping proposal slot due to sync.");
+			continue;
+		}
+
+		let _ = worker.on_slot(slot_info).await;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn slots_are_timed_by_the_schedule() {
+		let genesis =
+			SlotSchedule { start_slot: 0.into(), start_timestamp: 0, slot_duration: 6_000 };
+		assert_eq!(until_next_slot(&genesis, 6_000), Some(Duration::from_millis(6_000)));
+		assert_eq!(until_next_slot(&genesis, 10_000), Some(Duration::from_millis(2_000)));
+		assert_eq!(until_slot_end(&genesis, 1.into(), 10_000), Duration::from_millis(2_000));
+
+		// slots are shorter from slot 10 on.
+		let faster = genesis.switch_at(10.into(), 2_000);
+		assert_eq!(until_next_slot(&faster, 60_500), Some(Duration::from_millis(1_500)));
+		assert_eq!(until_slot_end(&faster, 11.into(), 62_500), Duration::from_millis(1_500));
+		// the last slot of the previous schedule is still the longer one.
+		assert_eq!(until_next_slot(&genesis, 55_000), Some(Duration::from_millis(5_000)));
+
+		let stopped = SlotSchedule { slot_duration: 0, ..genesis };
+		assert_eq!(until_next_slot(&stopped, 6_000), None);
+	}
+}
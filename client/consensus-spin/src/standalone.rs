@@ -11,7 +11,7 @@ use log::trace;
 use codec::Codec;
 
 use sc_client_api::UsageProvider;
-use sp_api::{Core, ProvideRuntimeApi};
+use sp_api::{ApiExt, Core, ProvideRuntimeApi};
 use sp_application_crypto::{AppCrypto, AppPublic};
 use sp_blockchain::Result as CResult;
 use sp_consensus::Error as ConsensusError;
@@ -26,8 +26,9 @@ use sp_runtime::{
 pub use sc_consensus_slots::check_equivocation;
 
 use super::{
-	AuthorityId, CompatibilityMode, CompatibleDigestItem, SessionLength, SlotDuration, SpinApi,
-	SpinAuxData, LOG_TARGET,
+	AuthorityId, AuthorityIndex, CompatibilityMode, CompatibleDigestItem, PendingChange, PreDigest,
+	SessionLength, SlotDuration, SlotSchedule, SpinApi, SpinAuxData, SpinFallbackApi,
+	SpinScheduledChangeApi, SpinSlotScheduleApi, LOG_TARGET,
 };
 
 /// Get the slot duration for SPIN by reading from a runtime API at the best
@@ -54,26 +55,79 @@ where
 	client.runtime_api().slot_duration(block_hash).map_err(|err| err.into())
 }
 
+/// Get the slot schedule that numbers the slot of a block built on top of
+/// `parent_hash` at `timestamp`, in milliseconds.
+///
+/// A pending change of the slot duration takes over from the timestamp its
+/// start slot starts at. Runtimes without [`SpinSlotScheduleApi`] run at the
+/// slot duration of their [`SpinApi`] since genesis.
+pub fn slot_schedule_at<A, B, C>(
+	client: &C,
+	parent_hash: B::Hash,
+	timestamp: u64,
+) -> CResult<SlotSchedule>
+where
+	A: Codec,
+	B: BlockT,
+	C: ProvideRuntimeApi<B>,
+	C::Api: SpinApi<B, A, NumberFor<B>> + SpinSlotScheduleApi<B>,
+{
+	let runtime_api = client.runtime_api();
+
+	if !runtime_api.has_api::<dyn SpinSlotScheduleApi<B>>(parent_hash)? {
+		return slot_duration_at(client, parent_hash).map(SlotSchedule::genesis);
+	}
+
+	match runtime_api.pending_slot_schedule(parent_hash)? {
+		Some(pending) if timestamp >= pending.start_timestamp => Ok(pending),
+		_ => runtime_api.slot_schedule(parent_hash).map_err(Into::into),
+	}
+}
+
+/// The chain state on top of a block that determines who may author the slots
+/// after it.
+#[derive(Clone, Debug)]
+pub struct SlotAuthors<A, N> {
+	/// The current authorities.
+	pub authorities: Vec<A>,
+	/// The number of consecutive slots every authority leads.
+	pub session_length: SessionLength<N>,
+	/// The order in which the authorities lead tenures.
+	pub leader_order: Vec<AuthorityIndex>,
+	/// The indices of the disabled authorities.
+	pub disabled: Vec<AuthorityIndex>,
+	/// The number of empty slots after which the secondary author of a slot
+	/// may author it, zero if secondary authoring is disabled.
+	pub fallback_after: u64,
+	/// The pre-digest of the block.
+	pub parent: PreDigest,
+}
+
 /// Get the slot author for given block along with authorities.
 ///
-/// Session increment is assumed to be done by the runtime
-pub fn slot_author<B: BlockT, P: Pair>(
+/// Session increment is assumed to be done by the runtime. The authorities
+/// lead tenures in the order selected by the runtime, an empty `leader_order`
+/// stands for index order. The tenure of a disabled authority falls through
+/// to the next authority that is not disabled.
+pub fn slot_author<'a, B: BlockT, P: Pair>(
 	slot: Slot,
 	session_length: SessionLength<NumberFor<B>>,
-	authorities: &[AuthorityId<P>],
-) -> Option<&AuthorityId<P>> {
+	authorities: &'a [AuthorityId<P>],
+	leader_order: &[AuthorityIndex],
+	disabled: &[AuthorityIndex],
+) -> Option<&'a AuthorityId<P>> {
 	if authorities.is_empty() {
 		return None;
 	}
 	assert!(!session_length.is_zero(), "session_length can not be zero; qed");
 
-	let session_idx = *slot / session_length.saturated_into::<u64>();
-
-	let idx = session_idx % (authorities.len() as u64);
-	assert!(
-		idx <= usize::MAX as u64,
-		"It is impossible to have a vector with length beyond the address space; qed",
-	);
+	let idx = qfp_consensus_spin::slot_leader(
+		slot,
+		session_length.saturated_into::<u64>(),
+		leader_order,
+		authorities.len().saturated_into::<u32>(),
+		disabled,
+	)?;
 
 	let current_author = authorities.get(idx as usize).expect(
 		"authorities not empty; index constrained to list length;this is a valid index; qed",
@@ -82,25 +136,90 @@ pub fn slot_author<B: BlockT, P: Pair>(
 	Some(current_author)
 }
 
+/// Get the secondary author of `slot` along with its authority index, if it may
+/// author the slot on top of the block `slot_authors` belong to.
+pub fn secondary_slot_author<'a, B: BlockT, P: Pair>(
+	slot: Slot,
+	slot_authors: &'a SlotAuthors<AuthorityId<P>, NumberFor<B>>,
+) -> Option<(AuthorityIndex, &'a AuthorityId<P>)> {
+	let session_length = slot_authors.session_length.saturated_into::<u64>();
+	let parent = &slot_authors.parent;
+	if !qfp_consensus_spin::secondary_authoring_allowed(
+		parent.slot(),
+		parent.is_primary(),
+		slot,
+		session_length,
+		slot_authors.fallback_after,
+	) {
+		return None;
+	}
+
+	let authority_index = qfp_consensus_spin::secondary_slot_leader(
+		slot,
+		session_length,
+		&slot_authors.leader_order,
+		slot_authors.authorities.len().saturated_into::<u32>(),
+		&slot_authors.disabled,
+	)?;
+
+	slot_authors
+		.authorities
+		.get(authority_index as usize)
+		.map(|author| (authority_index, author))
+}
+
+/// Get the author expected to seal a block with the given pre-digest on top of
+/// the block `slot_authors` belong to.
+pub fn expected_author<'a, B: BlockT, P: Pair>(
+	pre_digest: &PreDigest,
+	slot_authors: &'a SlotAuthors<AuthorityId<P>, NumberFor<B>>,
+) -> Option<&'a AuthorityId<P>> {
+	match *pre_digest {
+		PreDigest::Primary(slot) => slot_author::<B, P>(
+			slot,
+			slot_authors.session_length,
+			&slot_authors.authorities,
+			&slot_authors.leader_order,
+			&slot_authors.disabled,
+		),
+		PreDigest::Secondary { slot, authority_index } =>
+			secondary_slot_author::<B, P>(slot, slot_authors)
+				.filter(|(index, _)| *index == authority_index)
+				.map(|(_, author)| author),
+	}
+}
+
 /// Attempt to claim a slot using a keystore.
 ///
-/// This returns `None` if the slot author is not locally controlled, and `Some`
-/// if it is, with the public key of the slot author.
+/// This returns `None` if neither the slot author nor, when allowed, the
+/// secondary author is locally controlled, and `Some` if one is, with its
+/// public key and the pre-digest to author the slot with.
 pub async fn claim_slot<B: BlockT, P: Pair>(
 	slot: Slot,
-	aux_data: &SpinAuxData<AuthorityId<P>, NumberFor<B>>,
+	slot_authors: &SlotAuthors<AuthorityId<P>, NumberFor<B>>,
 	keystore: &KeystorePtr,
-) -> Option<P::Public> {
-	let (authorities, session_length) = aux_data;
-	let expected_author = slot_author::<B, P>(slot, *session_length, authorities);
-	expected_author.and_then(|p| {
-		// TODO: add SPIN key type
-		if keystore.has_keys(&[(p.to_raw_vec(), sp_application_crypto::key_types::AURA)]) {
-			Some(p.clone())
-		} else {
-			None
-		}
-	})
+) -> Option<(P::Public, PreDigest)>
+where
+	P::Public: AppPublic,
+{
+	let has_key = |p: &AuthorityId<P>| {
+		keystore.has_keys(&[(p.to_raw_vec(), <AuthorityId<P> as AppCrypto>::ID)])
+	};
+
+	let expected_author = slot_author::<B, P>(
+		slot,
+		slot_authors.session_length,
+		&slot_authors.authorities,
+		&slot_authors.leader_order,
+		&slot_authors.disabled,
+	);
+	if let Some(p) = expected_author.filter(|p| has_key(p)) {
+		return Some((p.clone(), PreDigest::Primary(slot)));
+	}
+
+	secondary_slot_author::<B, P>(slot, slot_authors)
+		.filter(|(_, p)| has_key(p))
+		.map(|(authority_index, p)| (p.clone(), PreDigest::Secondary { slot, authority_index }))
 }
 
 /// Produce the pre-runtime digest containing the slot info.
@@ -114,6 +233,21 @@ where
 	<DigestItem as CompatibleDigestItem<P::Signature>>::spin_pre_digest(slot)
 }
 
+/// Produce the pre-runtime digest of a block authored by the secondary author
+/// of the slot.
+pub fn secondary_pre_digest<P: Pair>(
+	slot: Slot,
+	authority_index: AuthorityIndex,
+) -> sp_runtime::DigestItem
+where
+	P::Signature: Codec,
+{
+	<DigestItem as CompatibleDigestItem<P::Signature>>::spin_secondary_pre_digest(
+		slot,
+		authority_index,
+	)
+}
+
 /// Produce the seal digest item by signing the hash of a block.
 ///
 /// Note that after this is added to a block header, the hash of the block will
@@ -172,14 +306,27 @@ pub enum PreDigestLookupError {
 pub fn find_pre_digest<B: BlockT, Signature: Codec>(
 	header: &B::Header,
 ) -> Result<Slot, PreDigestLookupError> {
+	find_full_pre_digest::<B, Signature>(header).map(|pre_digest| pre_digest.slot())
+}
+
+/// Extract a pre-digest from a block header.
+///
+/// This fails if there is no pre-digest or there are multiple. The genesis
+/// block is treated as authored by the leader of slot zero.
+pub fn find_full_pre_digest<B: BlockT, Signature: Codec>(
+	header: &B::Header,
+) -> Result<PreDigest, PreDigestLookupError> {
 	if header.number().is_zero() {
-		return Ok(0.into());
+		return Ok(PreDigest::Primary(0.into()));
 	}
 
-	let mut pre_digest: Option<Slot> = None;
+	let mut pre_digest: Option<PreDigest> = None;
 	for log in header.digest().logs() {
 		trace!(target: LOG_TARGET, "Checking log {:?}", log);
-		match (CompatibleDigestItem::<Signature>::as_spin_pre_digest(log), pre_digest.is_some()) {
+		match (
+			CompatibleDigestItem::<Signature>::as_spin_full_pre_digest(log),
+			pre_digest.is_some(),
+		) {
 			(Some(_), true) => return Err(PreDigestLookupError::MultipleHeaders),
 			(None, _) => trace!(target: LOG_TARGET, "Ignoring digest not meant for us"),
 			(s, false) => pre_digest = s,
@@ -227,10 +374,7 @@ where
 			},
 	}
 
-	runtime_api
-		.aux_data(parent_hash)
-		.ok()
-		.ok_or(ConsensusError::InvalidAuthoritiesSet)
+	call_aux_data::<A, B, _>(&*runtime_api, parent_hash)
 }
 
 /// Load the current set of authorities from a runtime at a specific block.
@@ -244,11 +388,110 @@ where
 	C: ProvideRuntimeApi<B>,
 	C::Api: SpinApi<B, A, NumberFor<B>>,
 {
-	client
-		.runtime_api()
-		.aux_data(parent_hash)
-		.ok()
-		.ok_or(ConsensusError::InvalidAuthoritiesSet)
+	call_aux_data::<A, B, _>(&*client.runtime_api(), parent_hash)
+}
+
+/// Call [`SpinApi::aux_data`] at a specific block.
+///
+/// Runtimes that predate the current version of [`SpinApi`] are called with
+/// the version they implement, and the data they do not report is filled in:
+/// - before version 2 the authorities lead tenures in index order;
+/// - before version 3 no authorities are disabled, the session index is zero and no next
+///   authorities are known.
+fn call_aux_data<A, B, Api>(
+	runtime_api: &Api,
+	parent_hash: B::Hash,
+) -> Result<SpinAuxData<A, NumberFor<B>>, ConsensusError>
+where
+	A: Codec,
+	B: BlockT,
+	Api: SpinApi<B, A, NumberFor<B>>,
+{
+	let has_version = |version: u32| {
+		runtime_api
+			.has_api_with::<dyn SpinApi<B, A, NumberFor<B>>, _>(parent_hash, |v| v >= version)
+			.map_err(|_| ConsensusError::InvalidAuthoritiesSet)
+	};
+
+	if has_version(3)? {
+		return runtime_api
+			.aux_data(parent_hash)
+			.map_err(|_| ConsensusError::InvalidAuthoritiesSet);
+	}
+
+	#[allow(deprecated)]
+	let (authorities, session_length, leader_order) = if has_version(2)? {
+		runtime_api.aux_data_before_version_3(parent_hash)
+	} else {
+		runtime_api
+			.aux_data_before_version_2(parent_hash)
+			.map(|(authorities, session_length)| (authorities, session_length, Vec::new()))
+	}
+	.map_err(|_| ConsensusError::InvalidAuthoritiesSet)?;
+
+	Ok(SpinAuxData {
+		authorities,
+		session_length,
+		session_index: 0,
+		leader_order,
+		disabled: Vec::new(),
+		next_authorities: Vec::new(),
+	})
+}
+
+/// Load the number of empty slots after which secondary authors may author from
+/// a runtime at a specific block.
+///
+/// Runtimes without [`SpinFallbackApi`] do not allow secondary authoring.
+pub fn fetch_fallback_after<B, C>(client: &C, parent_hash: B::Hash) -> Result<u64, ConsensusError>
+where
+	B: BlockT,
+	C: ProvideRuntimeApi<B>,
+	C::Api: SpinFallbackApi<B>,
+{
+	let runtime_api = client.runtime_api();
+
+	if !runtime_api
+		.has_api::<dyn SpinFallbackApi<B>>(parent_hash)
+		.map_err(|_| ConsensusError::InvalidAuthoritiesSet)?
+	{
+		return Ok(0);
+	}
+
+	runtime_api
+		.fallback_after(parent_hash)
+		.map(Into::into)
+		.map_err(|_| ConsensusError::InvalidAuthoritiesSet)
+}
+
+/// Load the change of the authority set that is scheduled but not enacted yet
+/// from a runtime at a specific block.
+///
+/// The change is read from the state of `parent_hash`, so every fork follows
+/// the changes scheduled in its own chain. Runtimes without
+/// [`SpinScheduledChangeApi`] do not schedule changes.
+pub fn fetch_pending_change<A, B, C>(
+	client: &C,
+	parent_hash: B::Hash,
+) -> Result<Option<PendingChange<A, NumberFor<B>>>, ConsensusError>
+where
+	A: Codec,
+	B: BlockT,
+	C: ProvideRuntimeApi<B>,
+	C::Api: SpinScheduledChangeApi<B, A, NumberFor<B>>,
+{
+	let runtime_api = client.runtime_api();
+
+	if !runtime_api
+		.has_api::<dyn SpinScheduledChangeApi<B, A, NumberFor<B>>>(parent_hash)
+		.map_err(|_| ConsensusError::InvalidAuthoritiesSet)?
+	{
+		return Ok(None);
+	}
+
+	runtime_api
+		.pending_change(parent_hash)
+		.map_err(|_| ConsensusError::InvalidAuthoritiesSet)
 }
 
 /// Errors in slot and seal verification.
@@ -274,6 +517,10 @@ pub enum SealVerificationError<Header> {
 	#[error("No slot author for provided slot")]
 	SlotAuthorNotFound,
 
+	/// The secondary author is not allowed to author the slot.
+	#[error("Secondary author is not allowed to author the slot")]
+	SecondaryNotAllowed,
+
 	/// Header has no valid slot pre-digest.
 	#[error("Header has no valid slot pre-digest")]
 	InvalidPreDigest(PreDigestLookupError),
@@ -281,8 +528,12 @@ pub enum SealVerificationError<Header> {
 
 /// Check a header has been signed by the right key. If the slot is too far in
 /// the future, an error will be returned. If it's successful, returns the
-/// pre-header (i.e. without the seal), the slot, and the digest item containing
-/// the seal.
+/// pre-header (i.e. without the seal), the pre-digest, and the digest item
+/// containing the seal.
+///
+/// Headers with a secondary pre-digest must be signed by the secondary author
+/// of the slot, which is only allowed to author after the leader missed
+/// enough slots.
 ///
 /// Note that this does not check for equivocations, and [`check_equivocation`]
 /// is recommended for that purpose.
@@ -291,19 +542,19 @@ pub enum SealVerificationError<Header> {
 pub fn check_header_slot_and_seal<B: BlockT, P: Pair>(
 	slot_now: Slot,
 	mut header: B::Header,
-	aux_data: &SpinAuxData<AuthorityId<P>, NumberFor<B>>,
-) -> Result<(B::Header, Slot, DigestItem), SealVerificationError<B::Header>>
+	slot_authors: &SlotAuthors<AuthorityId<P>, NumberFor<B>>,
+) -> Result<(B::Header, PreDigest, DigestItem), SealVerificationError<B::Header>>
 where
 	P::Signature: Codec,
 	P::Public: Codec + PartialEq + Clone,
 {
-	let (authorities, session_length) = aux_data;
 	let seal = header.digest_mut().pop().ok_or(SealVerificationError::Unsealed)?;
 
 	let sig = seal.as_spin_seal().ok_or(SealVerificationError::BadSeal)?;
 
-	let slot = find_pre_digest::<B, P::Signature>(&header)
+	let pre_digest = find_full_pre_digest::<B, P::Signature>(&header)
 		.map_err(SealVerificationError::InvalidPreDigest)?;
+	let slot = pre_digest.slot();
 
 	if slot > slot_now {
 		header.digest_mut().push(seal);
@@ -311,13 +562,18 @@ where
 	} else {
 		// check the signature is valid under the expected authority and
 		// chain state.
-		let expected_author = slot_author::<B, P>(slot, *session_length, &authorities)
-			.ok_or(SealVerificationError::SlotAuthorNotFound)?;
+		let author = expected_author::<B, P>(&pre_digest, slot_authors).ok_or(
+			if pre_digest.is_primary() {
+				SealVerificationError::SlotAuthorNotFound
+			} else {
+				SealVerificationError::SecondaryNotAllowed
+			},
+		)?;
 
 		let pre_hash = header.hash();
 
-		if P::verify(&sig, pre_hash.as_ref(), expected_author) {
-			Ok((header, slot, seal))
+		if P::verify(&sig, pre_hash.as_ref(), author) {
+			Ok((header, pre_digest, seal))
 		} else {
 			Err(SealVerificationError::BadSignature)
 		}
@@ -327,6 +583,7 @@ where
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use codec::Encode;
 	use sp_keyring::sr25519::Keyring;
 	use sp_runtime::testing::{Block as TestBlock, MockCallU64, TestXt};
 
@@ -348,7 +605,9 @@ mod tests {
 			slot_author::<TestBlockType, qfp_consensus_spin::sr25519::AuthorityPair>(
 				slot,
 				session_length,
-				&authorities
+				&authorities,
+				&[],
+				&[]
 			),
 			Some(&Keyring::Alice.public().into())
 		);
@@ -357,7 +616,9 @@ mod tests {
 			slot_author::<TestBlockType, qfp_consensus_spin::sr25519::AuthorityPair>(
 				slot,
 				session_length,
-				&authorities
+				&authorities,
+				&[],
+				&[]
 			),
 			Some(&Keyring::Alice.public().into())
 		);
@@ -366,7 +627,9 @@ mod tests {
 			slot_author::<TestBlockType, qfp_consensus_spin::sr25519::AuthorityPair>(
 				slot,
 				session_length,
-				&authorities
+				&authorities,
+				&[],
+				&[]
 			),
 			Some(&Keyring::Alice.public().into())
 		);
@@ -375,7 +638,9 @@ mod tests {
 			slot_author::<TestBlockType, qfp_consensus_spin::sr25519::AuthorityPair>(
 				slot,
 				session_length,
-				&authorities
+				&authorities,
+				&[],
+				&[]
 			),
 			Some(&Keyring::Bob.public().into())
 		);
@@ -384,7 +649,9 @@ mod tests {
 			slot_author::<TestBlockType, qfp_consensus_spin::sr25519::AuthorityPair>(
 				slot,
 				session_length,
-				&authorities
+				&authorities,
+				&[],
+				&[]
 			),
 			Some(&Keyring::Bob.public().into())
 		);
@@ -394,7 +661,9 @@ mod tests {
 			slot_author::<TestBlockType, qfp_consensus_spin::sr25519::AuthorityPair>(
 				slot,
 				session_length,
-				&authorities
+				&authorities,
+				&[],
+				&[]
 			),
 			Some(&Keyring::Alice.public().into())
 		);
@@ -405,7 +674,9 @@ mod tests {
 			slot_author::<TestBlockType, qfp_consensus_spin::sr25519::AuthorityPair>(
 				slot,
 				session_length,
-				&authorities
+				&authorities,
+				&[],
+				&[]
 			),
 			Some(&Keyring::Dave.public().into())
 		);
@@ -416,7 +687,9 @@ mod tests {
 			slot_author::<TestBlockType, qfp_consensus_spin::sr25519::AuthorityPair>(
 				slot,
 				session_length,
-				&authorities
+				&authorities,
+				&[],
+				&[]
 			),
 			Some(&Keyring::Alice.public().into())
 		);
@@ -439,7 +712,154 @@ mod tests {
 			slot,
 			session_length,
 			&authorities,
+			&[],
+			&[],
+		);
+	}
+
+	#[test]
+	fn test_secondary_author_after_empty_slots() {
+		let authorities: Vec<qfp_consensus_spin::sr25519::AuthorityId> = vec![
+			Keyring::Alice.public().into(),
+			Keyring::Bob.public().into(),
+			Keyring::Charlie.public().into(),
+		];
+		let author = |pre_digest: PreDigest, parent: PreDigest, fallback_after: u64| {
+			let slot_authors = SlotAuthors {
+				authorities: authorities.clone(),
+				session_length: 4,
+				leader_order: vec![0, 1, 2],
+				disabled: vec![],
+				fallback_after,
+				parent,
+			};
+			expected_author::<TestBlockType, qfp_consensus_spin::sr25519::AuthorityPair>(
+				&pre_digest,
+				&slot_authors,
+			)
+			.cloned()
+		};
+		let secondary = |slot: u64, authority_index| PreDigest::Secondary {
+			slot: slot.into(),
+			authority_index,
+		};
+		let primary = |slot: u64| PreDigest::Primary(slot.into());
+		let bob: Option<qfp_consensus_spin::sr25519::AuthorityId> =
+			Some(Keyring::Bob.public().into());
+		let charlie: Option<qfp_consensus_spin::sr25519::AuthorityId> =
+			Some(Keyring::Charlie.public().into());
+
+		// slots 4 to 7 are led by Bob, Charlie is the secondary author.
+		assert_eq!(author(primary(6), primary(4), 2), bob);
+		// the two slots after a block of the leader are reserved to it.
+		assert_eq!(author(secondary(6, 2), primary(4), 2), None);
+		assert_eq!(author(secondary(7, 2), primary(4), 2), charlie);
+		// and so are the first two slots of the tenure.
+		assert_eq!(author(secondary(5, 2), primary(3), 2), None);
+		assert_eq!(author(secondary(6, 2), primary(3), 2), charlie);
+		// the secondary author keeps authoring once it took over.
+		assert_eq!(author(secondary(6, 2), secondary(5, 2), 2), charlie);
+		// only the secondary author of the slot may take over.
+		assert_eq!(author(secondary(7, 0), primary(4), 2), None);
+		// and nobody may if secondary authoring is disabled.
+		assert_eq!(author(secondary(7, 2), primary(4), 0), None);
+	}
+
+	#[test]
+	fn test_secondary_pre_digest_is_distinct() {
+		type AuthorityPair = qfp_consensus_spin::sr25519::AuthorityPair;
+		type Signature = <AuthorityPair as Pair>::Signature;
+
+		// blocks of the leader keep the plain slot encoding.
+		assert_eq!(
+			pre_digest::<AuthorityPair>(5.into()),
+			DigestItem::PreRuntime(crate::SPIN_ENGINE_ID, Slot::from(5).encode()),
 		);
+		// blocks of a secondary author are told apart by the engine id rather
+		// than by the length of the payload.
+		assert_eq!(
+			secondary_pre_digest::<AuthorityPair>(5.into(), 2),
+			DigestItem::PreRuntime(
+				qfp_consensus_spin::SPIN_SECONDARY_ENGINE_ID,
+				(Slot::from(5), 2u32).encode()
+			),
+		);
+
+		let header = <TestBlockType as BlockT>::Header::new(
+			1,
+			Default::default(),
+			Default::default(),
+			Default::default(),
+			sp_runtime::Digest { logs: vec![secondary_pre_digest::<AuthorityPair>(5.into(), 2)] },
+		);
+		assert_eq!(
+			find_full_pre_digest::<TestBlockType, Signature>(&header).unwrap(),
+			PreDigest::Secondary { slot: 5.into(), authority_index: 2 },
+		);
+		assert_eq!(find_pre_digest::<TestBlockType, Signature>(&header).unwrap(), Slot::from(5));
+	}
+
+	#[test]
+	fn test_slot_author_skips_disabled_authorities() {
+		let authorities: Vec<qfp_consensus_spin::sr25519::AuthorityId> = vec![
+			Keyring::Alice.public().into(),
+			Keyring::Bob.public().into(),
+			Keyring::Charlie.public().into(),
+		];
+		let session_length = 2;
+		let author = |slot: u64, disabled: &[AuthorityIndex]| {
+			slot_author::<TestBlockType, qfp_consensus_spin::sr25519::AuthorityPair>(
+				slot.into(),
+				session_length,
+				&authorities,
+				&[],
+				disabled,
+			)
+			.cloned()
+		};
+
+		// Bob's tenure falls through to Charlie.
+		assert_eq!(author(2, &[1]), Some(Keyring::Charlie.public().into()));
+		assert_eq!(author(3, &[1]), Some(Keyring::Charlie.public().into()));
+		// Charlie's tenure wraps around to Alice.
+		assert_eq!(author(4, &[1, 2]), Some(Keyring::Alice.public().into()));
+		// other tenures are unaffected.
+		assert_eq!(author(0, &[1]), Some(Keyring::Alice.public().into()));
+		// the regular leader is kept if everyone is disabled.
+		assert_eq!(author(2, &[0, 1, 2]), Some(Keyring::Bob.public().into()));
+	}
+
+	#[test]
+	fn test_slot_author_follows_leader_order() {
+		let authorities: Vec<qfp_consensus_spin::sr25519::AuthorityId> = vec![
+			Keyring::Alice.public().into(),
+			Keyring::Bob.public().into(),
+			Keyring::Charlie.public().into(),
+		];
+		let session_length = 2;
+		let author = |slot: u64, leader_order: &[AuthorityIndex], disabled: &[AuthorityIndex]| {
+			slot_author::<TestBlockType, qfp_consensus_spin::sr25519::AuthorityPair>(
+				slot.into(),
+				session_length,
+				&authorities,
+				leader_order,
+				disabled,
+			)
+			.cloned()
+		};
+
+		// Charlie leads two tenures of every rotation.
+		let leader_order = [2, 0, 2, 1];
+		assert_eq!(author(0, &leader_order, &[]), Some(Keyring::Charlie.public().into()));
+		assert_eq!(author(2, &leader_order, &[]), Some(Keyring::Alice.public().into()));
+		assert_eq!(author(5, &leader_order, &[]), Some(Keyring::Charlie.public().into()));
+		assert_eq!(author(7, &leader_order, &[]), Some(Keyring::Bob.public().into()));
+		assert_eq!(author(8, &leader_order, &[]), Some(Keyring::Charlie.public().into()));
+		// disabled leaders still fall through to the next authority.
+		assert_eq!(author(0, &leader_order, &[2]), Some(Keyring::Alice.public().into()));
+		// no order means index order, unknown authorities lead nothing.
+		assert_eq!(author(2, &[], &[]), Some(Keyring::Bob.public().into()));
+		assert_eq!(author(0, &[3], &[]), None);
 	}
 
 	// #[test]
@@ -0,0 +1,232 @@
+// Copyright (C) QF Network, 2025.
+// SPDX-License-Identifier: Apache-2.0
+
+//! RPC API for the secure finality anchoring of SPIN.
+
+#![forbid(missing_docs, unsafe_code)]
+
+use std::{marker::PhantomData, sync::Arc};
+
+use codec::Codec;
+use futures::{future, stream, Stream, StreamExt};
+use jsonrpsee::{
+	core::RpcResult,
+	proc_macros::rpc,
+	types::{ErrorObject, ErrorObjectOwned},
+	PendingSubscriptionSink,
+};
+use qfp_spin_anchoring::SpinAnchoringApi;
+use sc_client_api::BlockchainEvents;
+use sc_rpc::SubscriptionTaskExecutor;
+use serde::Serialize;
+use sp_api::{ApiError, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::{Block as BlockT, NumberFor};
+
+/// The base code of the errors of the anchoring RPC.
+const BASE_ERROR: i32 = 7100;
+
+/// Provides rpc methods for classifying the finality of blocks.
+#[rpc(client, server)]
+pub trait AnchoringApi<Hash, BlockNumber> {
+	/// Returns the highest block number that is securely anchored, as of the
+	/// best block.
+	#[method(name = "anchoring_secureUpTo")]
+	fn secure_up_to(&self) -> RpcResult<BlockNumber>;
+
+	/// Returns whether the block with the given hash is securely anchored, as
+	/// of the best block.
+	///
+	/// Blocks that are not on the best chain are not secure.
+	#[method(name = "anchoring_isSecure")]
+	fn is_secure(&self, hash: Hash) -> RpcResult<bool>;
+
+	/// Subscribes to the highest block number that is securely anchored,
+	/// notified whenever it changes with the best block.
+	#[subscription(
+		name = "anchoring_subscribeSecureUpTo" => "anchoring_secureUpToChanged",
+		unsubscribe = "anchoring_unsubscribeSecureUpTo",
+		item = BlockNumber
+	)]
+	fn subscribe_secure_up_to(&self);
+}
+
+/// Errors of the anchoring RPC.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+	/// Reading the chain failed.
+	#[error(transparent)]
+	Blockchain(#[from] sp_blockchain::Error),
+	/// Calling the runtime failed.
+	#[error(transparent)]
+	Runtime(#[from] ApiError),
+	/// The block is not known.
+	#[error("Unknown block {0}")]
+	UnknownBlock(String),
+}
+
+impl From<Error> for ErrorObjectOwned {
+	fn from(error: Error) -> Self {
+		let code = match error {
+			Error::Blockchain(_) => BASE_ERROR + 1,
+			Error::Runtime(_) => BASE_ERROR + 2,
+			Error::UnknownBlock(_) => BASE_ERROR + 3,
+		};
+		ErrorObject::owned(code, error.to_string(), None::<()>)
+	}
+}
+
+/// Provides RPC methods for classifying the finality of blocks.
+pub struct Anchoring<C, B, AccountId, Balance> {
+	client: Arc<C>,
+	executor: SubscriptionTaskExecutor,
+	_phantom: PhantomData<fn() -> (B, AccountId, Balance)>,
+}
+
+impl<C, B, AccountId, Balance> Anchoring<C, B, AccountId, Balance> {
+	/// Creates a new instance of the anchoring RPC handler.
+	pub fn new(client: Arc<C>, executor: SubscriptionTaskExecutor) -> Self {
+		Self { client, executor, _phantom: PhantomData }
+	}
+}
+
+impl<C, B, AccountId, Balance> AnchoringApiServer<B::Hash, NumberFor<B>>
+	for Anchoring<C, B, AccountId, Balance>
+where
+	B: BlockT,
+	NumberFor<B>: Serialize,
+	AccountId: Codec + 'static,
+	Balance: Codec + 'static,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B> + BlockchainEvents<B> + Send + Sync + 'static,
+	C::Api: SpinAnchoringApi<B, AccountId, Balance, NumberFor<B>>,
+{
+	fn secure_up_to(&self) -> RpcResult<NumberFor<B>> {
+		let best_hash = self.client.info().best_hash;
+		Ok(self.client.runtime_api().secure_up_to(best_hash).map_err(Error::from)?)
+	}
+
+	fn is_secure(&self, hash: B::Hash) -> RpcResult<bool> {
+		let best_hash = self.client.info().best_hash;
+		Ok(is_secure::<B>(
+			hash,
+			|hash| self.client.number(hash),
+			|number| self.client.hash(number),
+			|number| self.client.runtime_api().is_secure(best_hash, number),
+		)?)
+	}
+
+	fn subscribe_secure_up_to(&self, pending: PendingSubscriptionSink) {
+		let client = self.client.clone();
+		let current = client.runtime_api().secure_up_to(client.info().best_hash).ok();
+		let imports = self
+			.client
+			.import_notification_stream()
+			.map(|notification| (notification.hash, notification.is_new_best));
+		let changes = secure_up_to_changes(current, imports, move |hash| {
+			client.runtime_api().secure_up_to(hash).ok()
+		});
+
+		sc_rpc::utils::spawn_subscription_task(
+			&self.executor,
+			sc_rpc::utils::pipe_from_stream(pending, changes.boxed()),
+		);
+	}
+}
+
+/// Whether the block with `hash` is securely anchored, looking up the number
+/// of a block, the hash of the block of the best chain at a number and whether
+/// the runtime reports a number secure.
+fn is_secure<B: BlockT>(
+	hash: B::Hash,
+	number: impl Fn(B::Hash) -> sp_blockchain::Result<Option<NumberFor<B>>>,
+	best_chain_hash: impl Fn(NumberFor<B>) -> sp_blockchain::Result<Option<B::Hash>>,
+	is_secure: impl Fn(NumberFor<B>) -> Result<bool, ApiError>,
+) -> Result<bool, Error> {
+	let number = number(hash)?.ok_or_else(|| Error::UnknownBlock(hash.to_string()))?;
+	// a block next to the best chain is never anchored.
+	if best_chain_hash(number)? != Some(hash) {
+		return Ok(false);
+	}
+
+	Ok(is_secure(number)?)
+}
+
+/// The watermark, starting with `current`, then as of each new best block of
+/// `imports` whenever it changed.
+fn secure_up_to_changes<H, N: Copy + PartialEq>(
+	current: Option<N>,
+	imports: impl Stream<Item = (H, bool)>,
+	secure_up_to: impl Fn(H) -> Option<N>,
+) -> impl Stream<Item = N> {
+	let mut last = current;
+	let changes = imports.filter_map(move |(hash, is_new_best)| {
+		let changed = is_new_best
+			.then(|| secure_up_to(hash))
+			.flatten()
+			.filter(|secure_up_to| last != Some(*secure_up_to));
+		if changed.is_some() {
+			last = changed;
+		}
+		future::ready(changed)
+	});
+
+	stream::iter(current).chain(changes)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_runtime::{generic, testing::H256, traits::BlakeTwo256, OpaqueExtrinsic};
+	use std::collections::HashMap;
+
+	type Block = generic::Block<generic::Header<u32, BlakeTwo256>, OpaqueExtrinsic>;
+
+	#[test]
+	fn blocks_next_to_the_best_chain_are_not_secure() {
+		// blocks one to three are on the best chain, the fork is at height two.
+		let best_chain = [H256::repeat_byte(1), H256::repeat_byte(2), H256::repeat_byte(3)];
+		let fork = H256::repeat_byte(0xf2);
+		let numbers =
+			HashMap::from([(best_chain[0], 1), (best_chain[1], 2), (best_chain[2], 3), (fork, 2)]);
+		let is_secure = |hash| {
+			is_secure::<Block>(
+				hash,
+				|hash| Ok(numbers.get(&hash).copied()),
+				|number| Ok(best_chain.get(number as usize - 1).copied()),
+				// blocks are secure up to two.
+				|number| Ok(number <= 2),
+			)
+		};
+
+		assert!(is_secure(best_chain[1]).unwrap());
+		assert!(!is_secure(fork).unwrap());
+		assert!(!is_secure(best_chain[2]).unwrap());
+		assert!(matches!(is_secure(H256::repeat_byte(4)), Err(Error::UnknownBlock(_))));
+	}
+
+	#[test]
+	fn secure_up_to_is_notified_on_change_only() {
+		let secure_up_to = HashMap::from([
+			(H256::repeat_byte(1), 1),
+			(H256::repeat_byte(2), 5),
+			(H256::repeat_byte(3), 3),
+			(H256::repeat_byte(4), 3),
+			(H256::repeat_byte(6), 4),
+		]);
+		let imports = stream::iter([
+			// the watermark did not change.
+			(H256::repeat_byte(1), true),
+			// the block is not the new best one.
+			(H256::repeat_byte(2), false),
+			(H256::repeat_byte(3), true),
+			(H256::repeat_byte(4), true),
+			// the watermark is unknown.
+			(H256::repeat_byte(5), true),
+			(H256::repeat_byte(6), true),
+		]);
+
+		let changes =
+			secure_up_to_changes(Some(1), imports, |hash| secure_up_to.get(&hash).copied());
+		assert_eq!(futures::executor::block_on(changes.collect::<Vec<_>>()), vec![1, 3, 4]);
+	}
+}
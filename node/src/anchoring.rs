@@ -0,0 +1,74 @@
+//! Prometheus metrics of the secure finality anchoring.
+
+use std::sync::Arc;
+
+use futures::StreamExt;
+use prometheus_endpoint::{register, Gauge, PrometheusError, Registry, U64};
+use qf_runtime::opaque::Block;
+use qfp_spin_anchoring::SpinAnchoringApi;
+use sc_client_api::BlockchainEvents;
+use sp_api::ProvideRuntimeApi;
+
+use crate::service::FullClient;
+
+const LOG_TARGET: &str = "anchoring";
+
+/// Metrics of the anchoring, as of the best block.
+struct Metrics {
+	secure_up_to: Gauge<U64>,
+	lag: Gauge<U64>,
+}
+
+impl Metrics {
+	fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			secure_up_to: register(
+				Gauge::new(
+					"spin_anchoring_secure_up_to",
+					"Highest block number that is securely anchored",
+				)?,
+				registry,
+			)?,
+			lag: register(
+				Gauge::new(
+					"spin_anchoring_lag",
+					"Number of blocks the best block is above the anchoring watermark",
+				)?,
+				registry,
+			)?,
+		})
+	}
+}
+
+/// Report the anchoring watermark and lag of every new best block on
+/// `registry`.
+pub async fn report_lag(client: Arc<FullClient>, registry: Registry) {
+	let metrics = match Metrics::register(&registry) {
+		Ok(metrics) => metrics,
+		Err(e) => {
+			log::warn!(target: LOG_TARGET, "Failed to register anchoring metrics: {}", e);
+			return;
+		},
+	};
+
+	let mut imports = client.import_notification_stream();
+	while let Some(notification) = imports.next().await {
+		if !notification.is_new_best {
+			continue;
+		}
+
+		let api = client.runtime_api();
+		match (api.secure_up_to(notification.hash), api.anchor_lag(notification.hash)) {
+			(Ok(secure_up_to), Ok(lag)) => {
+				metrics.secure_up_to.set(secure_up_to.into());
+				metrics.lag.set(lag.into());
+			},
+			(Err(e), _) | (_, Err(e)) => log::debug!(
+				target: LOG_TARGET,
+				"Unable to read the anchoring watermark at {}: {}",
+				notification.hash,
+				e,
+			),
+		}
+	}
+}
@@ -5,6 +5,34 @@ pub struct Cli {
 
 	#[clap(flatten)]
 	pub run: sc_cli::RunCmd,
+
+	#[clap(flatten)]
+	pub spin: SpinParams,
+}
+
+/// Parameters of the SPIN consensus.
+#[derive(Debug, Clone, clap::Args)]
+pub struct SpinParams {
+	/// The number of blocks the SPIN slot leader builds per slot, and the most
+	/// blocks of a slot that are imported. More than one block per slot must be
+	/// allowed by the runtime.
+	#[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+	pub blocks_per_slot: u32,
+
+	/// Refuse to author blocks while the local clock is estimated to be off by
+	/// more than this proportion of the slot duration, e.g. 0.5. Blocks are
+	/// authored regardless of the clock if unset.
+	#[arg(long, value_name = "PROPORTION", value_parser = parse_slot_proportion)]
+	pub refuse_authoring_at_clock_drift: Option<f32>,
+}
+
+fn parse_slot_proportion(s: &str) -> Result<f32, String> {
+	let proportion = s.parse::<f32>().map_err(|e| e.to_string())?;
+	if proportion > 0.0 && proportion <= 1.0 {
+		Ok(proportion)
+	} else {
+		Err("must be above 0 and at most 1".into())
+	}
 }
 
 #[derive(Debug, clap::Subcommand)]
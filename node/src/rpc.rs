@@ -12,6 +12,8 @@ use qf_runtime::{
 	opaque::{Block, Hash},
 	AccountId, Balance, BlockNumber, Nonce,
 };
+use qfc_consensus_spin::ClockDrift;
+use qfp_consensus_spin::sr25519::{AuthorityId as SpinId, AuthorityPair as SpinPair};
 use sc_consensus_grandpa::{
 	FinalityProofProvider, GrandpaJustificationStream, SharedAuthoritySet, SharedVoterState,
 };
@@ -35,6 +37,12 @@ pub struct GrandpaDeps<B> {
 	pub finality_provider: Arc<FinalityProofProvider<B, Block>>,
 }
 
+/// Extra dependencies for SPIN
+pub struct SpinDeps {
+	/// The estimated offset of the local clock.
+	pub clock_drift: ClockDrift,
+}
+
 /// Full client dependencies.
 pub struct FullDeps<C, P, B> {
 	/// The client instance to use.
@@ -43,6 +51,8 @@ pub struct FullDeps<C, P, B> {
 	pub pool: Arc<P>,
 	/// GRANDPA specific dependencies.
 	pub grandpa: GrandpaDeps<B>,
+	/// SPIN specific dependencies.
+	pub spin: SpinDeps,
 }
 
 /// Instantiate all full RPC extensions.
@@ -52,20 +62,27 @@ pub fn create_full<C, P, B>(
 where
 	C: ProvideRuntimeApi<Block>,
 	C: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError> + 'static,
+	C: sc_client_api::BlockchainEvents<Block>,
 	C: Send + Sync + 'static,
 	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Nonce>,
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
 	C::Api: BlockBuilder<Block>,
+	C::Api: qfc_consensus_spin::SpinApi<Block, SpinId, BlockNumber>
+		+ qfc_consensus_spin::SpinSlotScheduleApi<Block>,
+	C::Api: qfp_spin_anchoring::SpinAnchoringApi<Block, AccountId, Balance, BlockNumber>,
 	P: TransactionPool + 'static,
 	B: sc_client_api::Backend<Block> + Send + Sync + 'static,
 	B::State: sc_client_api::backend::StateBackend<sp_runtime::traits::HashingFor<Block>>,
 {
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApiServer};
+	use qfc_consensus_spin_rpc::{Spin, SpinApiServer, SpinSchedule, SpinScheduleApiServer};
+	use qfc_spin_anchoring_rpc::{Anchoring, AnchoringApiServer};
 	use sc_consensus_grandpa_rpc::{Grandpa, GrandpaApiServer};
 	use substrate_frame_rpc_system::{System, SystemApiServer};
 
 	let mut module = RpcModule::new(());
-	let FullDeps { client, pool, grandpa } = deps;
+	let FullDeps { client, pool, grandpa, spin } = deps;
+	let SpinDeps { clock_drift } = spin;
 	let GrandpaDeps {
 		shared_voter_state,
 		shared_authority_set,
@@ -75,10 +92,10 @@ where
 	} = grandpa;
 
 	module.merge(System::new(client.clone(), pool).into_rpc())?;
-	module.merge(TransactionPayment::new(client).into_rpc())?;
+	module.merge(TransactionPayment::new(client.clone()).into_rpc())?;
 	module.merge(
 		Grandpa::new(
-			subscription_executor,
+			subscription_executor.clone(),
 			shared_authority_set.clone(),
 			shared_voter_state,
 			justification_stream,
@@ -86,6 +103,14 @@ where
 		)
 		.into_rpc(),
 	)?;
+	module.merge(Spin::new(clock_drift).into_rpc())?;
+	module.merge(
+		SpinSchedule::<_, _, SpinPair>::new(client.clone(), subscription_executor.clone())
+			.into_rpc(),
+	)?;
+	module.merge(
+		Anchoring::<_, _, AccountId, Balance>::new(client, subscription_executor).into_rpc(),
+	)?;
 
 	// Extend this RPC with a custom API by using the following syntax.
 	// `YourRpcStruct` should have a reference to a client, which is needed
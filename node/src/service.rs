@@ -1,15 +1,20 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over
 //! substrate service.
 
+use crate::cli::SpinParams;
 use futures::FutureExt;
 use qf_runtime::{self, apis::RuntimeApi, opaque::Block};
-use qfc_consensus_spin::{ImportQueueParams, SlotProportion, StartSpinParams};
-use qfp_consensus_spin::sr25519::AuthorityPair as SpinPair;
+use qfc_consensus_spin::{
+	ClockDriftLimits, ImportQueueParams, LeaderHandoffParams, SlotProportion, StartSpinParams,
+};
+use qfp_consensus_spin::{sr25519::AuthorityPair as SpinPair, SpinMultiBlockApi};
 use sc_client_api::{Backend, BlockBackend};
 use sc_consensus_grandpa as grandpa;
 use sc_service::{error::Error as ServiceError, Configuration, TaskManager, WarpSyncConfig};
 use sc_telemetry::{Telemetry, TelemetryWorker};
 use sc_transaction_pool_api::OffchainTransactionPoolFactory;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
 use std::{sync::Arc, time::Duration};
 
 pub(crate) type FullClient = sc_service::TFullClient<
@@ -28,16 +33,17 @@ pub type Service = sc_service::PartialComponents<
 	FullClient,
 	FullBackend,
 	FullSelectChain,
-	sc_consensus::DefaultImportQueue<Block>,
+	qfc_consensus_spin::SpinImportQueue<Block>,
 	sc_transaction_pool::TransactionPoolHandle<Block, FullClient>,
 	(
 		sc_consensus_grandpa::GrandpaBlockImport<FullBackend, Block, FullClient, FullSelectChain>,
 		sc_consensus_grandpa::LinkHalf<Block, FullClient, FullSelectChain>,
 		Option<Telemetry>,
+		qfc_consensus_spin::ClockDrift,
 	),
 >;
 
-pub fn new_partial(config: &Configuration) -> Result<Service, ServiceError> {
+pub fn new_partial(config: &Configuration, spin: &SpinParams) -> Result<Service, ServiceError> {
 	let telemetry = config
 		.telemetry_endpoints
 		.clone()
@@ -85,6 +91,10 @@ pub fn new_partial(config: &Configuration) -> Result<Service, ServiceError> {
 		telemetry.as_ref().map(|x| x.handle()),
 	)?;
 
+	let clock_drift = qfc_consensus_spin::ClockDrift::new(ClockDriftLimits {
+		refuse_at: spin.refuse_authoring_at_clock_drift.map(SlotProportion::new),
+		..Default::default()
+	});
 	let cidp_client = client.clone();
 	let import_queue = qfc_consensus_spin::import_queue::<SpinPair, _, _, _, _, _>(
 		ImportQueueParams {
@@ -94,16 +104,17 @@ pub fn new_partial(config: &Configuration) -> Result<Service, ServiceError> {
 			create_inherent_data_providers: move |parent_hash, _| {
 				let cidp_client = cidp_client.clone();
 				async move {
-					let slot_duration = qfc_consensus_spin::standalone::slot_duration_at(
+					let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
+					let slot_schedule = qfc_consensus_spin::standalone::slot_schedule_at(
 						&*cidp_client,
 						parent_hash,
+						timestamp.as_millis(),
 					)?;
-					let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
 
 					let slot =
-                        qfp_consensus_spin::inherents::InherentDataProvider::from_timestamp_and_slot_duration(
+                        qfp_consensus_spin::inherents::InherentDataProvider::from_timestamp_and_slot_schedule(
                             *timestamp,
-                            slot_duration,
+                            slot_schedule,
                         );
 
 					Ok((slot, timestamp))
@@ -114,6 +125,10 @@ pub fn new_partial(config: &Configuration) -> Result<Service, ServiceError> {
 			check_for_equivocation: Default::default(),
 			telemetry: telemetry.as_ref().map(|x| x.handle()),
 			compatibility_mode: Default::default(),
+			offchain_tx_pool_factory: OffchainTransactionPoolFactory::new(transaction_pool.clone()),
+			future_headers: Default::default(),
+			clock_drift: clock_drift.clone(),
+			max_blocks_per_slot: spin.blocks_per_slot,
 		},
 	)?;
 
@@ -125,7 +140,7 @@ pub fn new_partial(config: &Configuration) -> Result<Service, ServiceError> {
 		keystore_container,
 		select_chain,
 		transaction_pool,
-		other: (grandpa_block_import, grandpa_link, telemetry),
+		other: (grandpa_block_import, grandpa_link, telemetry, clock_drift),
 	})
 }
 
@@ -134,6 +149,7 @@ pub fn new_full<
 	N: sc_network::NetworkBackend<Block, <Block as sp_runtime::traits::Block>::Hash>,
 >(
 	config: Configuration,
+	spin: SpinParams,
 ) -> Result<TaskManager, ServiceError> {
 	let sc_service::PartialComponents {
 		client,
@@ -143,8 +159,23 @@ pub fn new_full<
 		keystore_container,
 		select_chain,
 		transaction_pool,
-		other: (block_import, grandpa_link, mut telemetry),
-	} = new_partial(&config)?;
+		other: (block_import, grandpa_link, mut telemetry, clock_drift),
+	} = new_partial(&config, &spin)?;
+
+	// the slot leader may build several blocks per slot only if the runtime
+	// does not report them as equivocations.
+	if spin.blocks_per_slot > 1 {
+		let allowed = client
+			.runtime_api()
+			.allow_multiple_blocks_per_slot(client.info().best_hash)
+			.unwrap_or(false);
+		if !allowed {
+			return Err(ServiceError::Other(format!(
+				"--blocks-per-slot {} needs a runtime that allows multiple blocks per slot",
+				spin.blocks_per_slot,
+			)));
+		}
+	}
 
 	let mut net_config = sc_network::config::FullNetworkConfiguration::<
 		Block,
@@ -154,10 +185,9 @@ pub fn new_full<
 	let metrics = N::register_notification_metrics(config.prometheus_registry());
 
 	let peer_store_handle = net_config.peer_store_handle();
-	let grandpa_protocol_name = sc_consensus_grandpa::protocol_standard_name(
-		&client.block_hash(0).ok().flatten().expect("Genesis block exists; qed"),
-		&config.chain_spec,
-	);
+	let genesis_hash = client.block_hash(0).ok().flatten().expect("Genesis block exists; qed");
+	let grandpa_protocol_name =
+		sc_consensus_grandpa::protocol_standard_name(&genesis_hash, &config.chain_spec);
 	let (grandpa_protocol_config, grandpa_notification_service) =
 		sc_consensus_grandpa::grandpa_peers_set_config::<_, N>(
 			grandpa_protocol_name.clone(),
@@ -166,6 +196,23 @@ pub fn new_full<
 		);
 	net_config.add_notification_protocol(grandpa_protocol_config);
 
+	// the slot leaders announce the hand-off to the next leader to the other
+	// authorities only.
+	let handoff_network = config.role.is_authority().then(|| {
+		let peer_store = net_config.peer_store_handle();
+		let (handoff_protocol_config, handoff_notification_service) =
+			qfc_consensus_spin::handoff_peers_set_config::<_, N>(
+				qfc_consensus_spin::handoff_protocol_name(
+					&genesis_hash,
+					config.chain_spec.fork_id(),
+				),
+				metrics.clone(),
+				peer_store.clone(),
+			);
+		net_config.add_notification_protocol(handoff_protocol_config);
+		(handoff_notification_service, peer_store)
+	});
+
 	let warp_sync = Arc::new(sc_consensus_grandpa::warp_proof::NetworkProvider::new(
 		backend.clone(),
 		grandpa_link.shared_authority_set().clone(),
@@ -208,6 +255,14 @@ pub fn new_full<
 		);
 	}
 
+	if let Some(registry) = config.prometheus_registry() {
+		task_manager.spawn_handle().spawn(
+			"spin-anchoring-metrics",
+			None,
+			crate::anchoring::report_lag(client.clone(), registry.clone()),
+		);
+	}
+
 	let role = config.role;
 	let force_authoring = config.force_authoring;
 	let backoff_authoring_blocks: Option<()> = None;
@@ -228,6 +283,7 @@ pub fn new_full<
 
 		let client = client.clone();
 		let pool = transaction_pool.clone();
+		let clock_drift = clock_drift.clone();
 
 		let rpc_extensions_builder =
 			Box::new(move |subscription_executor: crate::rpc::SubscriptionTaskExecutor| {
@@ -241,6 +297,7 @@ pub fn new_full<
 						subscription_executor: subscription_executor.clone(),
 						finality_provider: finality_proof_provider.clone(),
 					},
+					spin: crate::rpc::SpinDeps { clock_drift: clock_drift.clone() },
 				};
 				crate::rpc::create_full(deps).map_err(Into::into)
 			});
@@ -272,25 +329,32 @@ pub fn new_full<
 			telemetry.as_ref().map(|x| x.handle()),
 		);
 
-		let slot_duration = qfc_consensus_spin::slot_duration(&*client)?;
+		let cidp_client = client.clone();
 
 		let spin = qfc_consensus_spin::start_spin::<SpinPair, _, _, _, _, _, _, _, _, _, _>(
 			StartSpinParams {
-				slot_duration,
 				client,
 				select_chain,
 				block_import,
 				proposer_factory,
-				create_inherent_data_providers: move |_, ()| async move {
-					let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
-
-					let slot =
-                        qfp_consensus_spin::inherents::InherentDataProvider::from_timestamp_and_slot_duration(
-                            *timestamp,
-                            slot_duration,
-                        );
-
-					Ok((slot, timestamp))
+				create_inherent_data_providers: move |parent_hash, ()| {
+					let cidp_client = cidp_client.clone();
+					async move {
+						let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
+						let slot_schedule = qfc_consensus_spin::standalone::slot_schedule_at(
+							&*cidp_client,
+							parent_hash,
+							timestamp.as_millis(),
+						)?;
+
+						let slot =
+                            qfp_consensus_spin::inherents::InherentDataProvider::from_timestamp_and_slot_schedule(
+                                *timestamp,
+                                slot_schedule,
+                            );
+
+						Ok((slot, timestamp))
+					}
 				},
 				force_authoring,
 				backoff_authoring_blocks,
@@ -301,6 +365,17 @@ pub fn new_full<
 				max_block_proposal_slot_portion: None,
 				telemetry: telemetry.as_ref().map(|x| x.handle()),
 				compatibility_mode: Default::default(),
+				registry: prometheus_registry.clone(),
+				clock_drift,
+				blocks_per_slot: spin.blocks_per_slot,
+				handoff: handoff_network.map(|(notification_service, peer_store)| {
+					LeaderHandoffParams {
+						notification_service,
+						peer_store,
+						announce: true,
+						wait: SlotProportion::new(1f32 / 4f32),
+					}
+				}),
 			},
 		)?;
 
@@ -0,0 +1,248 @@
+//! Benchmarks for the secure finality anchoring.
+
+use super::*;
+use crate::Pallet as SpinAnchoring;
+use finality_grandpa::Message as GrandpaMessage;
+use frame_benchmarking::v2::*;
+use frame_support::traits::fungible::{Inspect, Mutate};
+use frame_system::RawOrigin;
+use sp_consensus_grandpa::{ConsensusLog, ScheduledChange, GRANDPA_ENGINE_ID};
+use sp_runtime::{
+	traits::{One, Saturating, Zero},
+	Digest, DigestItem, RuntimeAppPublic,
+};
+
+const ROUND: u64 = 1;
+const SET_ID: SetId = 1;
+
+/// Register `count` relayers that can afford the bond.
+fn register_relayers<T: Config>(count: u32) -> Vec<T::AccountId>
+where
+	T::Currency: Mutate<T::AccountId>,
+{
+	let balance = T::RelayerBond::get()
+		.saturating_mul(2u32.into())
+		.saturating_add(T::Currency::minimum_balance());
+	(0..count)
+		.map(|index| {
+			let relayer: T::AccountId = account("relayer", index, 0);
+			T::Currency::set_balance(&relayer, balance);
+			SpinAnchoring::<T>::do_add_relayer(relayer.clone()).expect("the bond is funded; qed");
+			relayer
+		})
+		.collect()
+}
+
+/// A relay chain header with `state_root` and `digest`.
+fn relay_header<T: Config>(
+	number: RelayNumberOf<T>,
+	parent_hash: H256,
+	state_root: H256,
+	digest: Digest,
+) -> T::RelayChainHeader {
+	T::RelayChainHeader::new(number, Default::default(), state_root, parent_hash, digest)
+}
+
+/// `count` authorities with distinct keys, for sets no one signs with.
+fn authorities(count: u32) -> AuthorityList {
+	(0..count)
+		.map(|index| {
+			let mut raw = [0u8; 32];
+			raw[..4].copy_from_slice(&index.to_le_bytes());
+			(AuthorityId::from(sp_core::ed25519::Public::from_raw(raw)), 1)
+		})
+		.collect()
+}
+
+/// A justification of `header` signed by `p` authorities, which become the
+/// relay chain authority set, precommitting the last of `v` descendants of it.
+fn justification<T: Config>(
+	header: &T::RelayChainHeader,
+	p: u32,
+	v: u32,
+) -> BoundedGrandpaJustification<T::RelayChainHeader> {
+	let voters = (0..p).map(|_| AuthorityId::generate_pair(None)).collect::<Vec<_>>();
+	RelayAuthoritySet::<T>::put(RelayAuthoritySetData {
+		set_id: SET_ID,
+		authorities: BoundedVec::truncate_from(voters.iter().map(|id| (id.clone(), 1)).collect()),
+		handed_off_at: Zero::zero(),
+	});
+
+	let mut votes_ancestries = Vec::new();
+	let mut tip = header.clone();
+	for _ in 0..v {
+		tip = relay_header::<T>(
+			*tip.number() + One::one(),
+			tip.hash(),
+			Default::default(),
+			Default::default(),
+		);
+		votes_ancestries.push(tip.clone());
+	}
+
+	let precommit =
+		finality_grandpa::Precommit { target_hash: tip.hash(), target_number: *tip.number() };
+	let payload = sp_consensus_grandpa::localized_payload(
+		ROUND,
+		SET_ID,
+		&GrandpaMessage::Precommit(precommit.clone()),
+	);
+	let precommits = voters
+		.into_iter()
+		.map(|id| finality_grandpa::SignedPrecommit {
+			precommit: precommit.clone(),
+			signature: id.sign(&payload).expect("the key was generated in the keystore; qed"),
+			id,
+		})
+		.collect();
+
+	BoundedGrandpaJustification {
+		round: ROUND,
+		commit: finality_grandpa::Commit {
+			target_hash: header.hash(),
+			target_number: *header.number(),
+			precommits,
+		},
+		votes_ancestries: BoundedVec::truncate_from(votes_ancestries),
+	}
+}
+
+#[benchmarks(where T::Currency: Mutate<T::AccountId>)]
+mod benchmarks {
+	use super::*;
+
+	/// The watermark advances and every other relayer, having attested another
+	/// block at the same height, is slashed.
+	#[benchmark]
+	fn note_anchor_verified(
+		p: Linear<1, { T::MaxAuthorities::get() }>,
+		v: Linear<0, MAX_VOTES_ANCESTRIES>,
+	) {
+		let header = HeaderFor::<T>::new(
+			One::one(),
+			Default::default(),
+			Default::default(),
+			Default::default(),
+			Default::default(),
+		);
+		frame_system::BlockHash::<T>::insert(*header.number(), header.hash());
+
+		let last_finalized = (*header.number(), header.hash()).encode();
+		let (para_root, last_finalized_proof) =
+			trie_proof(&SpinAnchoring::<T>::last_finalized_key(), &last_finalized);
+		let para_header = T::ParachainHeader::new(
+			One::one(),
+			Default::default(),
+			para_root,
+			Default::default(),
+			Default::default(),
+		);
+		let (relay_root, para_head_proof) =
+			trie_proof(&SpinAnchoring::<T>::para_head_key(), &para_header.encode().encode());
+		let relay_header =
+			relay_header::<T>(One::one(), Default::default(), relay_root, Default::default());
+		let proof = AnchorProof {
+			justification: justification::<T>(&relay_header, p, v),
+			relay_header,
+			para_head_proof,
+			last_finalized_proof,
+		};
+
+		let relayers = register_relayers::<T>(T::MaxRelayers::get());
+		Relayers::<T>::mutate(|relayers| {
+			relayers.iter_mut().skip(1).for_each(|relayer| {
+				relayer.attestation = Some((*header.number(), Default::default()))
+			})
+		});
+		RequiredAttestations::<T>::put(1);
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(relayers[0].clone()), proof);
+
+		assert_eq!(SecureUpTo::<T>::get(), *header.number());
+		assert_eq!(Relayers::<T>::get().len(), 1);
+	}
+
+	#[benchmark]
+	fn add_relayer() {
+		let mut relayers = register_relayers::<T>(T::MaxRelayers::get());
+		let relayer = relayers.pop().expect("there is at least a relayer; qed");
+		SpinAnchoring::<T>::do_remove_relayer(&relayer).expect("the relayer is registered; qed");
+
+		#[extrinsic_call]
+		_(RawOrigin::Root, relayer.clone());
+
+		assert!(Relayers::<T>::get().iter().any(|status| status.account == relayer));
+	}
+
+	#[benchmark]
+	fn remove_relayer() {
+		let relayer = register_relayers::<T>(T::MaxRelayers::get())
+			.pop()
+			.expect("there is at least a relayer; qed");
+
+		#[extrinsic_call]
+		_(RawOrigin::Root, relayer.clone());
+
+		assert!(Relayers::<T>::get().iter().all(|status| status.account != relayer));
+	}
+
+	#[benchmark]
+	fn retire_relayer() {
+		let relayer = register_relayers::<T>(T::MaxRelayers::get())
+			.pop()
+			.expect("there is at least a relayer; qed");
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(relayer.clone()));
+
+		assert!(Relayers::<T>::get().iter().all(|status| status.account != relayer));
+	}
+
+	#[benchmark]
+	fn set_required_attestations() {
+		let required = T::MaxRelayers::get();
+
+		#[extrinsic_call]
+		_(RawOrigin::Root, required);
+
+		assert_eq!(RequiredAttestations::<T>::get(), required);
+	}
+
+	#[benchmark]
+	fn set_relay_authority_set(a: Linear<1, { T::MaxAuthorities::get() }>) {
+		let authorities = authorities(a);
+
+		#[extrinsic_call]
+		_(RawOrigin::Root, SET_ID, authorities, Zero::zero());
+
+		assert_eq!(
+			RelayAuthoritySet::<T>::get().map(|set| set.authorities.len()),
+			Some(a as usize)
+		);
+	}
+
+	/// The handed off set has as many authorities as allowed.
+	#[benchmark]
+	fn hand_off_relay_authority_set(
+		p: Linear<1, { T::MaxAuthorities::get() }>,
+		v: Linear<0, MAX_VOTES_ANCESTRIES>,
+	) {
+		let change = ConsensusLog::<RelayNumberOf<T>>::ScheduledChange(ScheduledChange {
+			next_authorities: authorities(T::MaxAuthorities::get()),
+			delay: Zero::zero(),
+		});
+		let digest =
+			Digest { logs: vec![DigestItem::Consensus(GRANDPA_ENGINE_ID, change.encode())] };
+		let header = relay_header::<T>(One::one(), Default::default(), Default::default(), digest);
+		let justification = justification::<T>(&header, p, v);
+		let relayer = register_relayers::<T>(1).pop().expect("a relayer was registered; qed");
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(relayer), justification, header);
+
+		assert_eq!(RelayAuthoritySet::<T>::get().map(|set| set.set_id), Some(SET_ID + 1));
+	}
+
+	impl_benchmark_test_suite!(SpinAnchoring, crate::mock::new_test_ext(), crate::mock::Test);
+}
@@ -1,81 +1,771 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use frame_support::traits::Get;
+extern crate alloc;
+
+use alloc::vec::Vec;
+use codec::{Decode, DecodeAll, DecodeWithMemTracking, Encode};
+use core::marker::PhantomData;
+use frame_support::{
+	ensure,
+	storage::storage_prefix,
+	traits::{
+		fungible::{BalancedHold, Credit, MutateHold},
+		tokens::{imbalance::Imbalance, Precision},
+		ConstU32, Contains, Get, OnUnbalanced,
+	},
+	BoundedVec, StorageHasher, Twox64Concat,
+};
 use frame_system::pallet_prelude::*;
+use qfp_grandpa_justification::{
+	scheduled_change, verify_justification, Error as JustificationError,
+};
+use qfp_spin_anchoring::{AnchorRecord, RelayerStatus};
+use scale_info::TypeInfo;
+use sp_consensus_grandpa::{AuthorityId, AuthorityList, AuthorityWeight, SetId};
+use sp_core::H256;
+use sp_runtime::{
+	traits::{BlakeTwo256, Hash as HashT, Header as HeaderT, Zero},
+	DispatchResult,
+};
+use sp_trie::{LayoutV1, StorageProof};
 
 pub use pallet::*;
+pub use qfp_grandpa_justification::{BoundedGrandpaJustification, MAX_VOTES_ANCESTRIES};
+pub use weights::WeightInfo;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+mod mock;
+mod tests;
+pub mod weights;
+
+/// The maximum number of trie nodes in each storage proof of an [`AnchorProof`].
+pub const MAX_PROOF_NODES: u32 = 64;
+
+/// The trie nodes proving a value in the state of a block.
+pub type StorageProofNodes = BoundedVec<Vec<u8>, ConstU32<MAX_PROOF_NODES>>;
+
+/// A proof that the anchoring parachain finalized a block of this chain.
+///
+/// The relay chain authority set justifies `relay_header`, the state of which
+/// holds the head of the anchoring parachain. The state of that head holds the
+/// last block of this chain the bridge pallet of the parachain finalized.
+#[derive(Clone, PartialEq, Eq, Encode, Decode, DecodeWithMemTracking, TypeInfo, Debug)]
+pub struct AnchorProof<RelayHeader: HeaderT> {
+	/// A justification of `relay_header` by the relay chain authority set.
+	pub justification: BoundedGrandpaJustification<RelayHeader>,
+	/// The header of the justified relay chain block.
+	pub relay_header: RelayHeader,
+	/// The proof of the parachain head in the state of `relay_header`.
+	pub para_head_proof: StorageProofNodes,
+	/// The proof of the `LastFinalized` of the bridge pallet in the state of
+	/// the parachain head.
+	pub last_finalized_proof: StorageProofNodes,
+}
 
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
 	use frame_support::{
 		pallet_prelude::{ValueQuery, *},
-		DefaultNoBound,
+		CloneNoBound, DefaultNoBound, EqNoBound, PartialEqNoBound, RuntimeDebugNoBound,
 	};
 	use sp_runtime::traits::BadOrigin;
 
+	/// GRANDPA authorities with their weights, at most `MaxAuthorities` of them.
+	pub type BoundedAuthorityList<T> =
+		BoundedVec<(AuthorityId, AuthorityWeight), <T as Config>::MaxAuthorities>;
+
+	/// The GRANDPA authority set of the relay chain, with the relay chain block
+	/// that handed it off.
+	#[derive(
+		CloneNoBound,
+		PartialEqNoBound,
+		EqNoBound,
+		Encode,
+		Decode,
+		TypeInfo,
+		MaxEncodedLen,
+		RuntimeDebugNoBound,
+	)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound(T: Config))]
+	pub struct RelayAuthoritySetData<T: Config> {
+		pub set_id: SetId,
+		pub authorities: BoundedAuthorityList<T>,
+		pub handed_off_at: RelayNumberOf<T>,
+	}
+
 	#[pallet::config]
-	pub trait Config: frame_system::Config {}
+	pub trait Config: frame_system::Config {
+		/// The header of the relay chain validating the anchoring parachain.
+		type RelayChainHeader: HeaderT<Hash = H256>;
+
+		/// The header of the anchoring parachain.
+		type ParachainHeader: HeaderT<Hash = H256>;
+
+		/// The id of the anchoring parachain on the relay chain.
+		#[pallet::constant]
+		type ParaId: Get<u32>;
+
+		/// The name of the bridge pallet in the runtime of the anchoring
+		/// parachain.
+		type BridgePalletName: Get<&'static str>;
+
+		/// The maximum number of authorities in a relay chain authority set.
+		#[pallet::constant]
+		type MaxAuthorities: Get<u32>;
+
+		/// The currency the bonds of the relayers are held in.
+		type Currency: MutateHold<Self::AccountId, Reason = Self::RuntimeHoldReason>
+			+ BalancedHold<Self::AccountId>;
+
+		/// The overarching hold reason.
+		type RuntimeHoldReason: From<HoldReason>;
+
+		/// The amount held from every relayer.
+		#[pallet::constant]
+		type RelayerBond: Get<BalanceOf<Self>>;
+
+		/// The maximum number of relayers.
+		#[pallet::constant]
+		type MaxRelayers: Get<u32>;
+
+		/// Handler for the bonds of slashed relayers.
+		type Slash: OnUnbalanced<Credit<Self::AccountId, Self::Currency>>;
+
+		/// The number of the most recent anchors kept.
+		#[pallet::constant]
+		type MaxAnchors: Get<u32>;
+
+		/// The number of blocks without the watermark advancing after which
+		/// the anchoring is stalled.
+		#[pallet::constant]
+		type StallThreshold: Get<BlockNumberFor<Self>>;
+
+		/// Weight information for the calls of this pallet.
+		type WeightInfo: WeightInfo;
+	}
 
 	#[pallet::pallet]
 	pub struct Pallet<T>(core::marker::PhantomData<T>);
 
+	/// A reason for the pallet placing a hold on funds.
+	#[pallet::composite_enum]
+	pub enum HoldReason {
+		/// The bond of a relayer.
+		RelayerBond,
+	}
+
+	/// The registered relayers.
+	#[pallet::storage]
+	pub type Relayers<T: Config> =
+		StorageValue<_, BoundedVec<RelayerOf<T>, T::MaxRelayers>, ValueQuery>;
+
+	/// How many relayers must attest a block before the watermark advances to
+	/// it.
 	#[pallet::storage]
-	pub type Relayer<T: Config> = StorageValue<_, T::AccountId>;
+	pub type RequiredAttestations<T: Config> = StorageValue<_, u32, ValueQuery>;
 
 	/// Highest fast-chain block number that is securely anchored.
 	#[pallet::storage]
 	pub type SecureUpTo<T: Config> = StorageValue<_, BlockNumberFor<T>, ValueQuery>;
 
+	/// The block the watermark last advanced in, or the first block the pallet
+	/// ran in until it advances. Zero until the pallet ran.
+	#[pallet::storage]
+	pub type LastAdvancedAt<T: Config> = StorageValue<_, BlockNumberFor<T>, ValueQuery>;
+
+	/// Whether the watermark did not advance for `StallThreshold` blocks.
+	#[pallet::storage]
+	pub type Stalled<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	/// The number of anchors noted so far.
+	#[pallet::storage]
+	pub type AnchorCount<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	/// The most recent anchors, a ring buffer indexed by the anchor count
+	/// modulo `MaxAnchors`.
+	#[pallet::storage]
+	pub type Anchors<T: Config> = StorageMap<_, Twox64Concat, u32, AnchorOf<T>>;
+
+	/// The relay chain authority set that must justify the relay chain blocks
+	/// anchor proofs start from.
+	#[pallet::storage]
+	pub type RelayAuthoritySet<T: Config> = StorageValue<_, RelayAuthoritySetData<T>>;
+
 	#[pallet::genesis_config]
 	#[derive(DefaultNoBound)]
 	pub struct GenesisConfig<T: Config> {
-		pub relayer: Option<T::AccountId>,
+		pub relayers: Vec<T::AccountId>,
+		pub required_attestations: u32,
 	}
 
 	#[pallet::genesis_build]
 	impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
 		fn build(&self) {
-			Relayer::<T>::set(self.relayer.clone());
+			for relayer in &self.relayers {
+				Pallet::<T>::do_add_relayer(relayer.clone())
+					.expect("genesis relayers can afford the bond; qed");
+			}
+			RequiredAttestations::<T>::put(self.required_attestations);
 		}
 	}
 
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
-		/// Secure finality advanced to `up_to`.
-		SecureFinalityAdvanced { up_to: BlockNumberFor<T> },
+		/// Secure finality advanced to `up_to`, the block with `hash`.
+		SecureFinalityAdvanced { up_to: BlockNumberFor<T>, hash: T::Hash },
+		/// A relayer attested that a block is anchored.
+		AnchorAttested { relayer: T::AccountId, number: BlockNumberFor<T>, hash: T::Hash },
+		/// A relayer was registered, `bond` is held from its account.
+		RelayerAdded { relayer: T::AccountId, bond: BalanceOf<T> },
+		/// A relayer was removed and its bond released.
+		RelayerRemoved { relayer: T::AccountId },
+		/// A relayer attested another block than the one agreed on, and
+		/// `amount` of its bond was slashed.
+		RelayerSlashed { relayer: T::AccountId, amount: BalanceOf<T> },
+		/// The number of attestations required to advance the watermark changed.
+		RequiredAttestationsSet { required: u32 },
+		/// The watermark did not advance from `secure_up_to` since block `since`
+		/// for `StallThreshold` blocks.
+		AnchorStalled { secure_up_to: BlockNumberFor<T>, since: BlockNumberFor<T> },
+		/// The watermark advanced to `up_to` after having stalled.
+		AnchorResumed { up_to: BlockNumberFor<T> },
+		/// The relay chain block `handed_off_at` handed off to the relay chain
+		/// authority set `set_id`.
+		RelayAuthoritySetUpdated { set_id: SetId, handed_off_at: RelayNumberOf<T> },
+		/// The relay chain authority set was overridden by `Root`.
+		RelayAuthoritySetOverridden { set_id: SetId, authorities: u32 },
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(n: BlockNumberFor<T>) -> Weight {
+			if Stalled::<T>::get() {
+				return T::DbWeight::get().reads(1);
+			}
+
+			// the stall is counted from the first block the pallet runs in, be
+			// it after genesis or after the runtime upgrade that added it.
+			let since = LastAdvancedAt::<T>::get();
+			if since.is_zero() {
+				LastAdvancedAt::<T>::put(n);
+				return T::DbWeight::get().reads_writes(2, 1);
+			}
+			if n.saturating_sub(since) < T::StallThreshold::get() {
+				return T::DbWeight::get().reads(2);
+			}
+
+			Stalled::<T>::put(true);
+			let secure_up_to = SecureUpTo::<T>::get();
+			Self::deposit_event(Event::<T>::AnchorStalled { secure_up_to, since });
+			T::DbWeight::get().reads_writes(3, 2)
+		}
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The justified block is not above the watermark.
+		AlreadySecure,
+		/// The account is a relayer already.
+		AlreadyRelayer,
+		/// The account is not a relayer.
+		NotRelayer,
+		/// There are as many relayers as allowed.
+		TooManyRelayers,
+		/// The relayer attested a block above the watermark.
+		PendingAttestation,
+		/// The required number of attestations is zero or above the maximum
+		/// number of relayers.
+		InvalidThreshold,
+		/// The relay chain authority set is not set yet.
+		RelayAuthoritySetNotInitialized,
+		/// The relay chain header is not the target of the justification.
+		RelayHeaderMismatch,
+		/// A storage proof misses nodes or proves a value that does not decode.
+		InvalidStorageProof,
+		/// The relay chain state has no head of the anchoring parachain.
+		ParachainHeadNotFound,
+		/// The bridge pallet of the anchoring parachain finalized no block yet.
+		NothingFinalized,
+		/// The relay chain header does not signal an authority set change.
+		NoAuthoritySetChange,
+		/// The relay chain authority set has more authorities than
+		/// `MaxAuthorities`.
+		TooManyAuthorities,
+		/// The relay chain authority set is empty.
+		EmptyAuthoritySet,
+		/// The justification has no precommits.
+		NoPrecommits,
+		/// A precommit is not for the commit target or a descendant of it
+		/// through the votes ancestries.
+		PrecommitIsNotCommitDescendant,
+		/// A precommit is signed by a key outside the relay chain authority set.
+		UnknownAuthority,
+		/// A precommit signature is invalid.
+		BadSignature,
+		/// The precommits are signed by less than two thirds of the weight of
+		/// the relay chain authority set.
+		InsufficientWeight,
+		ComputationOverflow,
+		/// The votes ancestries contain duplicate headers or headers no
+		/// precommit descends through.
+		RedundantVotesAncestries,
 	}
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
-		/// Call when anchor verification completes
+		/// Attest that the block `proof` shows the anchoring parachain
+		/// finalized is anchored.
+		///
+		/// The relay chain block of the proof must be justified by more than
+		/// two thirds of the weight of the relay chain authority set. The
+		/// watermark advances to the block once the required number of
+		/// relayers attested it and it is a block of this chain. The relayers
+		/// that attested another block at the same height then lose their bond.
 		#[pallet::call_index(0)]
-		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		#[pallet::weight(T::WeightInfo::note_anchor_verified(
+			proof.justification.commit.precommits.len() as u32,
+			proof.justification.votes_ancestries.len() as u32,
+		))]
 		pub fn note_anchor_verified(
 			origin: OriginFor<T>,
-			up_to: BlockNumberFor<T>,
+			proof: AnchorProof<T::RelayChainHeader>,
 		) -> DispatchResult {
-			match ensure_signed_or_root(origin)? {
-				Some(signer) => ensure!(Relayer::<T>::get() == Some(signer), BadOrigin),
-				None => {}, // root is allowed
-			}
+			let who = ensure_signed(origin)?;
+			let mut relayers = Relayers::<T>::get().into_inner();
+			let index =
+				relayers.iter().position(|relayer| relayer.account == who).ok_or(BadOrigin)?;
+
+			let (number, hash) = Self::check_anchor_proof(&proof)?;
+			ensure!(number > SecureUpTo::<T>::get(), Error::<T>::AlreadySecure);
+
+			relayers[index].attestation = Some((number, hash));
+			Self::deposit_event(Event::<T>::AnchorAttested { relayer: who, number, hash });
+
+			// the block of this chain is the only one the relayers can agree on.
+			let agreed = frame_system::Pallet::<T>::block_hash(number);
+			let attestations = relayers
+				.iter()
+				.filter(|relayer| relayer.attestation == Some((number, agreed)))
+				.count();
+			if hash == agreed && attestations >= Self::required_attestations() as usize {
+				let now = frame_system::Pallet::<T>::block_number();
+				SecureUpTo::<T>::put(number);
+				LastAdvancedAt::<T>::put(now);
+				Self::note_anchor(AnchorRecord {
+					number,
+					hash,
+					anchored_at: now,
+					proof_ref: T::Hashing::hash_of(&proof),
+				});
+				Self::deposit_event(Event::<T>::SecureFinalityAdvanced { up_to: number, hash });
+				if Stalled::<T>::take() {
+					Self::deposit_event(Event::<T>::AnchorResumed { up_to: number });
+				}
 
-			let prev = SecureUpTo::<T>::get();
-			if up_to > prev {
-				SecureUpTo::<T>::put(up_to);
-				Self::deposit_event(Event::<T>::SecureFinalityAdvanced { up_to });
+				let mut conflicting = Vec::new();
+				relayers.retain_mut(|relayer| match relayer.attestation {
+					Some((at, attested)) if at == number && attested != agreed => {
+						conflicting.push(relayer.clone());
+						false
+					},
+					Some((at, _)) if at <= number => {
+						relayer.attestation = None;
+						true
+					},
+					_ => true,
+				});
+				conflicting.into_iter().for_each(Self::slash);
 			}
 
+			// relayers were at most removed, so they are within the bound.
+			Relayers::<T>::put(BoundedVec::truncate_from(relayers));
 			Ok(())
 		}
 
+		/// Register `relayer`, holding the relayer bond from its account.
 		#[pallet::call_index(1)]
-		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
-		pub fn set_relayer(origin: OriginFor<T>, new_relayer: T::AccountId) -> DispatchResult {
+		#[pallet::weight(T::WeightInfo::add_relayer())]
+		pub fn add_relayer(origin: OriginFor<T>, relayer: T::AccountId) -> DispatchResult {
 			ensure_root(origin)?;
-			Relayer::<T>::put(new_relayer);
+			Self::do_add_relayer(relayer)
+		}
+
+		/// Remove `relayer`, releasing its bond.
+		#[pallet::call_index(2)]
+		#[pallet::weight(T::WeightInfo::remove_relayer())]
+		pub fn remove_relayer(origin: OriginFor<T>, relayer: T::AccountId) -> DispatchResult {
+			ensure_root(origin)?;
+			Self::do_remove_relayer(&relayer)
+		}
+
+		/// Stop relaying and release the bond of the caller.
+		///
+		/// Relayers can only retire while they have not attested a block above
+		/// the watermark.
+		#[pallet::call_index(3)]
+		#[pallet::weight(T::WeightInfo::retire_relayer())]
+		pub fn retire_relayer(origin: OriginFor<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let pending = Relayers::<T>::get()
+				.iter()
+				.find(|relayer| relayer.account == who)
+				.ok_or(Error::<T>::NotRelayer)?
+				.attestation
+				.is_some();
+			ensure!(!pending, Error::<T>::PendingAttestation);
+			Self::do_remove_relayer(&who)
+		}
+
+		/// Set how many relayers must attest a block before the watermark
+		/// advances to it.
+		#[pallet::call_index(4)]
+		#[pallet::weight(T::WeightInfo::set_required_attestations())]
+		pub fn set_required_attestations(origin: OriginFor<T>, required: u32) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!((1..=T::MaxRelayers::get()).contains(&required), Error::<T>::InvalidThreshold);
+			RequiredAttestations::<T>::put(required);
+			Self::deposit_event(Event::<T>::RequiredAttestationsSet { required });
+			Ok(())
+		}
+
+		/// Set the relay chain authority set anchor proofs are checked against.
+		///
+		/// The set otherwise follows the hand-offs relayers submit, so this is
+		/// meant for bootstrapping and emergencies only.
+		#[pallet::call_index(5)]
+		#[pallet::weight(T::WeightInfo::set_relay_authority_set(authorities.len() as u32))]
+		pub fn set_relay_authority_set(
+			origin: OriginFor<T>,
+			set_id: SetId,
+			authorities: AuthorityList,
+			handed_off_at: RelayNumberOf<T>,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			let authorities = Self::bound_authorities(authorities)?;
+			let len = authorities.len() as u32;
+			RelayAuthoritySet::<T>::put(RelayAuthoritySetData {
+				set_id,
+				authorities,
+				handed_off_at,
+			});
+			Self::deposit_event(Event::<T>::RelayAuthoritySetOverridden {
+				set_id,
+				authorities: len,
+			});
+			Ok(())
+		}
+
+		/// Hand off to the relay chain authority set signalled in `header`.
+		///
+		/// `header` must be justified by the current relay chain authority set.
+		/// The next set is taken on as soon as it is signalled: the relay chain
+		/// enacts its standard changes in the signalling block, so the blocks
+		/// anchor proofs start from are justified by the next set from then on.
+		#[pallet::call_index(6)]
+		#[pallet::weight(T::WeightInfo::hand_off_relay_authority_set(
+			justification.commit.precommits.len() as u32,
+			justification.votes_ancestries.len() as u32,
+		))]
+		pub fn hand_off_relay_authority_set(
+			origin: OriginFor<T>,
+			justification: BoundedGrandpaJustification<T::RelayChainHeader>,
+			header: T::RelayChainHeader,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(Relayers::<T>::get().iter().any(|relayer| relayer.account == who), BadOrigin);
+
+			let current =
+				RelayAuthoritySet::<T>::get().ok_or(Error::<T>::RelayAuthoritySetNotInitialized)?;
+			Self::check_relay_header(&justification, &header, &current)?;
+
+			let (authorities, _) =
+				scheduled_change(&header).ok_or(Error::<T>::NoAuthoritySetChange)?;
+			let authorities = Self::bound_authorities(authorities)?;
+			let set_id = current.set_id.checked_add(1).ok_or(Error::<T>::ComputationOverflow)?;
+			let handed_off_at = *header.number();
+			RelayAuthoritySet::<T>::put(RelayAuthoritySetData {
+				set_id,
+				authorities,
+				handed_off_at,
+			});
+			Self::deposit_event(Event::<T>::RelayAuthoritySetUpdated { set_id, handed_off_at });
 			Ok(())
 		}
 	}
 }
+
+/// The block number of the relay chain.
+pub type RelayNumberOf<T> = <<T as Config>::RelayChainHeader as HeaderT>::Number;
+
+/// The balance of the currency the bonds are held in.
+pub type BalanceOf<T> = <<T as Config>::Currency as frame_support::traits::fungible::Inspect<
+	<T as frame_system::Config>::AccountId,
+>>::Balance;
+
+/// An anchor of the runtime.
+pub type AnchorOf<T> = AnchorRecord<BlockNumberFor<T>, <T as frame_system::Config>::Hash>;
+
+/// The status of a relayer of the runtime.
+pub type RelayerOf<T> = RelayerStatus<
+	<T as frame_system::Config>::AccountId,
+	BalanceOf<T>,
+	BlockNumberFor<T>,
+	<T as frame_system::Config>::Hash,
+>;
+
+impl<T> From<JustificationError> for Error<T> {
+	fn from(error: JustificationError) -> Self {
+		match error {
+			JustificationError::NoPrecommits => Self::NoPrecommits,
+			JustificationError::EmptyAuthoritySet => Self::EmptyAuthoritySet,
+			JustificationError::ComputationOverflow => Self::ComputationOverflow,
+			JustificationError::PrecommitIsNotCommitDescendant =>
+				Self::PrecommitIsNotCommitDescendant,
+			JustificationError::RedundantVotesAncestries => Self::RedundantVotesAncestries,
+			JustificationError::UnknownAuthority => Self::UnknownAuthority,
+			JustificationError::BadSignature => Self::BadSignature,
+			JustificationError::InsufficientWeight => Self::InsufficientWeight,
+		}
+	}
+}
+
+/// A [`frame_system::Config::BaseCallFilter`] rejecting the calls `Restricted`
+/// contains once the watermark did not advance for `Threshold` blocks.
+///
+/// `Threshold` is meant to be above `Config::StallThreshold`, so that
+/// high-value calls are only restricted once a stall went unresolved.
+pub struct RestrictWhenStalled<T, Threshold, Restricted>(PhantomData<(T, Threshold, Restricted)>);
+
+impl<T, Threshold, Restricted, Call> Contains<Call>
+	for RestrictWhenStalled<T, Threshold, Restricted>
+where
+	T: Config,
+	Threshold: Get<BlockNumberFor<T>>,
+	Restricted: Contains<Call>,
+{
+	fn contains(call: &Call) -> bool {
+		!Restricted::contains(call) || Pallet::<T>::blocks_since_advance() < Threshold::get()
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Highest fast-chain block number that is securely anchored.
+	pub fn secure_up_to() -> BlockNumberFor<T> {
+		SecureUpTo::<T>::get()
+	}
+
+	/// The registered relayers.
+	pub fn relayers() -> Vec<RelayerOf<T>> {
+		Relayers::<T>::get().into_inner()
+	}
+
+	/// How many relayers must attest a block before the watermark advances to
+	/// it.
+	pub fn required_attestations() -> u32 {
+		RequiredAttestations::<T>::get().max(1)
+	}
+
+	/// Whether the fast-chain block with `block_number` is securely anchored.
+	pub fn is_secure(block_number: BlockNumberFor<T>) -> bool {
+		block_number <= SecureUpTo::<T>::get()
+	}
+
+	/// Whether the fast-chain block with `block_number` and `hash` is securely
+	/// anchored. Blocks older than `BlockHashCount` are not known by their hash
+	/// any more and are never reported anchored.
+	pub fn is_anchored(block_number: BlockNumberFor<T>, hash: T::Hash) -> bool {
+		Self::is_secure(block_number) &&
+			frame_system::BlockHash::<T>::try_get(block_number).is_ok_and(|known| known == hash)
+	}
+
+	/// The number of blocks the current block is above the watermark.
+	pub fn anchor_lag() -> BlockNumberFor<T> {
+		frame_system::Pallet::<T>::block_number().saturating_sub(SecureUpTo::<T>::get())
+	}
+
+	/// The number of blocks since the watermark last advanced.
+	pub fn blocks_since_advance() -> BlockNumberFor<T> {
+		frame_system::Pallet::<T>::block_number().saturating_sub(LastAdvancedAt::<T>::get())
+	}
+
+	/// The earliest anchor still kept at or above `block_number`, if the
+	/// block is securely anchored.
+	pub fn anchor_covering(block_number: BlockNumberFor<T>) -> Option<AnchorOf<T>> {
+		if !Self::is_secure(block_number) {
+			return None;
+		}
+
+		// anchors are noted in ascending order, search the oldest one kept that
+		// is not below the block.
+		let (count, max_anchors) = (AnchorCount::<T>::get(), Self::max_anchors());
+		let (mut low, mut high) = (count.saturating_sub(max_anchors), count);
+		let mut covering = None;
+		while low < high {
+			let mid = low + (high - low) / 2;
+			match Anchors::<T>::get(mid % max_anchors) {
+				Some(anchor) if anchor.number >= block_number => {
+					covering = Some(anchor);
+					high = mid;
+				},
+				_ => low = mid + 1,
+			}
+		}
+		covering
+	}
+
+	/// Note `anchor` as the most recent one, replacing the oldest one kept if
+	/// there are `MaxAnchors` of them.
+	fn note_anchor(anchor: AnchorOf<T>) {
+		let count = AnchorCount::<T>::get();
+		Anchors::<T>::insert(count % Self::max_anchors(), anchor);
+		AnchorCount::<T>::put(count.saturating_add(1));
+	}
+
+	fn max_anchors() -> u32 {
+		T::MaxAnchors::get().max(1)
+	}
+
+	/// Register `account` as a relayer, holding the relayer bond from it.
+	pub fn do_add_relayer(account: T::AccountId) -> DispatchResult {
+		let mut relayers = Relayers::<T>::get();
+		ensure!(
+			relayers.iter().all(|relayer| relayer.account != account),
+			Error::<T>::AlreadyRelayer
+		);
+
+		let bond = T::RelayerBond::get();
+		T::Currency::hold(&HoldReason::RelayerBond.into(), &account, bond)?;
+		relayers
+			.try_push(RelayerStatus { account: account.clone(), bond, attestation: None })
+			.map_err(|_| Error::<T>::TooManyRelayers)?;
+		Relayers::<T>::put(relayers);
+
+		Self::deposit_event(Event::<T>::RelayerAdded { relayer: account, bond });
+		Ok(())
+	}
+
+	fn do_remove_relayer(account: &T::AccountId) -> DispatchResult {
+		let mut relayers = Relayers::<T>::get();
+		let index = relayers
+			.iter()
+			.position(|relayer| relayer.account == *account)
+			.ok_or(Error::<T>::NotRelayer)?;
+		let relayer = relayers.remove(index);
+		Relayers::<T>::put(relayers);
+
+		T::Currency::release(
+			&HoldReason::RelayerBond.into(),
+			account,
+			relayer.bond,
+			Precision::BestEffort,
+		)?;
+		Self::deposit_event(Event::<T>::RelayerRemoved { relayer: relayer.account });
+		Ok(())
+	}
+
+	fn slash(relayer: RelayerOf<T>) {
+		let (credit, _) =
+			T::Currency::slash(&HoldReason::RelayerBond.into(), &relayer.account, relayer.bond);
+		let amount = credit.peek();
+		T::Slash::on_unbalanced(credit);
+		Self::deposit_event(Event::<T>::RelayerSlashed { relayer: relayer.account, amount });
+	}
+
+	/// The block of this chain `proof` shows the anchoring parachain finalized.
+	fn check_anchor_proof(
+		proof: &AnchorProof<T::RelayChainHeader>,
+	) -> Result<(BlockNumberFor<T>, T::Hash), Error<T>> {
+		let relay_set =
+			RelayAuthoritySet::<T>::get().ok_or(Error::<T>::RelayAuthoritySetNotInitialized)?;
+		Self::check_relay_header(&proof.justification, &proof.relay_header, &relay_set)?;
+
+		let para_head: Vec<u8> = Self::read_proof(
+			*proof.relay_header.state_root(),
+			&proof.para_head_proof,
+			&Self::para_head_key(),
+		)?
+		.ok_or(Error::<T>::ParachainHeadNotFound)?;
+		let para_header = T::ParachainHeader::decode_all(&mut &para_head[..])
+			.map_err(|_| Error::<T>::InvalidStorageProof)?;
+
+		// the `FinalizedTarget` of the bridge pallet, a block number and hash.
+		Self::read_proof(
+			*para_header.state_root(),
+			&proof.last_finalized_proof,
+			&Self::last_finalized_key(),
+		)?
+		.ok_or(Error::<T>::NothingFinalized)
+	}
+
+	/// Check that `justification` proves the finality of `header` to the relay
+	/// chain authority set `set`.
+	fn check_relay_header(
+		justification: &BoundedGrandpaJustification<T::RelayChainHeader>,
+		header: &T::RelayChainHeader,
+		set: &RelayAuthoritySetData<T>,
+	) -> Result<(), Error<T>> {
+		ensure!(
+			justification.commit.target_hash == header.hash() &&
+				justification.commit.target_number == *header.number(),
+			Error::<T>::RelayHeaderMismatch
+		);
+		verify_justification(justification, set.set_id, &set.authorities).map_err(Into::into)
+	}
+
+	/// The value at `key` in the state with `root`, read from the trie nodes
+	/// of `proof`.
+	fn read_proof<V: Decode>(
+		root: H256,
+		proof: &StorageProofNodes,
+		key: &[u8],
+	) -> Result<Option<V>, Error<T>> {
+		let db = StorageProof::new(proof.iter().cloned()).into_memory_db::<BlakeTwo256>();
+		let Some(value) =
+			sp_trie::read_trie_value::<LayoutV1<BlakeTwo256>, _>(&db, &root, key, None, None)
+				.map_err(|_| Error::<T>::InvalidStorageProof)?
+		else {
+			return Ok(None);
+		};
+		V::decode_all(&mut &value[..])
+			.map(Some)
+			.map_err(|_| Error::<T>::InvalidStorageProof)
+	}
+
+	/// The key of the head of the anchoring parachain in the relay chain state.
+	pub(crate) fn para_head_key() -> Vec<u8> {
+		let mut key = storage_prefix(b"Paras", b"Heads").to_vec();
+		key.extend(Twox64Concat::hash(&T::ParaId::get().encode()));
+		key
+	}
+
+	/// The key of the `LastFinalized` of the bridge pallet in the parachain
+	/// state.
+	pub(crate) fn last_finalized_key() -> Vec<u8> {
+		storage_prefix(T::BridgePalletName::get().as_bytes(), b"LastFinalized").to_vec()
+	}
+
+	fn bound_authorities(authorities: AuthorityList) -> Result<BoundedAuthorityList<T>, Error<T>> {
+		ensure!(!authorities.is_empty(), Error::<T>::EmptyAuthoritySet);
+		BoundedAuthorityList::<T>::try_from(authorities).map_err(|_| Error::<T>::TooManyAuthorities)
+	}
+}
+
+/// A trie holding `value` at `key`, as its root and the nodes proving the
+/// value.
+#[cfg(any(test, feature = "runtime-benchmarks"))]
+pub(crate) fn trie_proof(key: &[u8], value: &[u8]) -> (H256, StorageProofNodes) {
+	use sp_trie::{trie_types::TrieDBMutBuilderV1, MemoryDB, TrieMut};
+
+	let mut db = MemoryDB::<BlakeTwo256>::default();
+	let mut root = H256::default();
+	TrieDBMutBuilderV1::new(&mut db, &mut root)
+		.build()
+		.insert(key, value)
+		.expect("inserting into an in-memory trie succeeds; qed");
+	let nodes = db.drain().into_values().filter(|(_, rc)| *rc > 0).map(|(node, _)| node);
+	(root, BoundedVec::truncate_from(nodes.collect()))
+}
@@ -0,0 +1,125 @@
+//! Test utilities
+
+#![cfg(test)]
+
+use crate::{self as pallet_spin_anchoring, RelayAuthoritySet, RelayAuthoritySetData};
+use frame_support::{
+	derive_impl, parameter_types,
+	traits::{ConstU32, ConstU64},
+	BoundedVec,
+};
+use sp_consensus_grandpa::SetId;
+use sp_keyring::Ed25519Keyring;
+use sp_runtime::{testing::Header, BuildStorage};
+
+type Block = frame_system::mocking::MockBlock<Test>;
+
+/// The relayer registered at genesis.
+pub const RELAYER: u64 = 1;
+
+/// Accounts that can afford the relayer bond but are no relayers at genesis.
+pub const OTHER_RELAYERS: [u64; 2] = [2, 3];
+
+/// The amount held from every relayer.
+pub const RELAYER_BOND: u64 = 100;
+
+/// The balance of every account at genesis.
+pub const BALANCE: u64 = 1_000;
+
+/// The number of the most recent anchors kept.
+pub const MAX_ANCHORS: u32 = 3;
+
+/// The number of blocks without the watermark advancing after which the
+/// anchoring is stalled.
+pub const STALL_THRESHOLD: u64 = 5;
+
+/// The id of the anchoring parachain.
+pub const PARA_ID: u32 = 3426;
+
+/// The id of the relay chain authority set.
+pub const SET_ID: SetId = 1;
+
+/// The relay chain authorities, each with a weight of one.
+pub const VOTERS: [Ed25519Keyring; 4] =
+	[Ed25519Keyring::Alice, Ed25519Keyring::Bob, Ed25519Keyring::Charlie, Ed25519Keyring::Dave];
+
+frame_support::construct_runtime!(
+	pub enum Test
+	{
+		System: frame_system,
+		Balances: pallet_balances,
+		SpinAnchoring: pallet_spin_anchoring,
+	}
+);
+
+#[derive_impl(frame_system::config_preludes::TestDefaultConfig)]
+impl frame_system::Config for Test {
+	type Block = Block;
+	type AccountData = pallet_balances::AccountData<u64>;
+}
+
+#[derive_impl(pallet_balances::config_preludes::TestDefaultConfig)]
+impl pallet_balances::Config for Test {
+	type AccountStore = System;
+}
+
+parameter_types! {
+	pub const BridgePalletName: &'static str = "SpinPolkadot";
+}
+
+impl pallet_spin_anchoring::Config for Test {
+	type RelayChainHeader = Header;
+	type ParachainHeader = Header;
+	type ParaId = ConstU32<PARA_ID>;
+	type BridgePalletName = BridgePalletName;
+	type MaxAuthorities = ConstU32<8>;
+	type Currency = Balances;
+	type RuntimeHoldReason = RuntimeHoldReason;
+	type RelayerBond = ConstU64<RELAYER_BOND>;
+	type MaxRelayers = ConstU32<3>;
+	type Slash = ();
+	type MaxAnchors = ConstU32<MAX_ANCHORS>;
+	type StallThreshold = ConstU64<STALL_THRESHOLD>;
+	type WeightInfo = ();
+}
+
+pub fn build_ext_and_execute_test(test: impl FnOnce()) {
+	let mut storage = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: [RELAYER].iter().chain(&OTHER_RELAYERS).map(|who| (*who, BALANCE)).collect(),
+		dev_accounts: None,
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+	pallet_spin_anchoring::GenesisConfig::<Test> {
+		relayers: vec![RELAYER],
+		required_attestations: 1,
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+
+	let mut ext = sp_io::TestExternalities::from(storage);
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		RelayAuthoritySet::<Test>::put(RelayAuthoritySetData {
+			set_id: SET_ID,
+			authorities: BoundedVec::truncate_from(
+				VOTERS.iter().map(|voter| (voter.public().into(), 1)).collect(),
+			),
+			handed_off_at: 0,
+		});
+		test()
+	});
+}
+
+/// Externalities with a keystore, for the benchmarks to sign precommits with.
+#[cfg(feature = "runtime-benchmarks")]
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	use sp_keystore::{testing::MemoryKeystore, KeystoreExt};
+
+	let storage = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
+	let mut ext = sp_io::TestExternalities::from(storage);
+	ext.register_extension(KeystoreExt::new(MemoryKeystore::new()));
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
@@ -0,0 +1,553 @@
+//! Tests for the module.
+
+#![cfg(test)]
+
+use crate::{
+	mock::{
+		build_ext_and_execute_test, Balances, RuntimeCall, RuntimeOrigin, SpinAnchoring, System,
+		Test, BALANCE, MAX_ANCHORS, OTHER_RELAYERS, PARA_ID, RELAYER, RELAYER_BOND, SET_ID,
+		STALL_THRESHOLD, VOTERS,
+	},
+	trie_proof, AnchorCount, AnchorProof, Anchors, BoundedGrandpaJustification, Error, Event,
+	RelayAuthoritySet, Relayers, RestrictWhenStalled, SecureUpTo, Stalled,
+};
+use codec::Encode;
+use frame_support::{
+	assert_noop, assert_ok,
+	storage::storage_prefix,
+	traits::{ConstU64, Contains, Hooks},
+	StorageHasher, Twox64Concat,
+};
+use frame_system::pallet_prelude::HeaderFor;
+use sp_consensus_grandpa::{
+	AuthorityId, AuthorityList, AuthoritySignature, ConsensusLog, ScheduledChange, SetId,
+	GRANDPA_ENGINE_ID,
+};
+use sp_core::H256;
+use sp_runtime::{
+	traits::{Hash as _, Header as _},
+	Digest, DigestItem, DispatchError, DispatchResult,
+};
+
+const ROUND: u64 = 7;
+
+type Header = HeaderFor<Test>;
+
+type SignedPrecommit =
+	finality_grandpa::SignedPrecommit<H256, u64, AuthoritySignature, AuthorityId>;
+
+/// A chain of `len` headers numbered from one, noted as the blocks of this
+/// chain.
+fn chain(len: u64) -> Vec<Header> {
+	let mut parent_hash = H256::zero();
+	(1..=len)
+		.map(|number| {
+			let header = Header::new(
+				number,
+				Default::default(),
+				Default::default(),
+				parent_hash,
+				Default::default(),
+			);
+			frame_system::BlockHash::<Test>::insert(number, header.hash());
+			parent_hash = header.hash();
+			header
+		})
+		.collect()
+}
+
+/// A block at the height of `header` that is not a block of this chain.
+fn fork_of(header: &Header) -> Header {
+	Header::new(
+		*header.number(),
+		Default::default(),
+		H256::repeat_byte(0xff),
+		*header.parent_hash(),
+		Default::default(),
+	)
+}
+
+/// A justification of `target` signed by three of the four voters as the relay
+/// chain authority set `set_id`.
+fn justification(target: &Header, set_id: SetId) -> BoundedGrandpaJustification<Header> {
+	let precommit =
+		finality_grandpa::Precommit { target_hash: target.hash(), target_number: *target.number() };
+	let payload = sp_consensus_grandpa::localized_payload(
+		ROUND,
+		set_id,
+		&finality_grandpa::Message::Precommit(precommit.clone()),
+	);
+	let precommits = VOTERS[..3]
+		.iter()
+		.map(|voter| SignedPrecommit {
+			precommit: precommit.clone(),
+			signature: voter.sign(&payload).into(),
+			id: voter.public().into(),
+		})
+		.collect();
+	BoundedGrandpaJustification {
+		round: ROUND,
+		commit: finality_grandpa::Commit {
+			target_hash: target.hash(),
+			target_number: *target.number(),
+			precommits,
+		},
+		votes_ancestries: Default::default(),
+	}
+}
+
+/// A relay chain header with `state_root` and `digest`.
+fn relay_header(number: u64, state_root: H256, digest: Digest) -> Header {
+	Header::new(number, Default::default(), state_root, Default::default(), digest)
+}
+
+/// A relay chain block justified by the relay chain authority set `set_id`,
+/// the state of which holds `para_head` at `key`.
+fn relay_proof(key: &[u8], para_head: &Header, set_id: SetId) -> AnchorProof<Header> {
+	let (relay_root, para_head_proof) = trie_proof(key, &para_head.encode().encode());
+	let relay_header = relay_header(1, relay_root, Default::default());
+	AnchorProof {
+		justification: justification(&relay_header, set_id),
+		relay_header,
+		para_head_proof,
+		last_finalized_proof: Default::default(),
+	}
+}
+
+/// A proof that the parachain finalized `target`, the relay chain block of
+/// which is justified by the relay chain authority set `set_id`.
+fn anchor_proof(target: &Header, set_id: SetId) -> AnchorProof<Header> {
+	let last_finalized = (*target.number(), target.hash()).encode();
+	let (para_root, last_finalized_proof) =
+		trie_proof(&SpinAnchoring::last_finalized_key(), &last_finalized);
+	let para_head = Header::new(1, Default::default(), para_root, H256::zero(), Default::default());
+	AnchorProof {
+		last_finalized_proof,
+		..relay_proof(&SpinAnchoring::para_head_key(), &para_head, set_id)
+	}
+}
+
+/// Note `proof` as `relayer`.
+fn note(relayer: u64, proof: AnchorProof<Header>) -> DispatchResult {
+	SpinAnchoring::note_anchor_verified(RuntimeOrigin::signed(relayer), proof)
+}
+
+/// Attest `target` as `relayer`.
+fn attest(relayer: u64, target: &Header) -> DispatchResult {
+	note(relayer, anchor_proof(target, SET_ID))
+}
+
+/// The voters as relay chain authorities.
+fn voters() -> AuthorityList {
+	VOTERS.iter().map(|voter| (voter.public().into(), 1)).collect()
+}
+
+/// Anchor `targets` one per block, starting in block two.
+fn anchor(targets: &[&Header]) {
+	for target in targets {
+		System::set_block_number(System::block_number() + 1);
+		assert_ok!(attest(RELAYER, target));
+	}
+}
+
+/// The numbers of the anchored blocks kept, from the oldest.
+fn anchored_numbers() -> Vec<u64> {
+	let count = AnchorCount::<Test>::get();
+	(count.saturating_sub(MAX_ANCHORS)..count)
+		.map(|index| Anchors::<Test>::get(index % MAX_ANCHORS).unwrap().number)
+		.collect()
+}
+
+/// Advance to block `n` and run the hook of the pallet.
+fn run_to_block(n: u64) {
+	System::set_block_number(n);
+	SpinAnchoring::on_initialize(n);
+}
+
+/// Register the other relayers and require `required` attestations.
+fn register_other_relayers(required: u32) {
+	for relayer in OTHER_RELAYERS {
+		assert_ok!(SpinAnchoring::add_relayer(RuntimeOrigin::root(), relayer));
+	}
+	assert_ok!(SpinAnchoring::set_required_attestations(RuntimeOrigin::root(), required));
+}
+
+#[test]
+fn relayers_are_bonded_and_unbonded() {
+	build_ext_and_execute_test(|| {
+		let relayer = OTHER_RELAYERS[0];
+		let headers = chain(1);
+		assert_noop!(
+			SpinAnchoring::add_relayer(RuntimeOrigin::signed(RELAYER), relayer),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(attest(relayer, &headers[0]), DispatchError::BadOrigin);
+
+		assert_ok!(SpinAnchoring::add_relayer(RuntimeOrigin::root(), relayer));
+		assert_eq!(Balances::reserved_balance(relayer), RELAYER_BOND);
+		System::assert_last_event(
+			Event::<Test>::RelayerAdded { relayer, bond: RELAYER_BOND }.into(),
+		);
+		assert_noop!(
+			SpinAnchoring::add_relayer(RuntimeOrigin::root(), relayer),
+			Error::<Test>::AlreadyRelayer
+		);
+
+		assert_ok!(SpinAnchoring::remove_relayer(RuntimeOrigin::root(), relayer));
+		assert_eq!(Balances::reserved_balance(relayer), 0);
+		System::assert_last_event(Event::<Test>::RelayerRemoved { relayer }.into());
+		assert_noop!(
+			SpinAnchoring::retire_relayer(RuntimeOrigin::signed(relayer)),
+			Error::<Test>::NotRelayer
+		);
+	});
+}
+
+#[test]
+fn relayers_retire_once_the_watermark_reached_their_attestation() {
+	build_ext_and_execute_test(|| {
+		register_other_relayers(2);
+		let headers = chain(1);
+
+		assert_ok!(attest(RELAYER, &headers[0]));
+		assert_noop!(
+			SpinAnchoring::retire_relayer(RuntimeOrigin::signed(RELAYER)),
+			Error::<Test>::PendingAttestation
+		);
+
+		assert_ok!(attest(OTHER_RELAYERS[0], &headers[0]));
+		assert_ok!(SpinAnchoring::retire_relayer(RuntimeOrigin::signed(RELAYER)));
+		assert_eq!(Balances::reserved_balance(RELAYER), 0);
+		System::assert_last_event(Event::<Test>::RelayerRemoved { relayer: RELAYER }.into());
+	});
+}
+
+#[test]
+fn watermark_advances_only_at_quorum() {
+	build_ext_and_execute_test(|| {
+		register_other_relayers(2);
+		assert_noop!(
+			SpinAnchoring::set_required_attestations(RuntimeOrigin::root(), 4),
+			Error::<Test>::InvalidThreshold
+		);
+		let headers = chain(2);
+
+		assert_ok!(attest(RELAYER, &headers[1]));
+		assert_eq!(SecureUpTo::<Test>::get(), 0);
+		System::assert_last_event(
+			Event::<Test>::AnchorAttested { relayer: RELAYER, number: 2, hash: headers[1].hash() }
+				.into(),
+		);
+
+		// an attestation of another height does not count.
+		assert_ok!(attest(OTHER_RELAYERS[0], &headers[0]));
+		assert_eq!(SecureUpTo::<Test>::get(), 0);
+
+		assert_ok!(attest(OTHER_RELAYERS[1], &headers[1]));
+		assert_eq!(SecureUpTo::<Test>::get(), 2);
+		System::assert_has_event(
+			Event::<Test>::SecureFinalityAdvanced { up_to: 2, hash: headers[1].hash() }.into(),
+		);
+		assert!(SpinAnchoring::relayers().iter().all(|relayer| relayer.attestation.is_none()));
+		assert_noop!(attest(OTHER_RELAYERS[0], &headers[1]), Error::<Test>::AlreadySecure);
+	});
+}
+
+#[test]
+fn blocks_of_another_chain_never_reach_quorum() {
+	build_ext_and_execute_test(|| {
+		let fork = fork_of(&chain(1)[0]);
+
+		assert_ok!(attest(RELAYER, &fork));
+
+		assert_eq!(SecureUpTo::<Test>::get(), 0);
+		assert_eq!(SpinAnchoring::relayers()[0].attestation, Some((1, fork.hash())));
+	});
+}
+
+#[test]
+fn relayers_attesting_another_block_are_slashed() {
+	build_ext_and_execute_test(|| {
+		register_other_relayers(2);
+		let headers = chain(2);
+		let slashed = OTHER_RELAYERS[1];
+
+		assert_ok!(attest(slashed, &fork_of(&headers[1])));
+		assert_ok!(attest(RELAYER, &headers[1]));
+		assert_ok!(attest(OTHER_RELAYERS[0], &headers[1]));
+
+		assert_eq!(SecureUpTo::<Test>::get(), 2);
+		assert!(Relayers::<Test>::get().iter().all(|relayer| relayer.account != slashed));
+		assert_eq!(Balances::reserved_balance(slashed), 0);
+		assert_eq!(Balances::free_balance(slashed), BALANCE - RELAYER_BOND);
+		System::assert_has_event(
+			Event::<Test>::RelayerSlashed { relayer: slashed, amount: RELAYER_BOND }.into(),
+		);
+	});
+}
+
+#[test]
+fn proofs_must_reach_the_bridge_state() {
+	build_ext_and_execute_test(|| {
+		let target = &chain(1)[0];
+		let proof = anchor_proof(target, SET_ID);
+
+		let mut unjustified = proof.clone();
+		unjustified.relay_header = relay_header(2, Default::default(), Default::default());
+		assert_noop!(note(RELAYER, unjustified), Error::<Test>::RelayHeaderMismatch);
+
+		assert_noop!(note(RELAYER, anchor_proof(target, SET_ID + 1)), Error::<Test>::BadSignature);
+
+		let mut incomplete = proof.clone();
+		incomplete.last_finalized_proof = Default::default();
+		assert_noop!(note(RELAYER, incomplete), Error::<Test>::InvalidStorageProof);
+
+		let mut other_para_key = storage_prefix(b"Paras", b"Heads").to_vec();
+		other_para_key.extend(Twox64Concat::hash(&(PARA_ID + 1).encode()));
+		let para_head = relay_header(1, Default::default(), Default::default());
+		assert_noop!(
+			note(RELAYER, relay_proof(&other_para_key, &para_head, SET_ID)),
+			Error::<Test>::ParachainHeadNotFound
+		);
+
+		// a parachain head whose bridge pallet finalized nothing yet.
+		let (para_root, last_finalized_proof) = trie_proof(b"unrelated", &[0]);
+		let para_head = relay_header(1, para_root, Default::default());
+		let nothing_finalized = AnchorProof {
+			last_finalized_proof,
+			..relay_proof(&SpinAnchoring::para_head_key(), &para_head, SET_ID)
+		};
+		assert_noop!(note(RELAYER, nothing_finalized), Error::<Test>::NothingFinalized);
+
+		RelayAuthoritySet::<Test>::kill();
+		assert_noop!(note(RELAYER, proof), Error::<Test>::RelayAuthoritySetNotInitialized);
+	});
+}
+
+#[test]
+fn relay_authority_set_is_overridden_by_root() {
+	build_ext_and_execute_test(|| {
+		assert_noop!(
+			SpinAnchoring::set_relay_authority_set(RuntimeOrigin::signed(RELAYER), 3, voters(), 9),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			SpinAnchoring::set_relay_authority_set(RuntimeOrigin::root(), 3, vec![], 9),
+			Error::<Test>::EmptyAuthoritySet
+		);
+
+		assert_ok!(SpinAnchoring::set_relay_authority_set(RuntimeOrigin::root(), 3, voters(), 9));
+		let set = RelayAuthoritySet::<Test>::get().unwrap();
+		assert_eq!((set.set_id, set.handed_off_at), (3, 9));
+		System::assert_last_event(
+			Event::<Test>::RelayAuthoritySetOverridden { set_id: 3, authorities: 4 }.into(),
+		);
+	});
+}
+
+#[test]
+fn relay_authority_set_is_handed_off_by_justified_headers() {
+	build_ext_and_execute_test(|| {
+		let target = &chain(1)[0];
+		let change = ConsensusLog::<u64>::ScheduledChange(ScheduledChange {
+			next_authorities: voters(),
+			delay: 0,
+		});
+		let signalling = relay_header(
+			5,
+			Default::default(),
+			Digest { logs: vec![DigestItem::Consensus(GRANDPA_ENGINE_ID, change.encode())] },
+		);
+		let hand_off = |relayer, header: &Header| {
+			SpinAnchoring::hand_off_relay_authority_set(
+				RuntimeOrigin::signed(relayer),
+				justification(header, SET_ID),
+				header.clone(),
+			)
+		};
+
+		assert_noop!(hand_off(OTHER_RELAYERS[0], &signalling), DispatchError::BadOrigin);
+		assert_noop!(
+			hand_off(RELAYER, &relay_header(5, Default::default(), Default::default())),
+			Error::<Test>::NoAuthoritySetChange
+		);
+
+		assert_ok!(hand_off(RELAYER, &signalling));
+		let set = RelayAuthoritySet::<Test>::get().unwrap();
+		assert_eq!((set.set_id, set.handed_off_at), (SET_ID + 1, 5));
+		System::assert_last_event(
+			Event::<Test>::RelayAuthoritySetUpdated { set_id: SET_ID + 1, handed_off_at: 5 }.into(),
+		);
+
+		// the previous set no longer justifies relay chain blocks.
+		assert_noop!(hand_off(RELAYER, &signalling), Error::<Test>::BadSignature);
+		assert_noop!(attest(RELAYER, target), Error::<Test>::BadSignature);
+		assert_ok!(note(RELAYER, anchor_proof(target, SET_ID + 1)));
+		assert_eq!(SecureUpTo::<Test>::get(), 1);
+	});
+}
+
+#[test]
+fn anchors_are_kept_in_a_ring_buffer() {
+	build_ext_and_execute_test(|| {
+		let headers = chain(5);
+
+		anchor(&[&headers[0], &headers[1]]);
+		assert_eq!(anchored_numbers(), vec![1, 2]);
+		let anchor = Anchors::<Test>::get(1).unwrap();
+		assert_eq!((anchor.hash, anchor.anchored_at), (headers[1].hash(), 3));
+		assert_eq!(
+			anchor.proof_ref,
+			<Test as frame_system::Config>::Hashing::hash_of(&anchor_proof(&headers[1], SET_ID))
+		);
+
+		// the fourth anchor replaces the first one, in its slot.
+		anchor(&[&headers[2], &headers[3]]);
+		assert_eq!(AnchorCount::<Test>::get(), 4);
+		assert_eq!(Anchors::<Test>::iter().count(), MAX_ANCHORS as usize);
+		assert_eq!(Anchors::<Test>::get(0).unwrap().number, 4);
+		assert_eq!(anchored_numbers(), vec![2, 3, 4]);
+
+		anchor(&[&headers[4]]);
+		assert_eq!(anchored_numbers(), vec![3, 4, 5]);
+	});
+}
+
+#[test]
+fn anchor_covering_finds_the_earliest_anchor_at_or_above_the_block() {
+	build_ext_and_execute_test(|| {
+		let headers = chain(6);
+		let covering = |number| SpinAnchoring::anchor_covering(number).map(|anchor| anchor.number);
+
+		assert_eq!(covering(0), None);
+
+		anchor(&[&headers[1], &headers[3], &headers[5]]);
+		assert_eq!(covering(0), Some(2));
+		assert_eq!(covering(1), Some(2));
+		assert_eq!(covering(2), Some(2));
+		assert_eq!(covering(3), Some(4));
+		assert_eq!(covering(6), Some(6));
+		// above the watermark, blocks are not secure.
+		assert_eq!(covering(7), None);
+	});
+}
+
+#[test]
+fn blocks_are_anchored_by_their_hash() {
+	build_ext_and_execute_test(|| {
+		let headers = chain(4);
+		anchor(&[&headers[2]]);
+
+		// blocks of this chain up to the watermark are anchored.
+		assert!(SpinAnchoring::is_anchored(1, headers[0].hash()));
+		assert!(SpinAnchoring::is_anchored(3, headers[2].hash()));
+		assert!(!SpinAnchoring::is_anchored(4, headers[3].hash()));
+
+		// blocks of other chains are not.
+		assert!(!SpinAnchoring::is_anchored(2, fork_of(&headers[1]).hash()));
+		assert!(!SpinAnchoring::is_anchored(2, headers[0].hash()));
+
+		// nor are blocks not known by their hash any more.
+		frame_system::BlockHash::<Test>::remove(1);
+		assert!(!SpinAnchoring::is_anchored(1, headers[0].hash()));
+		assert!(!SpinAnchoring::is_anchored(1, Default::default()));
+	});
+}
+
+#[test]
+fn anchor_covering_skips_evicted_anchors() {
+	build_ext_and_execute_test(|| {
+		let headers = chain(8);
+		let covering = |number| SpinAnchoring::anchor_covering(number).map(|anchor| anchor.number);
+
+		anchor(&[&headers[1], &headers[3], &headers[5], &headers[7]]);
+		assert_eq!(anchored_numbers(), vec![4, 6, 8]);
+
+		// the anchor of block two was evicted, the earliest one kept covers it.
+		assert_eq!(covering(1), Some(4));
+		assert_eq!(covering(2), Some(4));
+		assert_eq!(covering(5), Some(6));
+		assert_eq!(covering(8), Some(8));
+		assert_eq!(covering(9), None);
+	});
+}
+
+#[test]
+fn stalls_after_the_threshold_and_resumes_on_recovery() {
+	build_ext_and_execute_test(|| {
+		let headers = chain(2);
+		anchor(&[&headers[0]]);
+
+		run_to_block(2 + STALL_THRESHOLD - 1);
+		assert!(!Stalled::<Test>::get());
+
+		run_to_block(2 + STALL_THRESHOLD);
+		assert!(Stalled::<Test>::get());
+		System::assert_last_event(
+			Event::<Test>::AnchorStalled { secure_up_to: 1, since: 2 }.into(),
+		);
+
+		// the stall is reported once.
+		System::reset_events();
+		run_to_block(2 + STALL_THRESHOLD + 1);
+		assert!(System::events().is_empty());
+
+		assert_ok!(attest(RELAYER, &headers[1]));
+		assert!(!Stalled::<Test>::get());
+		System::assert_last_event(Event::<Test>::AnchorResumed { up_to: 2 }.into());
+		assert_eq!(SpinAnchoring::blocks_since_advance(), 0);
+	});
+}
+
+#[test]
+fn stall_is_counted_from_the_first_block_the_pallet_runs_in() {
+	build_ext_and_execute_test(|| {
+		// the pallet is added by a runtime upgrade long after genesis.
+		let upgraded_at = 100;
+		run_to_block(upgraded_at);
+		assert!(!Stalled::<Test>::get());
+		assert_eq!(SpinAnchoring::blocks_since_advance(), 0);
+
+		run_to_block(upgraded_at + STALL_THRESHOLD - 1);
+		assert!(!Stalled::<Test>::get());
+
+		run_to_block(upgraded_at + STALL_THRESHOLD);
+		assert!(Stalled::<Test>::get());
+		System::assert_last_event(
+			Event::<Test>::AnchorStalled { secure_up_to: 0, since: upgraded_at }.into(),
+		);
+	});
+}
+
+/// Balance transfers stand in for the high-value calls.
+struct IsBalancesCall;
+
+impl Contains<RuntimeCall> for IsBalancesCall {
+	fn contains(call: &RuntimeCall) -> bool {
+		matches!(call, RuntimeCall::Balances(_))
+	}
+}
+
+#[test]
+fn restricts_calls_while_stalled() {
+	build_ext_and_execute_test(|| {
+		type Filter = RestrictWhenStalled<Test, ConstU64<{ STALL_THRESHOLD + 2 }>, IsBalancesCall>;
+		let transfer = RuntimeCall::Balances(pallet_balances::Call::transfer_allow_death {
+			dest: RELAYER,
+			value: 1,
+		});
+		let remark = RuntimeCall::System(frame_system::Call::remark { remark: vec![] });
+		let headers = chain(1);
+
+		run_to_block(1);
+		run_to_block(STALL_THRESHOLD + 2);
+		assert!(Filter::contains(&transfer));
+
+		run_to_block(STALL_THRESHOLD + 3);
+		assert!(!Filter::contains(&transfer));
+		assert!(Filter::contains(&remark));
+
+		assert_ok!(attest(RELAYER, &headers[0]));
+		assert!(Filter::contains(&transfer));
+	});
+}
@@ -0,0 +1,65 @@
+//! Weights for `pallet_spin_anchoring`.
+//!
+//! The `()` implementation holds estimates for runtimes that did not generate
+//! weights with the benchmarks of the pallet yet. Verifying the signatures of
+//! the precommits dominates the cost of noting an anchor and of handing off the
+//! relay chain authority set.
+
+use frame_support::weights::{constants::RocksDbWeight, Weight};
+
+/// Weight functions needed for `pallet_spin_anchoring`.
+pub trait WeightInfo {
+	/// `p` is the number of precommits and `v` the number of votes ancestries
+	/// of the justification.
+	fn note_anchor_verified(p: u32, v: u32) -> Weight;
+	fn add_relayer() -> Weight;
+	fn remove_relayer() -> Weight;
+	fn retire_relayer() -> Weight;
+	fn set_required_attestations() -> Weight;
+	/// `a` is the number of authorities.
+	fn set_relay_authority_set(a: u32) -> Weight;
+	/// `p` is the number of precommits and `v` the number of votes ancestries
+	/// of the justification.
+	fn hand_off_relay_authority_set(p: u32, v: u32) -> Weight;
+}
+
+/// Estimates for up to 16 relayers, all but one slashed.
+impl WeightInfo for () {
+	fn note_anchor_verified(p: u32, v: u32) -> Weight {
+		Weight::from_parts(100_000_000, 40_000)
+			.saturating_add(Weight::from_parts(60_000_000, 0).saturating_mul(p.into()))
+			.saturating_add(Weight::from_parts(2_000_000, 0).saturating_mul(v.into()))
+			.saturating_add(RocksDbWeight::get().reads(8))
+			.saturating_add(RocksDbWeight::get().writes(36))
+	}
+	fn add_relayer() -> Weight {
+		Weight::from_parts(40_000_000, 4_500)
+			.saturating_add(RocksDbWeight::get().reads(3))
+			.saturating_add(RocksDbWeight::get().writes(3))
+	}
+	fn remove_relayer() -> Weight {
+		Weight::from_parts(40_000_000, 4_500)
+			.saturating_add(RocksDbWeight::get().reads(3))
+			.saturating_add(RocksDbWeight::get().writes(3))
+	}
+	fn retire_relayer() -> Weight {
+		Weight::from_parts(40_000_000, 4_500)
+			.saturating_add(RocksDbWeight::get().reads(3))
+			.saturating_add(RocksDbWeight::get().writes(3))
+	}
+	fn set_required_attestations() -> Weight {
+		Weight::from_parts(8_000_000, 0).saturating_add(RocksDbWeight::get().writes(1))
+	}
+	fn set_relay_authority_set(a: u32) -> Weight {
+		Weight::from_parts(10_000_000, 0)
+			.saturating_add(Weight::from_parts(20_000, 0).saturating_mul(a.into()))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+	fn hand_off_relay_authority_set(p: u32, v: u32) -> Weight {
+		Weight::from_parts(60_000_000, 36_000)
+			.saturating_add(Weight::from_parts(60_000_000, 0).saturating_mul(p.into()))
+			.saturating_add(Weight::from_parts(2_000_000, 0).saturating_mul(v.into()))
+			.saturating_add(RocksDbWeight::get().reads(2))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+}
@@ -0,0 +1,187 @@
+//! Benchmarks for the fastchain finality bridge.
+
+use super::*;
+use crate::Pallet as SpinPolkadot;
+use finality_grandpa::Message as GrandpaMessage;
+use frame_benchmarking::v2::*;
+use frame_support::traits::fungible::{Inspect, Mutate};
+use frame_system::RawOrigin;
+use sp_consensus_grandpa::{ConsensusLog, ScheduledChange, GRANDPA_ENGINE_ID};
+use sp_runtime::{traits::One, Digest, DigestItem, RuntimeAppPublic};
+use sp_std::vec;
+
+const ROUND: u64 = 1;
+const SET_ID: SetId = 1;
+
+/// `count` freshly generated authorities, each with a weight of one.
+fn authorities(count: u32) -> Vec<(AuthorityId, AuthorityWeight)> {
+	(0..count).map(|_| (AuthorityId::generate_pair(None), 1)).collect()
+}
+
+/// Register `count` relayers that can afford the bond.
+fn register_relayers<T: Config>(count: u32) -> Vec<T::AccountId>
+where
+	T::Currency: Mutate<T::AccountId>,
+{
+	let balance = T::RelayerBond::get()
+		.saturating_mul(2u32.into())
+		.saturating_add(T::Currency::minimum_balance());
+	(0..count)
+		.map(|index| {
+			let relayer: T::AccountId = account("relayer", index, 0);
+			T::Currency::set_balance(&relayer, balance);
+			SpinPolkadot::<T>::do_add_relayer(relayer.clone()).expect("the bond is funded; qed");
+			relayer
+		})
+		.collect()
+}
+
+fn header<T: Config>(
+	number: AnchoredNumberOf<T>,
+	parent_hash: AnchoredHashOf<T>,
+	digest: Digest,
+) -> T::AnchoredChainHeader {
+	T::AnchoredChainHeader::new(number, Default::default(), Default::default(), parent_hash, digest)
+}
+
+#[benchmarks(where T::Currency: Mutate<T::AccountId>)]
+mod benchmarks {
+	use super::*;
+
+	#[benchmark]
+	fn set_authority_set(a: Linear<1, { T::MaxAuthorities::get() }>) {
+		let authorities = authorities(a);
+		PendingAuthoritySetChange::<T>::put(PendingAuthoritySet {
+			authorities: BoundedVec::truncate_from(authorities.clone()),
+			enact_at: One::one(),
+		});
+
+		#[extrinsic_call]
+		_(RawOrigin::Root, SET_ID, authorities);
+
+		assert_eq!(
+			FastchainAuthoritySet::<T>::get().map(|set| set.authorities.len()),
+			Some(a as usize)
+		);
+		assert!(PendingAuthoritySetChange::<T>::get().is_none());
+	}
+
+	/// `p` authorities precommit for the last of `v` descendants of a target
+	/// header that signals an immediate change to the largest authority set,
+	/// ending a cool-down. Every other relayer, having attested another block
+	/// at the same height, is slashed.
+	#[benchmark]
+	fn submit_finality_proof(
+		p: Linear<1, { T::MaxAuthorities::get() }>,
+		v: Linear<0, MAX_VOTES_ANCESTRIES>,
+	) {
+		let voters = authorities(p);
+		FastchainAuthoritySet::<T>::put(AuthoritySetData {
+			set_id: SET_ID,
+			authorities: BoundedVec::truncate_from(voters.clone()),
+		});
+		let relayers = register_relayers::<T>(T::MaxRelayers::get());
+		RequiredAttestations::<T>::put(1);
+		CurrentMode::<T>::put(Mode::CoolDown);
+
+		let change = ConsensusLog::<AnchoredNumberOf<T>>::ScheduledChange(ScheduledChange {
+			next_authorities: authorities(T::MaxAuthorities::get()),
+			delay: Zero::zero(),
+		});
+		let digest =
+			Digest { logs: vec![DigestItem::Consensus(GRANDPA_ENGINE_ID, change.encode())] };
+		let target = header::<T>(One::one(), Default::default(), digest);
+
+		let mut votes_ancestries = Vec::new();
+		let mut tip = target.clone();
+		for _ in 0..v {
+			tip = header::<T>(*tip.number() + One::one(), tip.hash(), Default::default());
+			votes_ancestries.push(tip.clone());
+		}
+
+		let precommit =
+			finality_grandpa::Precommit { target_hash: tip.hash(), target_number: *tip.number() };
+		let payload = sp_consensus_grandpa::localized_payload(
+			ROUND,
+			SET_ID,
+			&GrandpaMessage::Precommit(precommit.clone()),
+		);
+		let precommits = voters
+			.into_iter()
+			.map(|(id, _)| finality_grandpa::SignedPrecommit {
+				precommit: precommit.clone(),
+				signature: id.sign(&payload).expect("the key was generated in the keystore; qed"),
+				id,
+			})
+			.collect();
+		let justification = BoundedGrandpaJustification {
+			round: ROUND,
+			commit: finality_grandpa::Commit {
+				target_hash: target.hash(),
+				target_number: *target.number(),
+				precommits,
+			},
+			votes_ancestries: BoundedVec::truncate_from(votes_ancestries),
+		};
+		Relayers::<T>::mutate(|relayers| {
+			relayers.iter_mut().skip(1).for_each(|relayer| {
+				relayer.attestation = Some((*target.number(), Default::default()))
+			})
+		});
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(relayers[0].clone()), SET_ID, justification, Some(target));
+
+		assert_eq!(FastchainAuthoritySet::<T>::get().map(|set| set.set_id), Some(SET_ID + 1));
+		assert_eq!(SpinPolkadot::<T>::mode(), Mode::Operational);
+		assert_eq!(Relayers::<T>::get().len(), 1);
+	}
+
+	#[benchmark]
+	fn add_relayer() {
+		let mut relayers = register_relayers::<T>(T::MaxRelayers::get());
+		let relayer = relayers.pop().expect("there is at least a relayer; qed");
+		SpinPolkadot::<T>::do_remove_relayer(&relayer).expect("the relayer is registered; qed");
+
+		#[extrinsic_call]
+		_(RawOrigin::Root, relayer.clone());
+
+		assert!(Relayers::<T>::get().iter().any(|status| status.account == relayer));
+	}
+
+	#[benchmark]
+	fn remove_relayer() {
+		let relayer = register_relayers::<T>(T::MaxRelayers::get())
+			.pop()
+			.expect("there is at least a relayer; qed");
+
+		#[extrinsic_call]
+		_(RawOrigin::Root, relayer.clone());
+
+		assert!(Relayers::<T>::get().iter().all(|status| status.account != relayer));
+	}
+
+	#[benchmark]
+	fn retire_relayer() {
+		let relayer = register_relayers::<T>(T::MaxRelayers::get())
+			.pop()
+			.expect("there is at least a relayer; qed");
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(relayer.clone()));
+
+		assert!(Relayers::<T>::get().iter().all(|status| status.account != relayer));
+	}
+
+	#[benchmark]
+	fn set_required_attestations() {
+		let required = T::MaxRelayers::get();
+
+		#[extrinsic_call]
+		_(RawOrigin::Root, required);
+
+		assert_eq!(RequiredAttestations::<T>::get(), required);
+	}
+
+	impl_benchmark_test_suite!(SpinPolkadot, crate::mock::new_test_ext(), crate::mock::Test);
+}
@@ -1,52 +1,137 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 pub use pallet::*;
-
-use codec::{Decode, DecodeWithMemTracking, Encode};
-use finality_grandpa::Message as GrandpaMessage;
-use frame_support::{ensure, pallet_prelude::*, BoundedVec, DefaultNoBound};
+pub use qfp_grandpa_justification::{BoundedGrandpaJustification, MAX_VOTES_ANCESTRIES};
+pub use weights::WeightInfo;
+
+use codec::{Decode, Encode};
+use frame_support::{
+	ensure,
+	pallet_prelude::*,
+	traits::{
+		fungible::{BalancedHold, Credit, MutateHold},
+		tokens::{imbalance::Imbalance, Precision},
+		OnUnbalanced,
+	},
+	BoundedVec, CloneNoBound, DefaultNoBound, EqNoBound, PartialEqNoBound,
+};
 use frame_system::pallet_prelude::*;
+use qfp_grandpa_justification::{
+	scheduled_change, verify_justification, Error as JustificationError,
+};
+use qfp_spin_polkadot::{Mode, RelayerStatus};
 use scale_info::TypeInfo;
-use sp_consensus_grandpa::{self, AuthorityList, SetId};
-use sp_runtime::traits::{BadOrigin, Header as HeaderT};
-use sp_std::collections::btree_set::BTreeSet;
+use sp_consensus_grandpa::{
+	self, AuthorityId, AuthorityList, AuthoritySignature, AuthorityWeight, SetId,
+};
+use sp_runtime::traits::{BadOrigin, BlockNumberProvider, Header as HeaderT, Saturating, Zero};
+use sp_std::vec::Vec;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+mod mock;
+mod tests;
+pub mod weights;
 
 #[frame_support::pallet]
 pub mod pallet {
 
 	use super::*;
 
-	/// TODO(zotho): pick sane limits for our network
-	pub const MAX_VOTES_ANCESTRIES: u32 = 512;
+	pub type AnchoredNumberOf<T> = <<T as Config>::AnchoredChainHeader as HeaderT>::Number;
+	pub type AnchoredHashOf<T> = <<T as Config>::AnchoredChainHeader as HeaderT>::Hash;
 
-	/// Identical to `sp_consensus_grandpa::GrandpaJustification` but with bounded
-	/// `votes_ancestries` vector.
-	#[derive(Clone, PartialEq, Eq, Encode, Decode, DecodeWithMemTracking, TypeInfo)]
-	pub struct BoundedGrandpaJustification<H: HeaderT> {
-		pub round: u64,
-		pub commit: sp_consensus_grandpa::Commit<H>,
-		pub votes_ancestries: BoundedVec<H, ConstU32<MAX_VOTES_ANCESTRIES>>,
+	/// GRANDPA authorities with their weights, at most `MaxAuthorities` of them.
+	pub type BoundedAuthorityList<T> =
+		BoundedVec<(AuthorityId, AuthorityWeight), <T as Config>::MaxAuthorities>;
+
+	/// Stored configuration for the GRANDPA authority set that signs fastchain finality proofs.
+	#[derive(
+		CloneNoBound, PartialEqNoBound, EqNoBound, Encode, Decode, TypeInfo, MaxEncodedLen,
+	)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound(T: Config))]
+	pub struct AuthoritySetData<T: Config> {
+		pub set_id: SetId,
+		pub authorities: BoundedAuthorityList<T>,
 	}
 
-	impl<H: HeaderT> core::fmt::Debug for BoundedGrandpaJustification<H> {
-		fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-			f.debug_struct("BoundedGrandpaJustification")
-				.field("round", &self.round)
-				.field("commit", &"<commit>") // TODO: replace the placeholder
-				.field("votes_ancestries", &self.votes_ancestries.len())
-				.finish()
-		}
+	/// An authority set change signalled in a justified fastchain header, to be
+	/// enacted once the current set finalizes the block at `enact_at`.
+	#[derive(
+		CloneNoBound, PartialEqNoBound, EqNoBound, Encode, Decode, TypeInfo, MaxEncodedLen,
+	)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound(T: Config))]
+	pub struct PendingAuthoritySet<T: Config> {
+		pub authorities: BoundedAuthorityList<T>,
+		pub enact_at: AnchoredNumberOf<T>,
 	}
 
-	/// Stored configuration for the GRANDPA authority set that signs fastchain finality proofs.
-	#[derive(Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
-	pub struct AuthoritySetData {
-		pub set_id: SetId,
-		pub authorities: AuthorityList,
+	/// What the header of an attested fastchain block commits to: its state
+	/// root and the authority set change it signals, if any.
+	#[derive(
+		CloneNoBound, PartialEqNoBound, EqNoBound, Encode, Decode, TypeInfo, MaxEncodedLen,
+	)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound(T: Config))]
+	pub struct AttestedHeader<T: Config> {
+		pub state_root: AnchoredHashOf<T>,
+		pub scheduled_change: Option<(BoundedAuthorityList<T>, AnchoredNumberOf<T>)>,
+	}
+
+	/// A precommit of a stored justification.
+	#[derive(Clone, Encode, Decode, TypeInfo, MaxEncodedLen, PartialEq, Eq)]
+	pub struct StoredPrecommit<BlockNumber, Hash> {
+		pub target_hash: Hash,
+		pub target_number: BlockNumber,
+		pub signature: AuthoritySignature,
+		pub id: AuthorityId,
+	}
+
+	/// A justification as kept in storage: its commit, with at most
+	/// `MaxAuthorities` precommits, but not its votes ancestries.
+	#[derive(
+		CloneNoBound, PartialEqNoBound, EqNoBound, Encode, Decode, TypeInfo, MaxEncodedLen,
+	)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound(T: Config))]
+	pub struct StoredJustification<T: Config> {
+		pub round: u64,
+		pub target_hash: AnchoredHashOf<T>,
+		pub target_number: AnchoredNumberOf<T>,
+		pub precommits:
+			BoundedVec<StoredPrecommit<AnchoredNumberOf<T>, AnchoredHashOf<T>>, T::MaxAuthorities>,
+	}
+
+	impl<T: Config> StoredJustification<T> {
+		/// The stored form of `justification`, unless it has more precommits
+		/// than `MaxAuthorities`.
+		pub fn bounded(
+			justification: &BoundedGrandpaJustification<T::AnchoredChainHeader>,
+		) -> Option<Self> {
+			let precommits = justification
+				.commit
+				.precommits
+				.iter()
+				.map(|signed| StoredPrecommit {
+					target_hash: signed.precommit.target_hash,
+					target_number: signed.precommit.target_number,
+					signature: signed.signature.clone(),
+					id: signed.id.clone(),
+				})
+				.collect::<Vec<_>>();
+			Some(Self {
+				round: justification.round,
+				target_hash: justification.commit.target_hash,
+				target_number: justification.commit.target_number,
+				precommits: BoundedVec::try_from(precommits).ok()?,
+			})
+		}
 	}
 
 	/// Metadata about the best fastchain block accepted on the parachain.
-	#[derive(Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
+	#[derive(Clone, Encode, Decode, TypeInfo, MaxEncodedLen, PartialEq, Eq)]
 	pub struct FinalizedTarget<BlockNumber, Hash> {
 		pub number: BlockNumber,
 		pub hash: Hash,
@@ -56,17 +141,80 @@ pub mod pallet {
 	pub trait Config: frame_system::Config {
 		/// The header type for the anchored chain.
 		type AnchoredChainHeader: HeaderT + TypeInfo;
+
+		/// Maximum number of authorities in a fastchain GRANDPA authority set,
+		/// and of precommits in a finality proof.
+		#[pallet::constant]
+		type MaxAuthorities: Get<u32>;
+
+		/// Provider of the number of the relay-chain block the parachain block
+		/// is built on.
+		type RelayBlockNumberProvider: BlockNumberProvider;
+
+		/// Number of relay-chain blocks without an accepted finality proof
+		/// after which the bridge cools down.
+		#[pallet::constant]
+		type HeartbeatTimeout: Get<RelayBlockNumberOf<Self>>;
+
+		/// The currency the bonds of the relayers are held in.
+		type Currency: MutateHold<Self::AccountId, Reason = Self::RuntimeHoldReason>
+			+ BalancedHold<Self::AccountId>;
+
+		/// The overarching hold reason.
+		type RuntimeHoldReason: From<HoldReason>;
+
+		/// The amount held from every relayer.
+		#[pallet::constant]
+		type RelayerBond: Get<BalanceOf<Self>>;
+
+		/// The maximum number of relayers.
+		#[pallet::constant]
+		type MaxRelayers: Get<u32>;
+
+		/// Handler for the bonds of slashed relayers.
+		type Slash: OnUnbalanced<Credit<Self::AccountId, Self::Currency>>;
+
+		/// Weight information for the calls of this pallet.
+		type WeightInfo: WeightInfo;
 	}
 
-	// TODO(zotho): remove `without_storage_info`
+	pub type RelayBlockNumberOf<T> =
+		<<T as Config>::RelayBlockNumberProvider as BlockNumberProvider>::BlockNumber;
+
+	/// The balance of the currency the bonds are held in.
+	pub type BalanceOf<T> = <<T as Config>::Currency as frame_support::traits::fungible::Inspect<
+		<T as frame_system::Config>::AccountId,
+	>>::Balance;
+
+	/// The status of a relayer, with the fastchain block it attested.
+	pub type RelayerOf<T> = RelayerStatus<
+		<T as frame_system::Config>::AccountId,
+		BalanceOf<T>,
+		AnchoredNumberOf<T>,
+		AnchoredHashOf<T>,
+	>;
+
+	/// The in-code storage version.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(2);
+
 	#[pallet::pallet]
-	#[pallet::without_storage_info]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
-	// TODO(zotho): Add MEL bound for `AuthoritySetData` https://github.com/QuantumFusion-network/spec/issues/629
+	/// A reason for the pallet placing a hold on funds.
+	#[pallet::composite_enum]
+	pub enum HoldReason {
+		/// The bond of a relayer.
+		RelayerBond,
+	}
+
 	/// Current GRANDPA authority set information.
 	#[pallet::storage]
-	pub type FastchainAuthoritySet<T: Config> = StorageValue<_, AuthoritySetData>;
+	pub type FastchainAuthoritySet<T: Config> = StorageValue<_, AuthoritySetData<T>>;
+
+	/// Authority set change scheduled by the current set and not enacted yet.
+	#[pallet::storage]
+	pub type PendingAuthoritySetChange<T: Config> = StorageValue<_, PendingAuthoritySet<T>>;
 
 	/// Highest fastchain block known to be finalized on the parachain.
 	#[pallet::storage]
@@ -78,39 +226,122 @@ pub mod pallet {
 		>,
 	>;
 
-	// TODO(zotho): Add MEL bound https://github.com/QuantumFusion-network/spec/issues/629
-	/// The most recent justification bytes accepted. This is informational only.
+	/// The most recent justification accepted. This is informational only.
+	#[pallet::storage]
+	pub type LastJustification<T: Config> = StorageValue<_, StoredJustification<T>>;
+
+	/// State root of the last finalized fastchain block, if its header was
+	/// submitted with the finality proof.
 	#[pallet::storage]
-	pub type LastJustification<T: Config> =
-		StorageValue<_, BoundedGrandpaJustification<T::AnchoredChainHeader>>;
+	pub type LastFinalizedStateRoot<T: Config> =
+		StorageValue<_, <T::AnchoredChainHeader as HeaderT>::Hash>;
 
+	/// The headers submitted with the pending attestations, by the hash of the
+	/// attested block, so that the header may come with any attestation of
+	/// the block.
 	#[pallet::storage]
-	pub type Relayer<T: Config> = StorageValue<_, T::AccountId>;
+	pub type AttestedHeaders<T: Config> =
+		StorageMap<_, Identity, AnchoredHashOf<T>, AttestedHeader<T>>;
+
+	/// The registered relayers.
+	#[pallet::storage]
+	pub type Relayers<T: Config> =
+		StorageValue<_, BoundedVec<RelayerOf<T>, T::MaxRelayers>, ValueQuery>;
+
+	/// How many relayers must attest a fastchain block before the bridge
+	/// advances to it.
+	#[pallet::storage]
+	pub type RequiredAttestations<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	/// Whether finality proofs arrive within the heartbeat timeout.
+	#[pallet::storage]
+	pub type CurrentMode<T: Config> = StorageValue<_, Mode, ValueQuery>;
+
+	/// Relay-chain block number the last finality proof was accepted at.
+	#[pallet::storage]
+	pub type LastHeartbeat<T: Config> = StorageValue<_, RelayBlockNumberOf<T>>;
 
 	#[pallet::genesis_config]
 	#[derive(DefaultNoBound)]
 	pub struct GenesisConfig<T: Config> {
-		pub relayer: Option<T::AccountId>,
+		pub relayers: Vec<T::AccountId>,
+		pub required_attestations: u32,
 	}
 
 	#[pallet::genesis_build]
 	impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
 		fn build(&self) {
-			Relayer::<T>::set(self.relayer.clone());
+			for relayer in &self.relayers {
+				Pallet::<T>::do_add_relayer(relayer.clone())
+					.expect("genesis relayers can afford the bond; qed");
+			}
+			RequiredAttestations::<T>::put(self.required_attestations);
 		}
 	}
 
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
-		/// The parachain accepted a new fastchain finality proof.
+		/// The parachain accepted a new fastchain finality proof, attested by
+		/// the required number of relayers.
 		FinalityProofAccepted {
 			who: T::AccountId,
 			number: <T::AnchoredChainHeader as HeaderT>::Number,
 			hash: <T::AnchoredChainHeader as HeaderT>::Hash,
 		},
-		/// The GRANDPA authority set was updated.
+		/// The GRANDPA authority set was handed off to the next set, as
+		/// proven by the previous one.
 		AuthoritySetUpdated { set_id: SetId, authorities: u64 },
+		/// A justified fastchain header scheduled an authority set change.
+		AuthoritySetChangeScheduled {
+			enact_at: <T::AnchoredChainHeader as HeaderT>::Number,
+			authorities: u64,
+		},
+		/// Root overwrote the GRANDPA authority set.
+		AuthoritySetOverridden { set_id: SetId, authorities: u64 },
+		/// No finality proof was accepted for `HeartbeatTimeout` relay-chain
+		/// blocks since the last heartbeat.
+		CoolDownEntered { last_heartbeat: RelayBlockNumberOf<T> },
+		/// A fresh finality proof was accepted during the cool-down.
+		OperationalResumed { at: RelayBlockNumberOf<T> },
+		/// A relayer attested that a fastchain block is finalized.
+		FinalityAttested {
+			relayer: T::AccountId,
+			number: <T::AnchoredChainHeader as HeaderT>::Number,
+			hash: <T::AnchoredChainHeader as HeaderT>::Hash,
+		},
+		/// A relayer was registered, `bond` is held from its account.
+		RelayerAdded { relayer: T::AccountId, bond: BalanceOf<T> },
+		/// A relayer was removed and its bond released.
+		RelayerRemoved { relayer: T::AccountId },
+		/// A relayer attested another block than the one the bridge advanced
+		/// to, and `amount` of its bond was slashed.
+		RelayerSlashed { relayer: T::AccountId, amount: BalanceOf<T> },
+		/// The number of attestations required to advance the bridge changed.
+		RequiredAttestationsSet { required: u32 },
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(_n: BlockNumberFor<T>) -> Weight {
+			if CurrentMode::<T>::get() == Mode::CoolDown {
+				return T::DbWeight::get().reads(1);
+			}
+
+			// the heartbeat timeout only runs from the first accepted proof on,
+			// neither a new chain nor an upgraded one cools down before.
+			let Some(last_heartbeat) = LastHeartbeat::<T>::get() else {
+				return T::DbWeight::get().reads(2);
+			};
+			let now = T::RelayBlockNumberProvider::current_block_number();
+			if now.saturating_sub(last_heartbeat) < T::HeartbeatTimeout::get() {
+				return T::DbWeight::get().reads(3);
+			}
+
+			CurrentMode::<T>::put(Mode::CoolDown);
+			Self::deposit_event(Event::<T>::CoolDownEntered { last_heartbeat });
+			T::DbWeight::get().reads_writes(3, 2)
+		}
 	}
 
 	#[pallet::error]
@@ -123,129 +354,402 @@ pub mod pallet {
 		InsufficientWeight,
 		AlreadyFinalized,
 		UnsupportedBlockNumber,
-		MismatchedTargets,
+		/// A precommit is not for the commit target or a descendant of it
+		/// through the votes ancestries.
+		PrecommitIsNotCommitDescendant,
+		/// The votes ancestries contain duplicate headers or headers no
+		/// precommit descends through.
+		RedundantVotesAncestries,
+		/// The submitted header is not the header of the commit target.
+		TargetHeaderMismatch,
 		NoPrecommits,
 		ComputationOverflow,
+		/// The authority set has more authorities than `MaxAuthorities`.
+		TooManyAuthorities,
+		/// The justification has more precommits than `MaxAuthorities`.
+		TooManyPrecommits,
+		/// The account is a relayer already.
+		AlreadyRelayer,
+		/// The account is not a relayer.
+		NotRelayer,
+		/// There are as many relayers as allowed.
+		TooManyRelayers,
+		/// The relayer attested a block above the last finalized one.
+		PendingAttestation,
+		/// The required number of attestations is zero or above the maximum
+		/// number of relayers.
+		InvalidThreshold,
 	}
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
-		/// Set (or refresh) the GRANDPA authority set that the parachain trusts for fastchain.
+		/// Overwrite the GRANDPA authority set that the parachain trusts for
+		/// fastchain, dropping any pending change.
+		///
+		/// The authority set otherwise follows the changes proven by finality
+		/// proofs, so this is meant for bootstrapping and emergencies only.
 		///
 		/// The call must be dispatched by `Root`.
 		#[pallet::call_index(0)]
-		#[pallet::weight(T::DbWeight::get().writes(1))]
+		#[pallet::weight(T::WeightInfo::set_authority_set(authorities.len() as u32))]
 		pub fn set_authority_set(
 			origin: OriginFor<T>,
 			set_id: SetId,
 			authorities: AuthorityList,
 		) -> DispatchResult {
-			match ensure_signed_or_root(origin)? {
-				Some(signer) => ensure!(Relayer::<T>::get() == Some(signer), BadOrigin),
-				None => {}, // root is allowed
-			}
+			ensure_root(origin)?;
 			ensure!(!authorities.is_empty(), Error::<T>::EmptyAuthoritySet);
+			let authorities = BoundedAuthorityList::<T>::try_from(authorities)
+				.map_err(|_| Error::<T>::TooManyAuthorities)?;
 
 			let authorities_len =
 				u64::try_from(authorities.len()).map_err(|_| Error::<T>::ComputationOverflow)?;
 
 			FastchainAuthoritySet::<T>::put(AuthoritySetData { set_id, authorities });
+			PendingAuthoritySetChange::<T>::kill();
 
-			Self::deposit_event(Event::AuthoritySetUpdated {
+			Self::deposit_event(Event::AuthoritySetOverridden {
 				set_id,
 				authorities: authorities_len,
 			});
 			Ok(())
 		}
 
-		/// Submit a `GrandpaJustification` produced by the fastchain node.
+		/// Submit a `GrandpaJustification` produced by the fastchain node,
+		/// attesting that its commit target is finalized.
+		///
+		/// The proof is accepted once the required number of relayers attested
+		/// the same block. The relayers that attested another block at the same
+		/// height then lose their bond.
+		///
+		/// The header of the commit target may be submitted along, so that its
+		/// state root is kept for verifying fastchain state. An authority set
+		/// change signalled in its digest is enacted once the current set has
+		/// finalized the block the change is scheduled at. It is enough that
+		/// one of the attestations of the block comes with its header.
+		///
+		/// Every accepted proof is a heartbeat of the fastchain, which ends a
+		/// cool-down.
 		#[pallet::call_index(1)]
-		#[pallet::weight(T::DbWeight::get().reads_writes(2, 2))]
+		#[pallet::weight(T::WeightInfo::submit_finality_proof(
+			justification.commit.precommits.len() as u32,
+			justification.votes_ancestries.len() as u32,
+		))]
 		pub fn submit_finality_proof(
 			origin: OriginFor<T>,
 			expected_set_id: SetId,
 			justification: BoundedGrandpaJustification<T::AnchoredChainHeader>,
+			target_header: Option<T::AnchoredChainHeader>,
 		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
-			ensure!(Some(who.clone()) == Relayer::<T>::get(), BadOrigin);
+			let mut relayers = Relayers::<T>::get().into_inner();
+			let index =
+				relayers.iter().position(|relayer| relayer.account == who).ok_or(BadOrigin)?;
 
 			ensure!(!justification.commit.precommits.is_empty(), Error::<T>::NoPrecommits);
+			let stored = StoredJustification::<T>::bounded(&justification)
+				.ok_or(Error::<T>::TooManyPrecommits)?;
 
 			let authority_set =
 				FastchainAuthoritySet::<T>::get().ok_or(Error::<T>::AuthoritySetNotInitialized)?;
 			ensure!(!authority_set.authorities.is_empty(), Error::<T>::EmptyAuthoritySet);
 			ensure!(authority_set.set_id == expected_set_id, Error::<T>::AuthoritySetMismatch);
 
-			// TODO(zotho): how do we verify that `target_hash` is hash of `target_number` block?
 			let target_hash = justification.commit.target_hash;
 			let target_number = justification.commit.target_number;
+			if let Some(header) = &target_header {
+				ensure!(
+					header.hash() == target_hash && *header.number() == target_number,
+					Error::<T>::TargetHeaderMismatch
+				);
+			}
 
 			if let Some(last) = LastFinalized::<T>::get() {
 				ensure!(target_number > last.number, Error::<T>::AlreadyFinalized);
 			}
 
-			// TODO(zotho): do we have to compute the total every time?
-			let mut total_weight: u128 = 0;
-			for &(_, weight) in &authority_set.authorities {
-				total_weight = total_weight
-					.checked_add(u128::from(weight))
-					.ok_or(Error::<T>::ComputationOverflow)?;
-			}
-			ensure!(total_weight > 0, Error::<T>::EmptyAuthoritySet);
+			verify_justification(&justification, authority_set.set_id, &authority_set.authorities)
+				.map_err(Error::<T>::from)?;
 
-			let mut seen = BTreeSet::new();
-			let mut signed_weight: u128 = 0;
+			let attested_header = match &target_header {
+				Some(header) => Some(Self::attested_header(header)?),
+				None => None,
+			};
 
-			for signed in &justification.commit.precommits {
-				ensure!(
-					signed.precommit.target_hash == target_hash &&
-						signed.precommit.target_number == justification.commit.target_number,
-					Error::<T>::MismatchedTargets
-				);
+			let previous = relayers[index].attestation.replace((target_number, target_hash));
+			if let Some(attested_header) = attested_header {
+				AttestedHeaders::<T>::insert(target_hash, attested_header);
+			}
+			if let Some((_, previous)) = previous {
+				Self::forget_unattested_header(&relayers, previous);
+			}
+			Self::deposit_event(Event::FinalityAttested {
+				relayer: who.clone(),
+				number: target_number,
+				hash: target_hash,
+			});
 
-				let signature_ok = sp_consensus_grandpa::check_message_signature(
-					&GrandpaMessage::Precommit(signed.precommit.clone()),
-					&signed.id,
-					&signed.signature,
-					justification.round,
-					authority_set.set_id,
-				)
-				.is_valid();
-				ensure!(signature_ok, Error::<T>::BadSignature);
-
-				// TODO(zotho): only first seen here. do we need filtering?
-				if seen.insert(signed.id.clone()) {
-					let weight = authority_set
-						.authorities
-						.iter()
-						.find_map(|(id, weight)| if *id == signed.id { Some(weight) } else { None })
-						.ok_or(Error::<T>::UnknownAuthority)?;
-					signed_weight = signed_weight.saturating_add(u128::from(*weight));
-				}
+			let attestations = relayers
+				.iter()
+				.filter(|relayer| relayer.attestation == Some((target_number, target_hash)))
+				.count();
+			if attestations < Self::required_attestations() as usize {
+				// relayers were not added, so they are within the bound.
+				Relayers::<T>::put(BoundedVec::truncate_from(relayers));
+				return Ok(());
 			}
 
-			ensure!(
-				signed_weight.saturating_mul(3) >= total_weight.saturating_mul(2),
-				Error::<T>::InsufficientWeight
-			);
+			let mut conflicting = Vec::new();
+			let mut settled = Vec::new();
+			relayers.retain_mut(|relayer| match relayer.attestation {
+				Some((at, attested)) if at == target_number && attested != target_hash => {
+					settled.push(attested);
+					conflicting.push(relayer.clone());
+					false
+				},
+				Some((at, attested)) if at <= target_number => {
+					settled.push(attested);
+					relayer.attestation = None;
+					true
+				},
+				_ => true,
+			});
+			// relayers were at most removed, so they are within the bound.
+			Relayers::<T>::put(BoundedVec::truncate_from(relayers));
+			conflicting.into_iter().for_each(Self::slash);
+			// the remaining attestations are of later blocks.
+			let attested_header = AttestedHeaders::<T>::take(target_hash);
+			settled.into_iter().for_each(AttestedHeaders::<T>::remove);
 
 			LastFinalized::<T>::put(FinalizedTarget { number: target_number, hash: target_hash });
-			LastJustification::<T>::put(justification);
+			LastFinalizedStateRoot::<T>::set(
+				attested_header.as_ref().map(|attested_header| attested_header.state_root),
+			);
+			LastJustification::<T>::put(stored);
 
 			Self::deposit_event(Event::FinalityProofAccepted {
 				who,
 				number: target_number,
 				hash: target_hash,
 			});
+
+			let now = T::RelayBlockNumberProvider::current_block_number();
+			LastHeartbeat::<T>::put(now);
+			if CurrentMode::<T>::get() == Mode::CoolDown {
+				CurrentMode::<T>::put(Mode::Operational);
+				Self::deposit_event(Event::OperationalResumed { at: now });
+			}
+
+			// The current set proved the change by finalizing the block that
+			// signalled it, and proves its enactment by finalizing the block the
+			// change is enacted at.
+			let mut set_id = authority_set.set_id;
+			if let Some(pending) = PendingAuthoritySetChange::<T>::get() {
+				if target_number >= pending.enact_at {
+					PendingAuthoritySetChange::<T>::kill();
+					set_id = Self::enact_authority_set(set_id, pending.authorities)?;
+				}
+			}
+			let scheduled =
+				attested_header.and_then(|attested_header| attested_header.scheduled_change);
+			if let Some((authorities, delay)) = scheduled {
+				if delay.is_zero() {
+					Self::enact_authority_set(set_id, authorities)?;
+				} else {
+					let enact_at = target_number.saturating_add(delay);
+					let authorities_len = u64::try_from(authorities.len())
+						.map_err(|_| Error::<T>::ComputationOverflow)?;
+					PendingAuthoritySetChange::<T>::put(PendingAuthoritySet {
+						authorities,
+						enact_at,
+					});
+					Self::deposit_event(Event::AuthoritySetChangeScheduled {
+						enact_at,
+						authorities: authorities_len,
+					});
+				}
+			}
 			Ok(())
 		}
 
-		#[pallet::call_index(2)]
-		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
-		pub fn set_relayer(origin: OriginFor<T>, new_relayer: T::AccountId) -> DispatchResult {
+		/// Register `relayer`, holding the relayer bond from its account.
+		#[pallet::call_index(3)]
+		#[pallet::weight(T::WeightInfo::add_relayer())]
+		pub fn add_relayer(origin: OriginFor<T>, relayer: T::AccountId) -> DispatchResult {
+			ensure_root(origin)?;
+			Self::do_add_relayer(relayer)
+		}
+
+		/// Remove `relayer`, releasing its bond.
+		#[pallet::call_index(4)]
+		#[pallet::weight(T::WeightInfo::remove_relayer())]
+		pub fn remove_relayer(origin: OriginFor<T>, relayer: T::AccountId) -> DispatchResult {
 			ensure_root(origin)?;
-			Relayer::<T>::put(new_relayer);
+			Self::do_remove_relayer(&relayer)
+		}
+
+		/// Stop relaying and release the bond of the caller.
+		///
+		/// Relayers can only retire while they have not attested a block above
+		/// the last finalized one.
+		#[pallet::call_index(5)]
+		#[pallet::weight(T::WeightInfo::retire_relayer())]
+		pub fn retire_relayer(origin: OriginFor<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let pending = Relayers::<T>::get()
+				.iter()
+				.find(|relayer| relayer.account == who)
+				.ok_or(Error::<T>::NotRelayer)?
+				.attestation
+				.is_some();
+			ensure!(!pending, Error::<T>::PendingAttestation);
+			Self::do_remove_relayer(&who)
+		}
+
+		/// Set how many relayers must attest a fastchain block before the
+		/// bridge advances to it.
+		#[pallet::call_index(6)]
+		#[pallet::weight(T::WeightInfo::set_required_attestations())]
+		pub fn set_required_attestations(origin: OriginFor<T>, required: u32) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!((1..=T::MaxRelayers::get()).contains(&required), Error::<T>::InvalidThreshold);
+			RequiredAttestations::<T>::put(required);
+			Self::deposit_event(Event::<T>::RequiredAttestationsSet { required });
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// The mode of the bridge.
+		pub fn mode() -> Mode {
+			CurrentMode::<T>::get()
+		}
+
+		/// Relay-chain block number the last finality proof was accepted at.
+		pub fn last_heartbeat() -> Option<RelayBlockNumberOf<T>> {
+			LastHeartbeat::<T>::get()
+		}
+
+		/// The registered relayers.
+		pub fn relayers() -> Vec<RelayerOf<T>> {
+			Relayers::<T>::get().into_inner()
+		}
+
+		/// How many relayers must attest a fastchain block before the bridge
+		/// advances to it.
+		pub fn required_attestations() -> u32 {
+			RequiredAttestations::<T>::get().max(1)
+		}
+
+		/// Register `account` as a relayer, holding the relayer bond from it.
+		pub fn do_add_relayer(account: T::AccountId) -> DispatchResult {
+			let mut relayers = Relayers::<T>::get();
+			ensure!(
+				relayers.iter().all(|relayer| relayer.account != account),
+				Error::<T>::AlreadyRelayer
+			);
+
+			let bond = T::RelayerBond::get();
+			T::Currency::hold(&HoldReason::RelayerBond.into(), &account, bond)?;
+			relayers
+				.try_push(RelayerStatus { account: account.clone(), bond, attestation: None })
+				.map_err(|_| Error::<T>::TooManyRelayers)?;
+			Relayers::<T>::put(relayers);
+
+			Self::deposit_event(Event::<T>::RelayerAdded { relayer: account, bond });
+			Ok(())
+		}
+
+		pub(crate) fn do_remove_relayer(account: &T::AccountId) -> DispatchResult {
+			let mut relayers = Relayers::<T>::get();
+			let index = relayers
+				.iter()
+				.position(|relayer| relayer.account == *account)
+				.ok_or(Error::<T>::NotRelayer)?;
+			let relayer = relayers.remove(index);
+			Relayers::<T>::put(&relayers);
+			if let Some((_, attested)) = relayer.attestation {
+				Self::forget_unattested_header(&relayers, attested);
+			}
+
+			T::Currency::release(
+				&HoldReason::RelayerBond.into(),
+				account,
+				relayer.bond,
+				Precision::BestEffort,
+			)?;
+			Self::deposit_event(Event::<T>::RelayerRemoved { relayer: relayer.account });
 			Ok(())
 		}
+
+		/// What `header` commits to, unless it signals a change to an empty or
+		/// too large authority set.
+		fn attested_header(
+			header: &T::AnchoredChainHeader,
+		) -> Result<AttestedHeader<T>, DispatchError> {
+			let scheduled_change = match scheduled_change(header) {
+				Some((authorities, delay)) => {
+					ensure!(!authorities.is_empty(), Error::<T>::EmptyAuthoritySet);
+					let authorities = BoundedAuthorityList::<T>::try_from(authorities)
+						.map_err(|_| Error::<T>::TooManyAuthorities)?;
+					Some((authorities, delay))
+				},
+				None => None,
+			};
+			Ok(AttestedHeader { state_root: *header.state_root(), scheduled_change })
+		}
+
+		/// Drop the header of the block with `hash` once none of `relayers`
+		/// attests it any more.
+		fn forget_unattested_header(relayers: &[RelayerOf<T>], hash: AnchoredHashOf<T>) {
+			if relayers.iter().all(|relayer| {
+				relayer.attestation.as_ref().is_none_or(|(_, attested)| *attested != hash)
+			}) {
+				AttestedHeaders::<T>::remove(hash);
+			}
+		}
+
+		fn slash(relayer: RelayerOf<T>) {
+			let (credit, _) =
+				T::Currency::slash(&HoldReason::RelayerBond.into(), &relayer.account, relayer.bond);
+			let amount = credit.peek();
+			T::Slash::on_unbalanced(credit);
+			Self::deposit_event(Event::<T>::RelayerSlashed { relayer: relayer.account, amount });
+		}
+
+		/// Hand the authority set off from set `set_id` to `authorities`,
+		/// returning the id of the new set.
+		fn enact_authority_set(
+			set_id: SetId,
+			authorities: BoundedAuthorityList<T>,
+		) -> Result<SetId, DispatchError> {
+			let set_id = set_id.checked_add(1).ok_or(Error::<T>::ComputationOverflow)?;
+			let authorities_len =
+				u64::try_from(authorities.len()).map_err(|_| Error::<T>::ComputationOverflow)?;
+
+			FastchainAuthoritySet::<T>::put(AuthoritySetData { set_id, authorities });
+
+			Self::deposit_event(Event::AuthoritySetUpdated {
+				set_id,
+				authorities: authorities_len,
+			});
+			Ok(set_id)
+		}
+	}
+}
+
+impl<T> From<JustificationError> for Error<T> {
+	fn from(error: JustificationError) -> Self {
+		match error {
+			JustificationError::NoPrecommits => Self::NoPrecommits,
+			JustificationError::EmptyAuthoritySet => Self::EmptyAuthoritySet,
+			JustificationError::ComputationOverflow => Self::ComputationOverflow,
+			JustificationError::PrecommitIsNotCommitDescendant =>
+				Self::PrecommitIsNotCommitDescendant,
+			JustificationError::RedundantVotesAncestries => Self::RedundantVotesAncestries,
+			JustificationError::UnknownAuthority => Self::UnknownAuthority,
+			JustificationError::BadSignature => Self::BadSignature,
+			JustificationError::InsufficientWeight => Self::InsufficientWeight,
+		}
 	}
 }
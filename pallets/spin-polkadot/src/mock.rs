@@ -0,0 +1,116 @@
+//! Test utilities
+
+#![cfg(test)]
+
+use crate::{self as pallet_spin_polkadot, AuthoritySetData, FastchainAuthoritySet};
+use frame_support::{
+	derive_impl,
+	traits::{ConstU32, ConstU64},
+};
+use sp_consensus_grandpa::SetId;
+use sp_keyring::Ed25519Keyring;
+use sp_runtime::{generic, traits::BlakeTwo256, BuildStorage};
+
+type Block = frame_system::mocking::MockBlock<Test>;
+
+/// The header of the anchored fastchain.
+pub type AnchoredHeader = generic::Header<u64, BlakeTwo256>;
+
+/// The relayer registered at genesis.
+pub const RELAYER: u64 = 1;
+
+/// Accounts that can afford the relayer bond but are no relayers at genesis.
+pub const OTHER_RELAYERS: [u64; 2] = [2, 3];
+
+/// The amount held from every relayer.
+pub const RELAYER_BOND: u64 = 100;
+
+/// The balance of every account at genesis.
+pub const BALANCE: u64 = 1_000;
+
+/// Number of blocks without a finality proof after which the bridge cools
+/// down. The system block number stands in for the relay-chain block number.
+pub const HEARTBEAT_TIMEOUT: u64 = 10;
+
+/// The id of the fastchain authority set at genesis.
+pub const SET_ID: SetId = 1;
+
+/// The fastchain authorities at genesis, each with a weight of one.
+pub const VOTERS: [Ed25519Keyring; 4] =
+	[Ed25519Keyring::Alice, Ed25519Keyring::Bob, Ed25519Keyring::Charlie, Ed25519Keyring::Dave];
+
+frame_support::construct_runtime!(
+	pub enum Test
+	{
+		System: frame_system,
+		Balances: pallet_balances,
+		SpinPolkadot: pallet_spin_polkadot,
+	}
+);
+
+#[derive_impl(frame_system::config_preludes::TestDefaultConfig)]
+impl frame_system::Config for Test {
+	type Block = Block;
+	type AccountData = pallet_balances::AccountData<u64>;
+}
+
+#[derive_impl(pallet_balances::config_preludes::TestDefaultConfig)]
+impl pallet_balances::Config for Test {
+	type AccountStore = System;
+}
+
+impl pallet_spin_polkadot::Config for Test {
+	type AnchoredChainHeader = AnchoredHeader;
+	type MaxAuthorities = ConstU32<{ VOTERS.len() as u32 }>;
+	type RelayBlockNumberProvider = System;
+	type HeartbeatTimeout = ConstU64<HEARTBEAT_TIMEOUT>;
+	type Currency = Balances;
+	type RuntimeHoldReason = RuntimeHoldReason;
+	type RelayerBond = ConstU64<RELAYER_BOND>;
+	type MaxRelayers = ConstU32<3>;
+	type Slash = ();
+	type WeightInfo = ();
+}
+
+pub fn build_ext_and_execute_test(test: impl FnOnce()) {
+	let mut storage = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: [RELAYER].iter().chain(&OTHER_RELAYERS).map(|who| (*who, BALANCE)).collect(),
+		dev_accounts: None,
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+	pallet_spin_polkadot::GenesisConfig::<Test> {
+		relayers: vec![RELAYER],
+		required_attestations: 1,
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+
+	let mut ext = sp_io::TestExternalities::from(storage);
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		FastchainAuthoritySet::<Test>::put(AuthoritySetData {
+			set_id: SET_ID,
+			authorities: VOTERS
+				.iter()
+				.map(|voter| (voter.public().into(), 1))
+				.collect::<Vec<_>>()
+				.try_into()
+				.unwrap(),
+		});
+		test()
+	});
+}
+
+/// Externalities with a keystore, for the benchmarks to sign precommits with.
+#[cfg(feature = "runtime-benchmarks")]
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	use sp_keystore::{testing::MemoryKeystore, KeystoreExt};
+
+	let storage = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
+	let mut ext = sp_io::TestExternalities::from(storage);
+	ext.register_extension(KeystoreExt::new(MemoryKeystore::new()));
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
@@ -0,0 +1,612 @@
+//! Tests for the module.
+
+#![cfg(test)]
+
+use crate::{
+	mock::{
+		build_ext_and_execute_test, AnchoredHeader, Balances, RuntimeOrigin, SpinPolkadot, System,
+		Test, BALANCE, HEARTBEAT_TIMEOUT, OTHER_RELAYERS, RELAYER, RELAYER_BOND, SET_ID, VOTERS,
+	},
+	AttestedHeaders, BoundedGrandpaJustification, Error, Event, FastchainAuthoritySet,
+	LastFinalized, LastFinalizedStateRoot, LastJustification, PendingAuthoritySetChange, Relayers,
+};
+use codec::Encode;
+use frame_support::{assert_noop, assert_ok, traits::Hooks, BoundedVec};
+use qfp_spin_polkadot::Mode;
+use sp_consensus_grandpa::{
+	AuthorityId, AuthorityList, AuthoritySignature, ConsensusLog, ScheduledChange, SetId,
+	GRANDPA_ENGINE_ID,
+};
+use sp_core::H256;
+use sp_keyring::Ed25519Keyring;
+use sp_runtime::{traits::Header as _, DigestItem, DispatchError};
+
+const ROUND: u64 = 7;
+
+type SignedPrecommit =
+	finality_grandpa::SignedPrecommit<H256, u64, AuthoritySignature, AuthorityId>;
+
+/// A chain of `len` headers, numbered from one.
+fn chain(len: u64) -> Vec<AnchoredHeader> {
+	let mut parent_hash = H256::zero();
+	(1..=len)
+		.map(|number| {
+			let header = AnchoredHeader::new(
+				number,
+				Default::default(),
+				H256::repeat_byte(number as u8),
+				parent_hash,
+				Default::default(),
+			);
+			parent_hash = header.hash();
+			header
+		})
+		.collect()
+}
+
+/// The child of `parent` signalling a change to `next` after `delay` blocks.
+fn signal_change(parent: &AnchoredHeader, next: &[Ed25519Keyring], delay: u64) -> AnchoredHeader {
+	let change = ScheduledChange { next_authorities: authorities(next), delay };
+	let mut header = AnchoredHeader::new(
+		parent.number() + 1,
+		Default::default(),
+		Default::default(),
+		parent.hash(),
+		Default::default(),
+	);
+	header.digest_mut().push(DigestItem::Consensus(
+		GRANDPA_ENGINE_ID,
+		ConsensusLog::<u64>::ScheduledChange(change).encode(),
+	));
+	header
+}
+
+fn authorities(voters: &[Ed25519Keyring]) -> AuthorityList {
+	voters.iter().map(|voter| (voter.public().into(), 1)).collect()
+}
+
+fn precommit(voter: Ed25519Keyring, target: &AnchoredHeader, set_id: SetId) -> SignedPrecommit {
+	let precommit =
+		finality_grandpa::Precommit { target_hash: target.hash(), target_number: *target.number() };
+	let payload = sp_consensus_grandpa::localized_payload(
+		ROUND,
+		set_id,
+		&finality_grandpa::Message::Precommit(precommit.clone()),
+	);
+	SignedPrecommit { precommit, signature: voter.sign(&payload).into(), id: voter.public().into() }
+}
+
+/// Precommits of `voters` for `target` in the genesis set.
+fn precommits(voters: &[Ed25519Keyring], target: &AnchoredHeader) -> Vec<SignedPrecommit> {
+	voters.iter().map(|voter| precommit(*voter, target, SET_ID)).collect()
+}
+
+fn justification(
+	target: &AnchoredHeader,
+	precommits: Vec<SignedPrecommit>,
+	votes_ancestries: Vec<AnchoredHeader>,
+) -> BoundedGrandpaJustification<AnchoredHeader> {
+	BoundedGrandpaJustification {
+		round: ROUND,
+		commit: finality_grandpa::Commit {
+			target_hash: target.hash(),
+			target_number: *target.number(),
+			precommits,
+		},
+		votes_ancestries: BoundedVec::truncate_from(votes_ancestries),
+	}
+}
+
+fn submit(
+	justification: BoundedGrandpaJustification<AnchoredHeader>,
+	target_header: Option<AnchoredHeader>,
+) -> frame_support::dispatch::DispatchResult {
+	submit_in(SET_ID, justification, target_header)
+}
+
+fn submit_in(
+	set_id: SetId,
+	justification: BoundedGrandpaJustification<AnchoredHeader>,
+	target_header: Option<AnchoredHeader>,
+) -> frame_support::dispatch::DispatchResult {
+	SpinPolkadot::submit_finality_proof(
+		RuntimeOrigin::signed(RELAYER),
+		set_id,
+		justification,
+		target_header,
+	)
+}
+
+/// Attest `target` as `relayer`, with precommits of three of the voters.
+fn attest(relayer: u64, target: &AnchoredHeader) -> frame_support::dispatch::DispatchResult {
+	SpinPolkadot::submit_finality_proof(
+		RuntimeOrigin::signed(relayer),
+		SET_ID,
+		justification(target, precommits(&VOTERS[..3], target), vec![]),
+		None,
+	)
+}
+
+/// Register the other relayers and require `required` attestations.
+fn register_other_relayers(required: u32) {
+	for relayer in OTHER_RELAYERS {
+		assert_ok!(SpinPolkadot::add_relayer(RuntimeOrigin::root(), relayer));
+	}
+	assert_ok!(SpinPolkadot::set_required_attestations(RuntimeOrigin::root(), required));
+}
+
+#[test]
+fn justification_of_two_thirds_of_the_voters_is_accepted() {
+	build_ext_and_execute_test(|| {
+		let headers = chain(3);
+		let target = &headers[2];
+		let precommits = precommits(&VOTERS[..3], target);
+
+		assert_ok!(submit(justification(target, precommits, vec![]), Some(target.clone())));
+
+		let last = LastFinalized::<Test>::get().unwrap();
+		assert_eq!((last.number, last.hash), (3, target.hash()));
+		assert_eq!(LastFinalizedStateRoot::<Test>::get(), Some(*target.state_root()));
+		assert_eq!(LastJustification::<Test>::get().unwrap().precommits.len(), 3);
+	});
+}
+
+#[test]
+fn justification_of_less_than_two_thirds_of_the_voters_is_rejected() {
+	build_ext_and_execute_test(|| {
+		let headers = chain(3);
+		let target = &headers[2];
+		let precommits = precommits(&VOTERS[..2], target);
+
+		assert_noop!(
+			submit(justification(target, precommits, vec![]), None),
+			Error::<Test>::InsufficientWeight
+		);
+	});
+}
+
+#[test]
+fn precommits_signed_for_another_set_are_rejected() {
+	build_ext_and_execute_test(|| {
+		let headers = chain(3);
+		let target = &headers[2];
+		let precommits =
+			VOTERS[..3].iter().map(|voter| precommit(*voter, target, SET_ID + 1)).collect();
+
+		assert_noop!(
+			submit(justification(target, precommits, vec![]), None),
+			Error::<Test>::BadSignature
+		);
+	});
+}
+
+#[test]
+fn precommits_for_descendants_are_accepted_through_the_ancestries() {
+	build_ext_and_execute_test(|| {
+		let headers = chain(5);
+		let target = &headers[1];
+		let precommits = vec![
+			precommit(VOTERS[0], target, SET_ID),
+			precommit(VOTERS[1], &headers[3], SET_ID),
+			precommit(VOTERS[2], &headers[4], SET_ID),
+		];
+		let ancestries = headers[2..5].to_vec();
+
+		assert_ok!(submit(justification(target, precommits, ancestries), None));
+
+		assert_eq!(LastFinalized::<Test>::get().unwrap().number, 2);
+		assert_eq!(LastFinalizedStateRoot::<Test>::get(), None);
+	});
+}
+
+#[test]
+fn precommits_without_a_route_to_the_target_are_rejected() {
+	build_ext_and_execute_test(|| {
+		let headers = chain(5);
+		let target = &headers[1];
+		let precommits = vec![
+			precommit(VOTERS[0], target, SET_ID),
+			precommit(VOTERS[1], target, SET_ID),
+			precommit(VOTERS[2], &headers[4], SET_ID),
+		];
+		// the parent of the precommit target is missing.
+		let ancestries = vec![headers[2].clone(), headers[4].clone()];
+
+		assert_noop!(
+			submit(justification(target, precommits, ancestries), None),
+			Error::<Test>::PrecommitIsNotCommitDescendant
+		);
+
+		// nor does a block on another fork descend from the target.
+		let fork = AnchoredHeader::new(
+			3,
+			Default::default(),
+			Default::default(),
+			headers[0].hash(),
+			Default::default(),
+		);
+		let precommits = vec![
+			precommit(VOTERS[0], target, SET_ID),
+			precommit(VOTERS[1], target, SET_ID),
+			precommit(VOTERS[2], &fork, SET_ID),
+		];
+		assert_noop!(
+			submit(justification(target, precommits, vec![fork]), None),
+			Error::<Test>::PrecommitIsNotCommitDescendant
+		);
+	});
+}
+
+#[test]
+fn unused_ancestries_are_rejected() {
+	build_ext_and_execute_test(|| {
+		let headers = chain(4);
+		let target = &headers[1];
+		let precommits = precommits(&VOTERS[..3], target);
+
+		assert_noop!(
+			submit(justification(target, precommits, vec![headers[2].clone()]), None),
+			Error::<Test>::RedundantVotesAncestries
+		);
+	});
+}
+
+#[test]
+fn target_header_must_match_the_commit() {
+	build_ext_and_execute_test(|| {
+		let headers = chain(3);
+		let target = &headers[2];
+		let precommits = precommits(&VOTERS[..3], target);
+
+		assert_noop!(
+			submit(justification(target, precommits, vec![]), Some(headers[1].clone())),
+			Error::<Test>::TargetHeaderMismatch
+		);
+	});
+}
+
+#[test]
+fn lists_longer_than_max_authorities_are_rejected() {
+	build_ext_and_execute_test(|| {
+		let headers = chain(3);
+		let target = &headers[2];
+		let mut precommits = precommits(&VOTERS, target);
+		precommits.push(precommit(VOTERS[0], target, SET_ID));
+
+		assert_noop!(
+			submit(justification(target, precommits, vec![]), None),
+			Error::<Test>::TooManyPrecommits
+		);
+
+		let too_many = [VOTERS.as_slice(), &[Ed25519Keyring::Eve]].concat();
+		assert_noop!(
+			SpinPolkadot::set_authority_set(
+				RuntimeOrigin::root(),
+				SET_ID + 1,
+				authorities(&too_many)
+			),
+			Error::<Test>::TooManyAuthorities
+		);
+	});
+}
+
+#[test]
+fn change_signalled_in_a_justified_header_hands_the_set_off() {
+	build_ext_and_execute_test(|| {
+		let next = [Ed25519Keyring::Eve, Ed25519Keyring::Ferdie];
+		let headers = chain(2);
+		let signal = signal_change(&headers[1], &next, 0);
+		let precommits = precommits(&VOTERS[..3], &signal);
+
+		assert_ok!(submit(justification(&signal, precommits, vec![]), Some(signal.clone())));
+
+		let set = FastchainAuthoritySet::<Test>::get().unwrap();
+		assert_eq!((set.set_id, set.authorities.into_inner()), (SET_ID + 1, authorities(&next)));
+		System::assert_last_event(
+			Event::<Test>::AuthoritySetUpdated { set_id: SET_ID + 1, authorities: 2 }.into(),
+		);
+
+		// the next block is finalized by the new set only.
+		let target = AnchoredHeader::new(
+			4,
+			Default::default(),
+			Default::default(),
+			signal.hash(),
+			Default::default(),
+		);
+		let old_set = justification(&target, precommits(&VOTERS[..3], &target), vec![]);
+		assert_noop!(submit(old_set, None), Error::<Test>::AuthoritySetMismatch);
+		let new_set = next.iter().map(|voter| precommit(*voter, &target, SET_ID + 1)).collect();
+		assert_ok!(submit_in(SET_ID + 1, justification(&target, new_set, vec![]), None));
+	});
+}
+
+#[test]
+fn delayed_change_is_enacted_once_the_previous_set_finalizes_it() {
+	build_ext_and_execute_test(|| {
+		let next = [Ed25519Keyring::Eve, Ed25519Keyring::Ferdie];
+		let signal = signal_change(&chain(1)[0], &next, 2);
+		let precommits_for = |target: &AnchoredHeader| precommits(&VOTERS[..3], target);
+
+		assert_ok!(submit(
+			justification(&signal, precommits_for(&signal), vec![]),
+			Some(signal.clone())
+		));
+		assert_eq!(PendingAuthoritySetChange::<Test>::get().unwrap().enact_at, 4);
+		assert_eq!(FastchainAuthoritySet::<Test>::get().unwrap().set_id, SET_ID);
+
+		let mut parent = signal;
+		for number in 3..=4 {
+			let target = AnchoredHeader::new(
+				number,
+				Default::default(),
+				Default::default(),
+				parent.hash(),
+				Default::default(),
+			);
+			assert_ok!(submit(justification(&target, precommits_for(&target), vec![]), None));
+			parent = target;
+		}
+
+		assert!(PendingAuthoritySetChange::<Test>::get().is_none());
+		let set = FastchainAuthoritySet::<Test>::get().unwrap();
+		assert_eq!((set.set_id, set.authorities.into_inner()), (SET_ID + 1, authorities(&next)));
+	});
+}
+
+#[test]
+fn only_root_overrides_the_authority_set() {
+	build_ext_and_execute_test(|| {
+		let next = [Ed25519Keyring::Eve, Ed25519Keyring::Ferdie];
+
+		assert_noop!(
+			SpinPolkadot::set_authority_set(
+				RuntimeOrigin::signed(RELAYER),
+				SET_ID + 1,
+				authorities(&next)
+			),
+			DispatchError::BadOrigin
+		);
+
+		let signal = signal_change(&chain(1)[0], &VOTERS, 5);
+		assert_ok!(submit(
+			justification(&signal, precommits(&VOTERS[..3], &signal), vec![]),
+			Some(signal.clone())
+		));
+		assert_ok!(SpinPolkadot::set_authority_set(
+			RuntimeOrigin::root(),
+			SET_ID + 5,
+			authorities(&next)
+		));
+
+		assert!(PendingAuthoritySetChange::<Test>::get().is_none());
+		assert_eq!(FastchainAuthoritySet::<Test>::get().unwrap().set_id, SET_ID + 5);
+		System::assert_last_event(
+			Event::<Test>::AuthoritySetOverridden { set_id: SET_ID + 5, authorities: 2 }.into(),
+		);
+	});
+}
+
+/// Advance to block `n` and run the hook of the pallet.
+fn run_to_block(n: u64) {
+	System::set_block_number(n);
+	SpinPolkadot::on_initialize(n);
+}
+
+#[test]
+fn cools_down_when_proofs_stall_and_resumes_on_a_fresh_proof() {
+	build_ext_and_execute_test(|| {
+		let headers = chain(2);
+		let justify = |target: &AnchoredHeader| {
+			justification(target, precommits(&VOTERS[..3], target), vec![])
+		};
+		assert_ok!(submit(justify(&headers[0]), None));
+		assert_eq!(SpinPolkadot::last_heartbeat(), Some(1));
+
+		run_to_block(HEARTBEAT_TIMEOUT);
+		assert_eq!(SpinPolkadot::mode(), Mode::Operational);
+
+		run_to_block(HEARTBEAT_TIMEOUT + 1);
+		assert_eq!(SpinPolkadot::mode(), Mode::CoolDown);
+		System::assert_last_event(Event::<Test>::CoolDownEntered { last_heartbeat: 1 }.into());
+
+		// the cool-down is entered once.
+		System::reset_events();
+		run_to_block(HEARTBEAT_TIMEOUT + 2);
+		assert!(System::events().is_empty());
+
+		assert_ok!(submit(justify(&headers[1]), None));
+		assert_eq!(SpinPolkadot::mode(), Mode::Operational);
+		assert_eq!(SpinPolkadot::last_heartbeat(), Some(HEARTBEAT_TIMEOUT + 2));
+		System::assert_last_event(
+			Event::<Test>::OperationalResumed { at: HEARTBEAT_TIMEOUT + 2 }.into(),
+		);
+	});
+}
+
+#[test]
+fn heartbeat_timeout_runs_from_the_first_proof() {
+	build_ext_and_execute_test(|| {
+		// the first block after deployment, and long after, with no proof yet.
+		run_to_block(2);
+		assert_eq!(SpinPolkadot::mode(), Mode::Operational);
+		run_to_block(3 * HEARTBEAT_TIMEOUT);
+		assert_eq!(SpinPolkadot::mode(), Mode::Operational);
+		assert_eq!(SpinPolkadot::last_heartbeat(), None);
+		assert!(System::events().is_empty());
+
+		let target = &chain(1)[0];
+		assert_ok!(submit(justification(target, precommits(&VOTERS[..3], target), vec![]), None));
+		assert_eq!(SpinPolkadot::last_heartbeat(), Some(3 * HEARTBEAT_TIMEOUT));
+
+		run_to_block(4 * HEARTBEAT_TIMEOUT);
+		assert_eq!(SpinPolkadot::mode(), Mode::CoolDown);
+		System::assert_last_event(
+			Event::<Test>::CoolDownEntered { last_heartbeat: 3 * HEARTBEAT_TIMEOUT }.into(),
+		);
+	});
+}
+
+#[test]
+fn relayers_are_bonded_and_unbonded() {
+	build_ext_and_execute_test(|| {
+		let relayer = OTHER_RELAYERS[0];
+		assert_noop!(
+			SpinPolkadot::add_relayer(RuntimeOrigin::signed(RELAYER), relayer),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(attest(relayer, &chain(1)[0]), DispatchError::BadOrigin);
+
+		assert_ok!(SpinPolkadot::add_relayer(RuntimeOrigin::root(), relayer));
+		assert_eq!(Balances::reserved_balance(relayer), RELAYER_BOND);
+		System::assert_last_event(
+			Event::<Test>::RelayerAdded { relayer, bond: RELAYER_BOND }.into(),
+		);
+		assert_noop!(
+			SpinPolkadot::add_relayer(RuntimeOrigin::root(), relayer),
+			Error::<Test>::AlreadyRelayer
+		);
+
+		assert_ok!(SpinPolkadot::remove_relayer(RuntimeOrigin::root(), relayer));
+		assert_eq!(Balances::reserved_balance(relayer), 0);
+		System::assert_last_event(Event::<Test>::RelayerRemoved { relayer }.into());
+		assert_noop!(
+			SpinPolkadot::retire_relayer(RuntimeOrigin::signed(relayer)),
+			Error::<Test>::NotRelayer
+		);
+	});
+}
+
+#[test]
+fn relayers_retire_once_the_bridge_reached_their_attestation() {
+	build_ext_and_execute_test(|| {
+		register_other_relayers(2);
+		let headers = chain(1);
+
+		assert_ok!(attest(RELAYER, &headers[0]));
+		assert_noop!(
+			SpinPolkadot::retire_relayer(RuntimeOrigin::signed(RELAYER)),
+			Error::<Test>::PendingAttestation
+		);
+
+		assert_ok!(attest(OTHER_RELAYERS[0], &headers[0]));
+		assert_ok!(SpinPolkadot::retire_relayer(RuntimeOrigin::signed(RELAYER)));
+		assert_eq!(Balances::reserved_balance(RELAYER), 0);
+	});
+}
+
+#[test]
+fn bridge_advances_once_enough_relayers_attested() {
+	build_ext_and_execute_test(|| {
+		register_other_relayers(2);
+		assert_noop!(
+			SpinPolkadot::set_required_attestations(RuntimeOrigin::root(), 4),
+			Error::<Test>::InvalidThreshold
+		);
+		let headers = chain(2);
+
+		assert_ok!(attest(RELAYER, &headers[1]));
+		assert!(LastFinalized::<Test>::get().is_none());
+		assert_eq!(SpinPolkadot::last_heartbeat(), None);
+		System::assert_last_event(
+			Event::<Test>::FinalityAttested {
+				relayer: RELAYER,
+				number: 2,
+				hash: headers[1].hash(),
+			}
+			.into(),
+		);
+
+		// an attestation of another height does not count.
+		assert_ok!(attest(OTHER_RELAYERS[0], &headers[0]));
+		assert!(LastFinalized::<Test>::get().is_none());
+
+		assert_ok!(attest(OTHER_RELAYERS[1], &headers[1]));
+		assert_eq!(LastFinalized::<Test>::get().unwrap().number, 2);
+		assert!(SpinPolkadot::relayers().iter().all(|relayer| relayer.attestation.is_none()));
+		assert_noop!(attest(OTHER_RELAYERS[0], &headers[1]), Error::<Test>::AlreadyFinalized);
+	});
+}
+
+#[test]
+fn relayers_attesting_another_block_are_slashed() {
+	build_ext_and_execute_test(|| {
+		register_other_relayers(2);
+		let headers = chain(2);
+		let fork = AnchoredHeader::new(
+			2,
+			Default::default(),
+			Default::default(),
+			headers[0].hash(),
+			Default::default(),
+		);
+		let slashed = OTHER_RELAYERS[1];
+
+		assert_ok!(attest(slashed, &fork));
+		assert_ok!(attest(RELAYER, &headers[1]));
+		assert_ok!(attest(OTHER_RELAYERS[0], &headers[1]));
+
+		assert_eq!(LastFinalized::<Test>::get().unwrap().hash, headers[1].hash());
+		assert!(Relayers::<Test>::get().iter().all(|relayer| relayer.account != slashed));
+		assert_eq!(Balances::reserved_balance(slashed), 0);
+		assert_eq!(Balances::free_balance(slashed), BALANCE - RELAYER_BOND);
+		System::assert_has_event(
+			Event::<Test>::RelayerSlashed { relayer: slashed, amount: RELAYER_BOND }.into(),
+		);
+	});
+}
+
+#[test]
+fn header_of_any_attestation_hands_the_set_off() {
+	build_ext_and_execute_test(|| {
+		register_other_relayers(2);
+		let next = [Ed25519Keyring::Eve, Ed25519Keyring::Ferdie];
+		let signal = signal_change(&chain(1)[0], &next, 0);
+		let signalled = || justification(&signal, precommits(&VOTERS[..3], &signal), vec![]);
+
+		assert_ok!(SpinPolkadot::submit_finality_proof(
+			RuntimeOrigin::signed(RELAYER),
+			SET_ID,
+			signalled(),
+			Some(signal.clone()),
+		));
+		assert!(AttestedHeaders::<Test>::contains_key(signal.hash()));
+		assert_eq!(FastchainAuthoritySet::<Test>::get().unwrap().set_id, SET_ID);
+
+		// the attestation completing the quorum comes without the header.
+		assert_ok!(attest(OTHER_RELAYERS[0], &signal));
+		assert_eq!(LastFinalized::<Test>::get().unwrap().hash, signal.hash());
+		assert_eq!(LastFinalizedStateRoot::<Test>::get(), Some(*signal.state_root()));
+		let set = FastchainAuthoritySet::<Test>::get().unwrap();
+		assert_eq!((set.set_id, set.authorities.into_inner()), (SET_ID + 1, authorities(&next)));
+		assert_eq!(AttestedHeaders::<Test>::iter().count(), 0);
+	});
+}
+
+#[test]
+fn headers_no_relayer_attests_any_more_are_dropped() {
+	build_ext_and_execute_test(|| {
+		register_other_relayers(3);
+		let headers = chain(2);
+		let attest_with_header = |relayer: u64, target: &AnchoredHeader| {
+			SpinPolkadot::submit_finality_proof(
+				RuntimeOrigin::signed(relayer),
+				SET_ID,
+				justification(target, precommits(&VOTERS[..3], target), vec![]),
+				Some(target.clone()),
+			)
+		};
+
+		assert_ok!(attest_with_header(RELAYER, &headers[0]));
+		assert_ok!(attest_with_header(OTHER_RELAYERS[1], &headers[1]));
+		// attesting another block drops the header of the previous one.
+		assert_ok!(attest(RELAYER, &headers[1]));
+		assert!(!AttestedHeaders::<Test>::contains_key(headers[0].hash()));
+
+		// so does removing the last relayer attesting the block.
+		assert_ok!(attest_with_header(OTHER_RELAYERS[0], &chain(3)[2]));
+		assert_ok!(SpinPolkadot::remove_relayer(RuntimeOrigin::root(), OTHER_RELAYERS[0]));
+		assert!(!AttestedHeaders::<Test>::contains_key(chain(3)[2].hash()));
+		assert!(AttestedHeaders::<Test>::contains_key(headers[1].hash()));
+	});
+}
@@ -0,0 +1,55 @@
+//! Weights for `pallet_spin_polkadot`.
+//!
+//! The `()` implementation holds estimates for runtimes that did not generate
+//! weights with the benchmarks of the pallet yet. Verifying the signatures of
+//! the precommits dominates the cost of a finality proof.
+
+use frame_support::weights::{constants::RocksDbWeight, Weight};
+
+/// Weight functions needed for `pallet_spin_polkadot`.
+pub trait WeightInfo {
+	/// `a` is the number of authorities of the set.
+	fn set_authority_set(a: u32) -> Weight;
+	/// `p` is the number of precommits and `v` the number of votes ancestries
+	/// of the justification.
+	fn submit_finality_proof(p: u32, v: u32) -> Weight;
+	fn add_relayer() -> Weight;
+	fn remove_relayer() -> Weight;
+	fn retire_relayer() -> Weight;
+	fn set_required_attestations() -> Weight;
+}
+
+/// Estimates for authority sets of up to 32 authorities and up to 16
+/// relayers, all but one slashed.
+impl WeightInfo for () {
+	fn set_authority_set(a: u32) -> Weight {
+		Weight::from_parts(10_000_000, 0)
+			.saturating_add(Weight::from_parts(100_000, 0).saturating_mul(a.into()))
+			.saturating_add(RocksDbWeight::get().writes(2))
+	}
+	fn submit_finality_proof(p: u32, v: u32) -> Weight {
+		Weight::from_parts(120_000_000, 12_500)
+			.saturating_add(Weight::from_parts(60_000_000, 0).saturating_mul(p.into()))
+			.saturating_add(Weight::from_parts(5_000_000, 0).saturating_mul(v.into()))
+			.saturating_add(RocksDbWeight::get().reads(8))
+			.saturating_add(RocksDbWeight::get().writes(56))
+	}
+	fn add_relayer() -> Weight {
+		Weight::from_parts(40_000_000, 4_500)
+			.saturating_add(RocksDbWeight::get().reads(3))
+			.saturating_add(RocksDbWeight::get().writes(3))
+	}
+	fn remove_relayer() -> Weight {
+		Weight::from_parts(40_000_000, 4_500)
+			.saturating_add(RocksDbWeight::get().reads(3))
+			.saturating_add(RocksDbWeight::get().writes(4))
+	}
+	fn retire_relayer() -> Weight {
+		Weight::from_parts(40_000_000, 4_500)
+			.saturating_add(RocksDbWeight::get().reads(3))
+			.saturating_add(RocksDbWeight::get().writes(4))
+	}
+	fn set_required_attestations() -> Weight {
+		Weight::from_parts(8_000_000, 0).saturating_add(RocksDbWeight::get().writes(1))
+	}
+}
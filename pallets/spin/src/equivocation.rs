@@ -0,0 +1,283 @@
+// Copyright (C) QF Network, 2025.
+// Copyright (C) Parity Technologies (UK) Ltd., until 2025.
+// SPDX-License-Identifier: Apache-2.0
+
+//! An opt-in utility module for reporting equivocations.
+//!
+//! This module defines an offence type for SPIN equivocations and some utility
+//! traits to wire together:
+//! - a system for reporting offences;
+//! - a system for submitting unsigned transactions;
+//! - a way to get the current block author;
+//!
+//! These can be used in an offchain context in order to submit equivocation
+//! reporting extrinsics (from the client that's importing blocks).
+//!
+//! IMPORTANT:
+//! When using this module for enabling equivocation reporting it is required
+//! that the `ValidateUnsigned` for the SPIN pallet is used in the runtime
+//! definition.
+//!
+//! SPIN has no notion of epochs, so only equivocations that happened during
+//! the current session can be reported. The key ownership proof must
+//! therefore be generated for the current session too.
+
+use alloc::{boxed::Box, vec, vec::Vec};
+use frame_support::{
+	traits::{Get, KeyOwnerProofSystem, ValidatorSet},
+	weights::{
+		constants::{WEIGHT_REF_TIME_PER_MICROS, WEIGHT_REF_TIME_PER_NANOS},
+		Weight,
+	},
+};
+use frame_system::pallet_prelude::HeaderFor;
+use log::{error, info};
+use qfp_consensus_spin::{Slot, SpinEquivocationProof, KEY_TYPE};
+use sp_runtime::{
+	traits::Header as HeaderT,
+	transaction_validity::{
+		InvalidTransaction, TransactionPriority, TransactionSource, TransactionValidity,
+		TransactionValidityError, ValidTransaction,
+	},
+	DispatchError, KeyTypeId, Perbill,
+};
+use sp_session::{GetSessionNumber, GetValidatorCount};
+use sp_staking::{
+	offence::{Kind, Offence, OffenceReportSystem, ReportOffence},
+	SessionIndex,
+};
+
+use crate::{Call, Config, Error, Pallet, SessionStartSlot, LOG_TARGET};
+
+/// SPIN equivocation offence report.
+///
+/// When a validator released two or more blocks at the same slot.
+pub struct EquivocationOffence<Offender> {
+	/// A SPIN slot in which this incident happened.
+	pub slot: Slot,
+	/// The session index in which the incident happened.
+	pub session_index: SessionIndex,
+	/// The size of the validator set at the time of the offence.
+	pub validator_set_count: u32,
+	/// The authority that produced the equivocation.
+	pub offender: Offender,
+}
+
+impl<Offender: Clone> Offence<Offender> for EquivocationOffence<Offender> {
+	const ID: Kind = *b"spin:equivocatio";
+	type TimeSlot = Slot;
+
+	fn offenders(&self) -> Vec<Offender> {
+		vec![self.offender.clone()]
+	}
+
+	fn session_index(&self) -> SessionIndex {
+		self.session_index
+	}
+
+	fn validator_set_count(&self) -> u32 {
+		self.validator_set_count
+	}
+
+	fn time_slot(&self) -> Self::TimeSlot {
+		self.slot
+	}
+
+	// The formula is min((3k / n)^2, 1)
+	// where k = offenders_number and n = validators_number
+	fn slash_fraction(&self, offenders_count: u32) -> Perbill {
+		// Perbill type domain is [0, 1] by definition
+		Perbill::from_rational(3 * offenders_count, self.validator_set_count).square()
+	}
+}
+
+/// SPIN equivocation offence report system.
+///
+/// This type implements `OffenceReportSystem` such that:
+/// - Equivocation reports are published on-chain as unsigned extrinsic via
+///   `offchain::CreateTransactionBase`.
+/// - On-chain validity checks and processing are mostly delegated to the user provided generic
+///   types implementing `KeyOwnerProofSystem` and `ReportOffence` traits.
+/// - Offence reporter for unsigned transactions is fetched via the authorship pallet.
+pub struct EquivocationReportSystem<T, R, P, L>(core::marker::PhantomData<(T, R, P, L)>);
+
+impl<T, R, P, L>
+	OffenceReportSystem<
+		Option<T::AccountId>,
+		(SpinEquivocationProof<HeaderFor<T>, T::AuthorityId>, T::KeyOwnerProof),
+	> for EquivocationReportSystem<T, R, P, L>
+where
+	T: Config + pallet_authorship::Config + frame_system::offchain::CreateBare<Call<T>>,
+	R: ReportOffence<
+		T::AccountId,
+		P::IdentificationTuple,
+		EquivocationOffence<P::IdentificationTuple>,
+	>,
+	P: KeyOwnerProofSystem<(KeyTypeId, T::AuthorityId), Proof = T::KeyOwnerProof>,
+	P::IdentificationTuple: Clone,
+	L: Get<u64>,
+{
+	type Longevity = L;
+
+	fn publish_evidence(
+		evidence: (SpinEquivocationProof<HeaderFor<T>, T::AuthorityId>, T::KeyOwnerProof),
+	) -> Result<(), ()> {
+		use frame_system::offchain::SubmitTransaction;
+		let (equivocation_proof, key_owner_proof) = evidence;
+
+		let call = Call::report_equivocation_unsigned {
+			equivocation_proof: Box::new(equivocation_proof),
+			key_owner_proof,
+		};
+		let xt = T::create_bare(call.into());
+		let res = SubmitTransaction::<T, Call<T>>::submit_transaction(xt);
+		match res {
+			Ok(_) => info!(target: LOG_TARGET, "Submitted equivocation report"),
+			Err(e) => error!(target: LOG_TARGET, "Error submitting equivocation report: {:?}", e),
+		}
+		res
+	}
+
+	fn check_evidence(
+		evidence: (SpinEquivocationProof<HeaderFor<T>, T::AuthorityId>, T::KeyOwnerProof),
+	) -> Result<(), TransactionValidityError> {
+		let (equivocation_proof, key_owner_proof) = evidence;
+
+		// Check the membership proof to extract the offender's id
+		let key = (KEY_TYPE, equivocation_proof.offender.clone());
+		let offender = P::check_proof(key, key_owner_proof).ok_or(InvalidTransaction::BadProof)?;
+
+		// Check if the offence has already been reported, and if so then we can discard the report.
+		if R::is_known_offence(&[offender], &equivocation_proof.slot) {
+			Err(InvalidTransaction::Stale.into())
+		} else {
+			Ok(())
+		}
+	}
+
+	fn process_evidence(
+		reporter: Option<T::AccountId>,
+		evidence: (SpinEquivocationProof<HeaderFor<T>, T::AuthorityId>, T::KeyOwnerProof),
+	) -> Result<(), DispatchError> {
+		let (equivocation_proof, key_owner_proof) = evidence;
+		let reporter = reporter.or_else(|| pallet_authorship::Pallet::<T>::author());
+		let offender = equivocation_proof.offender.clone();
+		let slot = equivocation_proof.slot;
+
+		// Validate the equivocation proof (check votes are different and signatures are valid)
+		if !Pallet::<T>::check_equivocation_proof(equivocation_proof) {
+			return Err(Error::<T>::InvalidEquivocationProof.into());
+		}
+
+		let validator_set_count = key_owner_proof.validator_count();
+		let session_index = key_owner_proof.session();
+
+		// SPIN has no epochs to derive the session of a slot from, so we only
+		// accept equivocations that happened during the current session.
+		let current_session = T::ValidatorSet::session_index();
+		if session_index != current_session || slot < SessionStartSlot::<T>::get() {
+			return Err(Error::<T>::InvalidKeyOwnershipProof.into());
+		}
+
+		// Check the membership proof and extract the offender's id
+		let key = (KEY_TYPE, offender);
+		let offender =
+			P::check_proof(key, key_owner_proof).ok_or(Error::<T>::InvalidKeyOwnershipProof)?;
+
+		let offence = EquivocationOffence { slot, validator_set_count, offender, session_index };
+
+		R::report_offence(reporter.into_iter().collect(), offence)
+			.map_err(|_| Error::<T>::DuplicateOffenceReport)?;
+
+		Ok(())
+	}
+}
+
+/// Weight of `report_equivocation` and `report_equivocation_unsigned`.
+///
+/// Mirrors the GRANDPA defaults until SPIN gets benchmarks of its own.
+pub(crate) fn report_equivocation_weight<T: Config>(validator_count: u32) -> Weight {
+	// checking membership proof
+	Weight::from_parts(35u64 * WEIGHT_REF_TIME_PER_MICROS, 0)
+		.saturating_add(
+			Weight::from_parts(175u64 * WEIGHT_REF_TIME_PER_NANOS, 0)
+				.saturating_mul(validator_count.into()),
+		)
+		.saturating_add(T::DbWeight::get().reads(6))
+		// check equivocation proof
+		.saturating_add(Weight::from_parts(95u64 * WEIGHT_REF_TIME_PER_MICROS, 0))
+		// report offence
+		.saturating_add(Weight::from_parts(110u64 * WEIGHT_REF_TIME_PER_MICROS, 0))
+		.saturating_add(T::DbWeight::get().writes(3))
+}
+
+/// Methods for the `ValidateUnsigned` implementation:
+/// It restricts calls to `report_equivocation_unsigned` to local calls (i.e. extrinsics generated
+/// on this node) or that already in a block. This guarantees that only block authors can include
+/// unsigned equivocation reports.
+impl<T: Config> Pallet<T> {
+	pub fn validate_unsigned(source: TransactionSource, call: &Call<T>) -> TransactionValidity {
+		if let Call::report_equivocation_unsigned { equivocation_proof, key_owner_proof } = call {
+			// discard equivocation report not coming from the local node
+			match source {
+				TransactionSource::Local | TransactionSource::InBlock => { /* allowed */ },
+				_ => {
+					log::warn!(
+						target: LOG_TARGET,
+						"rejecting unsigned report equivocation transaction because it is not local/in-block.",
+					);
+					return InvalidTransaction::Call.into();
+				},
+			}
+
+			// Check report validity
+			let evidence = (*equivocation_proof.clone(), key_owner_proof.clone());
+			T::EquivocationReportSystem::check_evidence(evidence)?;
+
+			let longevity =
+				<T::EquivocationReportSystem as OffenceReportSystem<_, _>>::Longevity::get();
+
+			ValidTransaction::with_tag_prefix("SpinEquivocation")
+				// We assign the maximum priority for any equivocation report.
+				.priority(TransactionPriority::MAX)
+				// Only one equivocation report for the same offender at the same slot.
+				.and_provides((equivocation_proof.offender.clone(), *equivocation_proof.slot))
+				.longevity(longevity)
+				// We don't propagate this. This can never be included on a remote node.
+				.propagate(false)
+				.build()
+		} else {
+			InvalidTransaction::Call.into()
+		}
+	}
+
+	pub fn pre_dispatch(call: &Call<T>) -> Result<(), TransactionValidityError> {
+		if let Call::report_equivocation_unsigned { equivocation_proof, key_owner_proof } = call {
+			let evidence = (*equivocation_proof.clone(), key_owner_proof.clone());
+			T::EquivocationReportSystem::check_evidence(evidence)
+		} else {
+			Err(InvalidTransaction::Call.into())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Check `proof` like [`qfp_consensus_spin::check_equivocation_proof`].
+	///
+	/// When the leader may build several blocks per slot, headers it built on
+	/// top of each other are no equivocation. The leader builds the blocks of
+	/// its slot at increasing heights, so only two headers of the slot at the
+	/// same height prove that it built next to its own block, whether or not
+	/// its blocks were orphaned since.
+	pub fn check_equivocation_proof(
+		proof: SpinEquivocationProof<HeaderFor<T>, T::AuthorityId>,
+	) -> bool {
+		if T::AllowMultipleBlocksPerSlot::get() &&
+			proof.first_header.number() != proof.second_header.number()
+		{
+			return false;
+		}
+
+		qfp_consensus_spin::check_equivocation_proof(proof)
+	}
+}
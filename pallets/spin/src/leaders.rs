@@ -0,0 +1,131 @@
+// Copyright (C) QF Network, 2025.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Selection of the order in which SPIN authorities lead tenures.
+//!
+//! At the start of every session the configured [`LeaderSelection`] decides
+//! in which order the validators queued for the next session lead the tenures
+//! of `SessionLength` slots. The order repeats from the start of that session
+//! until the session after it starts.
+
+use alloc::{vec, vec::Vec};
+use codec::{Decode, Encode};
+use frame_support::traits::Randomness;
+use frame_system::pallet_prelude::BlockNumberFor;
+use qfp_consensus_spin::AuthorityIndex;
+use sp_runtime::{
+	traits::{BlakeTwo256, Convert, Hash, TrailingZeroInput},
+	Perbill,
+};
+use sp_staking::SessionIndex;
+
+/// Subject of the randomness the leader order of a session is shuffled with.
+const LEADER_ORDER_SUBJECT: &[u8] = b"spin_leader_order";
+
+/// A strategy to select the order in which authorities lead tenures.
+pub trait LeaderSelection<AccountId> {
+	/// Return the order in which `validators` lead the tenures of the session
+	/// `session_index`, as indices into `validators`.
+	///
+	/// An authority may lead more than one tenure of a rotation, but a
+	/// rotation should not have more than `max_tenures` tenures. Indices out of
+	/// the bounds of `validators` are ignored.
+	fn leader_order(
+		session_index: SessionIndex,
+		validators: &[AccountId],
+		max_tenures: u32,
+	) -> Vec<AuthorityIndex>;
+}
+
+/// Every authority leads one tenure in turn, in index order.
+///
+/// This is the order SPIN always used.
+pub struct RoundRobin;
+
+impl<AccountId> LeaderSelection<AccountId> for RoundRobin {
+	fn leader_order(_: SessionIndex, validators: &[AccountId], _: u32) -> Vec<AuthorityIndex> {
+		(0..validators.len() as AuthorityIndex).collect()
+	}
+}
+
+/// Every authority leads a number of tenures proportional to its stake, as
+/// given by `S`.
+///
+/// Every authority leads at least one tenure, the remaining `max_tenures` are
+/// shared in proportion to stake. The tenures of an authority are spread over
+/// the rotation rather than led back to back. If there is no stake at all, or
+/// fewer than one tenure per authority, the authorities lead in index order.
+pub struct StakeWeighted<S>(core::marker::PhantomData<S>);
+
+impl<AccountId, S: Convert<AccountId, u128>> LeaderSelection<AccountId> for StakeWeighted<S>
+where
+	AccountId: Clone,
+{
+	fn leader_order(
+		session_index: SessionIndex,
+		validators: &[AccountId],
+		max_tenures: u32,
+	) -> Vec<AuthorityIndex> {
+		let extra = max_tenures.saturating_sub(validators.len() as u32);
+		let stakes = validators.iter().map(|who| S::convert(who.clone())).collect::<Vec<_>>();
+		let total = stakes.iter().fold(0u128, |total, stake| total.saturating_add(*stake));
+		if total == 0 || max_tenures < validators.len() as u32 {
+			return RoundRobin::leader_order(session_index, validators, max_tenures);
+		}
+
+		let tenures = stakes
+			.iter()
+			.map(|stake| 1 + Perbill::from_rational(*stake, total).mul_floor(extra) as i64)
+			.collect::<Vec<_>>();
+		let rotation: i64 = tenures.iter().sum();
+
+		// smooth weighted round-robin: every step credits each authority with its
+		// tenures and lets the most credited one lead, which then pays a rotation.
+		let mut credit = vec![0i64; tenures.len()];
+		let mut order = Vec::with_capacity(rotation as usize);
+		for _ in 0..rotation {
+			credit.iter_mut().zip(&tenures).for_each(|(credit, tenures)| *credit += tenures);
+			let leader = (0..credit.len())
+				.rev()
+				.max_by_key(|index| credit[*index])
+				.expect("there is at least one validator with stake; qed");
+			credit[leader] -= rotation;
+			order.push(leader as AuthorityIndex);
+		}
+
+		order
+	}
+}
+
+/// Every authority leads one tenure in turn, in an order shuffled with the
+/// randomness `R` at the start of every session.
+///
+/// The order of a session is only known once the session before it started,
+/// so a future leader cannot be targeted much in advance. How unpredictable it is
+/// depends on `R`, e.g. the VRF based randomness of BABE.
+pub struct RandomShuffle<T, R>(core::marker::PhantomData<(T, R)>);
+
+impl<T, R> LeaderSelection<T::AccountId> for RandomShuffle<T, R>
+where
+	T: frame_system::Config,
+	R: Randomness<T::Hash, BlockNumberFor<T>>,
+{
+	fn leader_order(
+		session_index: SessionIndex,
+		validators: &[T::AccountId],
+		max_tenures: u32,
+	) -> Vec<AuthorityIndex> {
+		let (seed, _) = R::random(&(LEADER_ORDER_SUBJECT, session_index).encode());
+		let mut order = RoundRobin::leader_order(session_index, validators, max_tenures);
+
+		// Fisher-Yates shuffle drawing from the hash of the seed and the position.
+		for i in (1..order.len()).rev() {
+			let entropy = BlakeTwo256::hash_of(&(&seed, i as u32));
+			let draw = u64::decode(&mut TrailingZeroInput::new(entropy.as_ref()))
+				.expect("input is padded with zeroes; qed");
+			order.swap(i, (draw % (i as u64 + 1)) as usize);
+		}
+
+		order
+	}
+}
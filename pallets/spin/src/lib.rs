@@ -6,32 +6,60 @@
 
 extern crate alloc;
 
-use alloc::vec::Vec;
+use alloc::{boxed::Box, vec::Vec};
 use codec::{Decode, Encode, MaxEncodedLen};
 use frame_support::{
 	pallet_prelude::*,
-	traits::{DisabledValidators, FindAuthor, Get, OnTimestampSet, OneSessionHandler},
+	traits::{
+		DisabledValidators, EstimateNextSessionRotation, FindAuthor, Get, OnTimestampSet,
+		OneSessionHandler, ValidatorSet, ValidatorSetWithIdentification,
+	},
 	BoundedSlice, BoundedVec, ConsensusEngineId, Parameter,
 };
 use frame_system::pallet_prelude::*;
 use log;
 use qfp_consensus_spin::{
-	AuthorityIndex, ConsensusLog, SessionLength as SessionLengthT, Slot, SpinAuxData,
-	SPIN_ENGINE_ID,
+	AuthorityIndex, AuthorityLiveness, ConsensusLog, PendingChange as PendingChangeT, PreDigest,
+	ScheduledChange, SessionLength as SessionLengthT, Slot, SlotDuration, SlotSchedule,
+	SpinAuxData, SpinEquivocationProof, SPIN_ENGINE_ID,
 };
 use sp_runtime::{
 	generic::DigestItem,
-	traits::{IsMember, Member, SaturatedConversion, Saturating, Zero},
+	traits::{Bounded, IsMember, Member, One, SaturatedConversion, Saturating, Zero},
 	RuntimeAppPublic,
 };
+use sp_session::{GetSessionNumber, GetValidatorCount};
+use sp_staking::{
+	offence::{OffenceReportSystem, ReportOffence},
+	SessionIndex,
+};
 
+mod equivocation;
+mod leaders;
+mod liveness;
 mod mock;
 mod tests;
 
+pub use equivocation::{EquivocationOffence, EquivocationReportSystem};
+pub use leaders::{LeaderSelection, RandomShuffle, RoundRobin, StakeWeighted};
+pub use liveness::{IdentificationTuple, UnresponsivenessOffence, ValidatorId};
 pub use pallet::*;
 
 const LOG_TARGET: &str = "runtime::spin";
 
+/// A stored pending change of the authority set.
+#[derive(Encode, Decode, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[scale_info(skip_type_params(MaxAuthorities, MaxTenures))]
+pub struct StoredPendingChange<AuthorityId, N, MaxAuthorities, MaxTenures> {
+	/// The authorities that take over.
+	pub next_authorities: BoundedVec<AuthorityId, MaxAuthorities>,
+	/// The order in which the next authorities lead tenures.
+	pub leader_order: BoundedVec<AuthorityIndex, MaxTenures>,
+	/// The number of the first block authored by the next authorities.
+	pub effective_at: N,
+}
+
 /// A slot duration provider which infers the slot duration from the
 /// [`pallet_timestamp::Config::MinimumPeriod`] by multiplying it by two, to
 /// ensure that authors have the majority of their slot to author within.
@@ -62,8 +90,8 @@ pub mod pallet {
 		type MaxAuthorities: Get<u32>;
 
 		/// A way to check whether a given validator is disabled and should not
-		/// be authoring blocks. Blocks authored by a disabled validator will
-		/// lead to a panic as part of this module's initialization.
+		/// be authoring blocks. The tenure of a disabled validator is led by
+		/// the next authority that is not disabled.
 		type DisabledValidators: DisabledValidators;
 
 		/// Whether to allow block authors to create multiple blocks per slot.
@@ -82,18 +110,70 @@ pub mod pallet {
 		/// on the number of blocks authors can create using the same slot.
 		type AllowMultipleBlocksPerSlot: Get<bool>;
 
-		/// The slot duration SPIN should run with, expressed in milliseconds.
-		/// The effective value of this type should not change while the chain
-		/// is running.
+		/// The slot duration SPIN should run with since genesis, expressed in
+		/// milliseconds. The effective value of this type should not change
+		/// while the chain is running, use `set_slot_duration` instead.
 		///
 		/// For backwards compatibility either use [`MinimumPeriodTimesTwo`] or
 		/// a const.
 		#[pallet::constant]
 		type SlotDuration: Get<<Self as pallet_timestamp::Config>::Moment>;
 
+		/// Minimum number of slots between scheduling a change of the slot
+		/// duration and the slot it takes effect at, so that every node learns
+		/// of the change in time.
+		#[pallet::constant]
+		type SlotDurationChangeDelay: Get<u64>;
+
 		/// Default session length in blocks.
 		#[pallet::constant]
 		type DefaultSessionLength: Get<SessionLengthT<BlockNumberFor<Self>>>;
+
+		/// Estimates the block the next session starts at, from which the
+		/// authorities scheduled at the start of the current session take over.
+		type NextSessionRotation: EstimateNextSessionRotation<BlockNumberFor<Self>>;
+
+		/// The strategy that selects the order in which the authorities lead
+		/// tenures, a session ahead.
+		type LeaderSelection: LeaderSelection<Self::AccountId>;
+
+		/// The maximum number of tenures in one rotation of the leader order.
+		///
+		/// Must be at least `MaxAuthorities`.
+		#[pallet::constant]
+		type MaxLeaderTenures: Get<u32>;
+
+		/// Number of empty slots after which the secondary author of a slot may
+		/// author it in place of the leader.
+		///
+		/// Zero disables secondary authoring.
+		#[pallet::constant]
+		type FallbackAfterSlots: Get<u32>;
+
+		/// The proof of key ownership, used for validating equivocation reports.
+		/// The proof must include the session index and validator count of the
+		/// session at which the equivocation occurred.
+		type KeyOwnerProof: Parameter + GetSessionNumber + GetValidatorCount;
+
+		/// The equivocation handling subsystem, defines methods to check/report an
+		/// offence and for submitting a transaction to report an equivocation
+		/// (from an offchain context).
+		type EquivocationReportSystem: OffenceReportSystem<
+			Option<Self::AccountId>,
+			(SpinEquivocationProof<HeaderFor<Self>, Self::AuthorityId>, Self::KeyOwnerProof),
+		>;
+
+		/// The validators of the current session, in the same order as the
+		/// authorities.
+		type ValidatorSet: ValidatorSetWithIdentification<Self::AccountId>;
+
+		/// A type that gives us the ability to submit unresponsiveness offence
+		/// reports for authorities that missed all of their slots in a session.
+		type ReportUnresponsiveness: ReportOffence<
+			Self::AccountId,
+			IdentificationTuple<Self>,
+			UnresponsivenessOffence<IdentificationTuple<Self>>,
+		>;
 	}
 
 	#[pallet::pallet]
@@ -101,9 +181,13 @@ pub mod pallet {
 
 	#[pallet::hooks]
 	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
-		fn on_initialize(_: BlockNumberFor<T>) -> Weight {
-			if let Some(new_slot) = Self::current_slot_from_digests() {
+		fn on_initialize(now: BlockNumberFor<T>) -> Weight {
+			let change_weight = Self::enact_due_change(now);
+
+			let weight = if let Some(pre_digest) = Self::pre_digest_from_digests() {
+				let new_slot = pre_digest.slot();
 				let current_slot = CurrentSlot::<T>::get();
+				let schedule_weight = Self::enact_due_slot_schedule(new_slot);
 
 				if T::AllowMultipleBlocksPerSlot::get() {
 					assert!(current_slot <= new_slot, "Slot must not decrease");
@@ -111,25 +195,36 @@ pub mod pallet {
 					assert!(current_slot < new_slot, "Slot must increase");
 				}
 
-				CurrentSlot::<T>::put(new_slot);
+				// the verifier checks that a secondary author may author the slot
+				// against the state of the parent and the change it schedules. At
+				// a session boundary, the session pallet rotated the authorities
+				// already, so that the runtime cannot check the same.
 
-				if let Some(n_authorities) = <Authorities<T>>::decode_len() {
-					let authority_index = *new_slot % n_authorities as u64;
-					if T::DisabledValidators::is_disabled(authority_index as u32) {
-						panic!(
-							"Validator with index {:?} is disabled and should not be attempting to author blocks.",
-							authority_index,
-						);
-					}
-				}
+				CurrentSlot::<T>::put(new_slot);
+				SecondaryAuthored::<T>::put(!pre_digest.is_primary());
 
-				// TODO [#3398] Generate offence report for all authorities that skipped their
-				// slots.
+				let liveness_weight = if new_slot > current_slot {
+					Self::note_liveness(current_slot, new_slot, pre_digest.is_primary())
+				} else {
+					Weight::zero()
+				};
 
-				T::DbWeight::get().reads_writes(2, 1)
+				T::DbWeight::get()
+					.reads_writes(2, 2)
+					.saturating_add(schedule_weight)
+					.saturating_add(liveness_weight)
 			} else {
 				T::DbWeight::get().reads(1)
-			}
+			};
+
+			weight.saturating_add(change_weight)
+		}
+
+		fn integrity_test() {
+			assert!(
+				T::MaxLeaderTenures::get() >= T::MaxAuthorities::get(),
+				"every authority must be able to lead a tenure",
+			);
 		}
 
 		#[cfg(feature = "try-runtime")]
@@ -143,12 +238,49 @@ pub mod pallet {
 	pub type Authorities<T: Config> =
 		StorageValue<_, BoundedVec<T::AuthorityId, T::MaxAuthorities>, ValueQuery>;
 
+	/// The change of the authority set scheduled for the next session.
+	#[pallet::storage]
+	pub type PendingChange<T: Config> = StorageValue<
+		_,
+		StoredPendingChange<
+			T::AuthorityId,
+			BlockNumberFor<T>,
+			T::MaxAuthorities,
+			T::MaxLeaderTenures,
+		>,
+	>;
+
 	/// The current slot of this block.
 	///
 	/// This will be set in `on_initialize`.
 	#[pallet::storage]
 	pub type CurrentSlot<T: Config> = StorageValue<_, Slot, ValueQuery>;
 
+	/// The slot schedule in effect.
+	///
+	/// Unset until the slot duration is changed for the first time, in which
+	/// case the chain runs at `SlotDuration` since genesis.
+	#[pallet::storage]
+	pub type ActiveSlotSchedule<T: Config> = StorageValue<_, SlotSchedule>;
+
+	/// The slot schedule that takes over at its start slot.
+	#[pallet::storage]
+	pub type PendingSlotSchedule<T: Config> = StorageValue<_, SlotSchedule>;
+
+	/// The order in which the authorities lead tenures in the current session,
+	/// as authority indices.
+	///
+	/// Empty if no order was selected yet, in which case the authorities lead
+	/// in index order.
+	#[pallet::storage]
+	pub type LeaderOrder<T: Config> =
+		StorageValue<_, BoundedVec<AuthorityIndex, T::MaxLeaderTenures>, ValueQuery>;
+
+	/// Whether the block at `CurrentSlot` was authored by the secondary author
+	/// of the slot.
+	#[pallet::storage]
+	pub type SecondaryAuthored<T: Config> = StorageValue<_, bool, ValueQuery>;
+
 	/// Session length in blocks
 	///
 	/// Selected leader produces blocks for `SessionLength` blocks.
@@ -156,6 +288,18 @@ pub mod pallet {
 	pub type SessionLength<T: Config> =
 		StorageValue<_, SessionLengthT<BlockNumberFor<T>>, ValueQuery, T::DefaultSessionLength>;
 
+	/// Slot of the first block of the current session.
+	///
+	/// Equivocations in earlier slots can no longer be reported.
+	#[pallet::storage]
+	pub type SessionStartSlot<T: Config> = StorageValue<_, Slot, ValueQuery>;
+
+	/// Slots led and blocks produced by each authority in the current session,
+	/// keyed by authority index.
+	#[pallet::storage]
+	pub type SessionLiveness<T: Config> =
+		StorageMap<_, Twox64Concat, AuthorityIndex, AuthorityLiveness, ValueQuery>;
+
 	#[pallet::genesis_config]
 	#[derive(frame_support::DefaultNoBound)]
 	pub struct GenesisConfig<T: Config> {
@@ -173,6 +317,18 @@ pub mod pallet {
 	pub enum Error<T> {
 		/// Zero session length.
 		SessionLengthZero,
+		/// An equivocation proof provided as part of an equivocation report is invalid.
+		InvalidEquivocationProof,
+		/// A key ownership proof provided as part of an equivocation report is invalid.
+		InvalidKeyOwnershipProof,
+		/// A given equivocation report is valid but already previously reported.
+		DuplicateOffenceReport,
+		/// The slot duration is shorter than twice the minimum period between
+		/// timestamps.
+		SlotDurationTooShort,
+		/// The slot duration change takes effect before `SlotDurationChangeDelay`
+		/// slots passed.
+		SlotDurationChangeTooSoon,
 	}
 
 	#[pallet::event]
@@ -180,6 +336,10 @@ pub mod pallet {
 	pub enum Event<T: Config> {
 		/// New session length set.
 		NewSessionLength(SessionLengthT<BlockNumberFor<T>>),
+		/// A change of the slot duration was scheduled.
+		SlotDurationScheduled { slot_duration: T::Moment, activation_slot: Slot },
+		/// The slot duration changed.
+		SlotDurationChanged { slot_duration: T::Moment },
 	}
 
 	#[pallet::call]
@@ -204,10 +364,177 @@ pub mod pallet {
 
 			Ok(())
 		}
+
+		/// Report authority equivocation/misbehavior. This method will verify
+		/// the equivocation proof and validate the given key ownership proof
+		/// against the extracted offender. If both are valid, the offence will
+		/// be reported.
+		#[pallet::call_index(1)]
+		#[pallet::weight(equivocation::report_equivocation_weight::<T>(
+			key_owner_proof.validator_count(),
+		))]
+		pub fn report_equivocation(
+			origin: OriginFor<T>,
+			equivocation_proof: Box<SpinEquivocationProof<HeaderFor<T>, T::AuthorityId>>,
+			key_owner_proof: T::KeyOwnerProof,
+		) -> DispatchResultWithPostInfo {
+			let reporter = ensure_signed(origin)?;
+			T::EquivocationReportSystem::process_evidence(
+				Some(reporter),
+				(*equivocation_proof, key_owner_proof),
+			)?;
+			// Waive the fee since the report is valid and beneficial
+			Ok(Pays::No.into())
+		}
+
+		/// Report authority equivocation/misbehavior. This method will verify
+		/// the equivocation proof and validate the given key ownership proof
+		/// against the extracted offender. If both are valid, the offence will
+		/// be reported.
+		/// This extrinsic must be called unsigned and it is expected that only
+		/// block authors will call it (validated in `ValidateUnsigned`), as such
+		/// if the block author is defined it will be defined as the equivocation
+		/// reporter.
+		#[pallet::call_index(2)]
+		#[pallet::weight(equivocation::report_equivocation_weight::<T>(
+			key_owner_proof.validator_count(),
+		))]
+		pub fn report_equivocation_unsigned(
+			origin: OriginFor<T>,
+			equivocation_proof: Box<SpinEquivocationProof<HeaderFor<T>, T::AuthorityId>>,
+			key_owner_proof: T::KeyOwnerProof,
+		) -> DispatchResultWithPostInfo {
+			ensure_none(origin)?;
+			T::EquivocationReportSystem::process_evidence(
+				None,
+				(*equivocation_proof, key_owner_proof),
+			)?;
+			Ok(Pays::No.into())
+		}
+
+		/// Schedule a change of the slot duration.
+		///
+		/// Slots last `slot_duration` milliseconds from `activation_slot` on and
+		/// keep being numbered from it. The activation slot must be at least
+		/// `SlotDurationChangeDelay` slots after the current one. A change that
+		/// did not take effect yet is replaced.
+		///
+		/// Origin must be root.
+		#[pallet::call_index(3)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(2, 1))]
+		pub fn set_slot_duration(
+			origin: OriginFor<T>,
+			slot_duration: T::Moment,
+			activation_slot: Slot,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+
+			// Blocks of consecutive slots must be able to advance the timestamp.
+			let min_duration =
+				<T as pallet_timestamp::Config>::MinimumPeriod::get().saturating_mul(2u32.into());
+			ensure!(
+				!slot_duration.is_zero() && slot_duration >= min_duration,
+				Error::<T>::SlotDurationTooShort,
+			);
+
+			let earliest =
+				CurrentSlot::<T>::get().saturating_add(T::SlotDurationChangeDelay::get());
+			ensure!(activation_slot >= earliest, Error::<T>::SlotDurationChangeTooSoon);
+
+			let schedule =
+				Self::slot_schedule().switch_at(activation_slot, slot_duration.saturated_into());
+			PendingSlotSchedule::<T>::put(schedule);
+
+			Self::deposit_event(Event::SlotDurationScheduled { slot_duration, activation_slot });
+
+			Ok(())
+		}
+	}
+
+	#[pallet::validate_unsigned]
+	impl<T: Config> ValidateUnsigned for Pallet<T> {
+		type Call = Call<T>;
+
+		fn validate_unsigned(source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+			Self::validate_unsigned(source, call)
+		}
+
+		fn pre_dispatch(call: &Self::Call) -> Result<(), TransactionValidityError> {
+			Self::pre_dispatch(call)
+		}
 	}
 }
 
 impl<T: Config> Pallet<T> {
+	/// Schedule a change of the authorities.
+	///
+	/// The change is enacted by the block `effective_at`, or at the next
+	/// session boundary if that comes first. The next authorities lead
+	/// tenures in `leader_order`. A scheduled change that was not enacted yet
+	/// is replaced.
+	///
+	/// A SPIN consensus log will be appended to block's log if the next
+	/// authorities differ from the current ones. This is a no-op if
+	/// `next_authorities` is empty.
+	pub fn schedule_change(
+		next_authorities: BoundedVec<T::AuthorityId, T::MaxAuthorities>,
+		leader_order: BoundedVec<AuthorityIndex, T::MaxLeaderTenures>,
+		effective_at: BlockNumberFor<T>,
+	) {
+		if next_authorities.is_empty() {
+			log::warn!(target: LOG_TARGET, "Ignoring empty scheduled authority change.");
+
+			return;
+		}
+
+		if next_authorities != Authorities::<T>::get() {
+			let now = frame_system::Pallet::<T>::block_number();
+			let change = ScheduledChange {
+				next_authorities: next_authorities.to_vec(),
+				delay: effective_at.saturating_sub(now),
+			};
+			let log = DigestItem::Consensus(
+				SPIN_ENGINE_ID,
+				ConsensusLog::ScheduledChange(change).encode(),
+			);
+			<frame_system::Pallet<T>>::deposit_log(log);
+		}
+
+		PendingChange::<T>::put(StoredPendingChange {
+			next_authorities,
+			leader_order,
+			effective_at,
+		});
+	}
+
+	/// Enact the scheduled change of the authorities if it is effective at
+	/// block `now`.
+	fn enact_due_change(now: BlockNumberFor<T>) -> Weight {
+		match PendingChange::<T>::get() {
+			Some(change) if change.effective_at <= now => {
+				PendingChange::<T>::kill();
+				Self::enact_change(change);
+				T::DbWeight::get().reads_writes(2, 4)
+			},
+			_ => T::DbWeight::get().reads(1),
+		}
+	}
+
+	/// Enact a scheduled change of the authorities.
+	fn enact_change(
+		change: StoredPendingChange<
+			T::AuthorityId,
+			BlockNumberFor<T>,
+			T::MaxAuthorities,
+			T::MaxLeaderTenures,
+		>,
+	) {
+		if change.next_authorities != Authorities::<T>::get() {
+			Self::change_authorities(change.next_authorities);
+		}
+		LeaderOrder::<T>::put(change.leader_order);
+	}
+
 	/// Change authorities.
 	///
 	/// The storage will be applied immediately.
@@ -225,7 +552,7 @@ impl<T: Config> Pallet<T> {
 
 		let log = DigestItem::Consensus(
 			SPIN_ENGINE_ID,
-			ConsensusLog::AuthoritiesChange(new.into_inner()).encode(),
+			ConsensusLog::<_, BlockNumberFor<T>>::AuthoritiesChange(new.into_inner()).encode(),
 		);
 		<frame_system::Pallet<T>>::deposit_log(log);
 	}
@@ -251,11 +578,16 @@ impl<T: Config> Pallet<T> {
 
 	/// Get the current slot from the pre-runtime digests.
 	fn current_slot_from_digests() -> Option<Slot> {
+		Self::pre_digest_from_digests().map(|pre_digest| pre_digest.slot())
+	}
+
+	/// Get the SPIN pre-digest from the pre-runtime digests.
+	fn pre_digest_from_digests() -> Option<PreDigest> {
 		let digest = frame_system::Pallet::<T>::digest();
 		let pre_runtime_digests = digest.logs.iter().filter_map(|d| d.as_pre_runtime());
-		for (id, mut data) in pre_runtime_digests {
-			if id == SPIN_ENGINE_ID {
-				return Slot::decode(&mut data).ok();
+		for (id, data) in pre_runtime_digests {
+			if let Some(pre_digest) = PreDigest::from_pre_runtime(id, data) {
+				return Some(pre_digest);
 			}
 		}
 
@@ -265,14 +597,169 @@ impl<T: Config> Pallet<T> {
 	/// Determine the SPIN slot-duration based on the Timestamp module
 	/// configuration.
 	pub fn slot_duration() -> T::Moment {
-		T::SlotDuration::get()
+		Self::slot_schedule().slot_duration.saturated_into()
+	}
+
+	/// The slot schedule in effect.
+	pub fn slot_schedule() -> SlotSchedule {
+		ActiveSlotSchedule::<T>::get().unwrap_or_else(|| {
+			SlotSchedule::genesis(SlotDuration::from_millis(
+				T::SlotDuration::get().saturated_into(),
+			))
+		})
+	}
+
+	/// The slot schedule that takes over at its start slot, if any.
+	pub fn pending_slot_schedule() -> Option<SlotSchedule> {
+		PendingSlotSchedule::<T>::get()
+	}
+
+	/// Put the pending slot schedule in effect if it starts by `slot`.
+	fn enact_due_slot_schedule(slot: Slot) -> Weight {
+		match PendingSlotSchedule::<T>::get() {
+			Some(schedule) if schedule.start_slot <= slot => {
+				PendingSlotSchedule::<T>::kill();
+				ActiveSlotSchedule::<T>::put(schedule);
+				Self::deposit_event(Event::SlotDurationChanged {
+					slot_duration: schedule.slot_duration.saturated_into(),
+				});
+				T::DbWeight::get().reads_writes(1, 3)
+			},
+			_ => T::DbWeight::get().reads(1),
+		}
+	}
+
+	/// Number of empty slots after which the secondary author of a slot may
+	/// author it.
+	pub fn fallback_after() -> u32 {
+		T::FallbackAfterSlots::get()
+	}
+
+	/// Whether the slot leader may build several blocks per slot.
+	pub fn allow_multiple_blocks_per_slot() -> bool {
+		T::AllowMultipleBlocksPerSlot::get()
 	}
 
 	/// Auxiliary data for SPIN.
 	pub fn aux_data() -> SpinAuxData<T::AuthorityId, BlockNumberFor<T>> {
-		let authorities = Authorities::<T>::get();
-		let session_length = SessionLength::<T>::get();
-		(authorities.into_inner(), session_length)
+		let authorities = Authorities::<T>::get().into_inner();
+		let next_authorities = PendingChange::<T>::get()
+			.map(|change| change.next_authorities.into_inner())
+			.unwrap_or_else(|| authorities.clone());
+
+		SpinAuxData {
+			authorities,
+			session_length: SessionLength::<T>::get(),
+			session_index: T::ValidatorSet::session_index(),
+			leader_order: Self::leader_order(),
+			disabled: Self::disabled_authorities(),
+			next_authorities,
+		}
+	}
+
+	/// The change of the authority set that is scheduled but not enacted yet.
+	pub fn pending_change() -> Option<PendingChangeT<T::AuthorityId, BlockNumberFor<T>>> {
+		PendingChange::<T>::get().map(|change| PendingChangeT {
+			next_authorities: change.next_authorities.into_inner(),
+			leader_order: change.leader_order.into_inner(),
+			effective_at: change.effective_at,
+		})
+	}
+
+	/// The order in which the authorities lead tenures in the current session.
+	///
+	/// Entries that do not refer to a current authority are skipped. If none
+	/// are left, the authorities lead in index order.
+	pub fn leader_order() -> Vec<AuthorityIndex> {
+		let authorities = Self::authorities_len() as AuthorityIndex;
+		let mut order = LeaderOrder::<T>::get().into_inner();
+		order.retain(|index| *index < authorities);
+		if order.is_empty() {
+			order = (0..authorities).collect();
+		}
+		order
+	}
+
+	/// Select the order in which `validators` lead tenures in the session
+	/// `session_index`.
+	fn select_leaders(
+		session_index: SessionIndex,
+		validators: &[T::AccountId],
+	) -> BoundedVec<AuthorityIndex, T::MaxLeaderTenures> {
+		let max_tenures = T::MaxLeaderTenures::get();
+		let mut order = T::LeaderSelection::leader_order(session_index, validators, max_tenures);
+		order.retain(|index| (*index as usize) < validators.len());
+
+		if order.len() > max_tenures as usize {
+			log::warn!(target: LOG_TARGET, "leader order longer than {}, truncating", max_tenures);
+		}
+		BoundedVec::truncate_from(order)
+	}
+
+	/// Indices of the authorities that are disabled in the current session.
+	pub fn disabled_authorities() -> Vec<AuthorityIndex> {
+		let authorities = Self::authorities_len() as AuthorityIndex;
+		let mut disabled = T::DisabledValidators::disabled_validators();
+		disabled.retain(|index| *index < authorities);
+		disabled.sort_unstable();
+		disabled
+	}
+
+	/// Index of the authority leading `slot`, skipping disabled authorities.
+	pub fn slot_leader(slot: Slot) -> Option<AuthorityIndex> {
+		qfp_consensus_spin::slot_leader(
+			slot,
+			SessionLength::<T>::get().saturated_into(),
+			&Self::leader_order(),
+			Self::authorities_len() as u32,
+			&Self::disabled_authorities(),
+		)
+	}
+
+	/// Index of the secondary author of `slot`.
+	pub fn secondary_slot_leader(slot: Slot) -> Option<AuthorityIndex> {
+		qfp_consensus_spin::secondary_slot_leader(
+			slot,
+			SessionLength::<T>::get().saturated_into(),
+			&Self::leader_order(),
+			Self::authorities_len() as u32,
+			&Self::disabled_authorities(),
+		)
+	}
+
+	/// Whether the secondary author may author `slot` on top of the block at
+	/// `CurrentSlot`.
+	///
+	/// Like [`Self::secondary_slot_leader`], this reflects the current state,
+	/// after any rotation of the authorities at the start of this block.
+	pub fn secondary_authoring_allowed(slot: Slot) -> bool {
+		qfp_consensus_spin::secondary_authoring_allowed(
+			CurrentSlot::<T>::get(),
+			!SecondaryAuthored::<T>::get(),
+			slot,
+			SessionLength::<T>::get().saturated_into(),
+			T::FallbackAfterSlots::get().into(),
+		)
+	}
+
+	/// Liveness of every authority in the current session.
+	pub fn session_liveness() -> Vec<(T::AuthorityId, AuthorityLiveness)> {
+		Authorities::<T>::get()
+			.into_iter()
+			.enumerate()
+			.map(|(index, id)| (id, SessionLiveness::<T>::get(index as AuthorityIndex)))
+			.collect()
+	}
+
+	/// Submits an extrinsic to report an equivocation. This method will create
+	/// an unsigned extrinsic with a call to `report_equivocation_unsigned` and
+	/// will push the transaction to the pool. Only useful in an offchain
+	/// context.
+	pub fn submit_unsigned_equivocation_report(
+		equivocation_proof: SpinEquivocationProof<HeaderFor<T>, T::AuthorityId>,
+		key_owner_proof: T::KeyOwnerProof,
+	) -> Option<()> {
+		T::EquivocationReportSystem::publish_evidence((equivocation_proof, key_owner_proof)).ok()
 	}
 
 	/// Ensure the correctness of the state of this pallet.
@@ -288,10 +775,17 @@ impl<T: Config> Pallet<T> {
 	/// must be less than the maximal slot number. Otherwise, it can be
 	/// arbitrary.
 	///
+	/// ## `PendingSlotSchedule`
+	///
+	/// A pending slot schedule must start after the active one, at the time
+	/// its start slot starts in the active one.
+	///
 	/// ## `Authorities`
 	///
 	/// * The authorities must be non-empty.
-	/// * The current authority cannot be disabled.
+	/// * The leader order can only refer to current authorities.
+	/// * The leader order of a pending change can only refer to its next authorities.
+	/// * The current slot leader cannot be disabled, unless every authority is.
 	/// * The number of authorities must be less than or equal to `T::MaxAuthorities`. This however,
 	///   is guarded by the type system.
 	#[cfg(any(test, feature = "try-runtime"))]
@@ -311,17 +805,43 @@ impl<T: Config> Pallet<T> {
 			);
 		}
 
+		// Check that slot numbers continue across a change of the slot duration.
+		if let Some(pending) = PendingSlotSchedule::<T>::get() {
+			let active = Self::slot_schedule();
+			frame_support::ensure!(
+				pending.start_slot > active.start_slot &&
+					pending.start_timestamp == active.slot_start(pending.start_slot),
+				"Pending slot schedule does not continue the active one.",
+			);
+		}
+
 		let authorities_len =
 			<Authorities<T>>::decode_len().ok_or("Failed to decode authorities length")?;
 
 		// Check that the authorities are non-empty.
 		frame_support::ensure!(!authorities_len.is_zero(), "Authorities must be non-empty.");
 
-		// Check that the current authority is not disabled.
-		let authority_index = *current_slot % authorities_len as u64;
+		// Check that the leader order only refers to current authorities.
 		frame_support::ensure!(
-			!T::DisabledValidators::is_disabled(authority_index as u32),
-			"Current validator is disabled and should not be attempting to author blocks.",
+			LeaderOrder::<T>::get().iter().all(|index| (*index as usize) < authorities_len),
+			"Leader order refers to an authority that does not exist.",
+		);
+
+		// Check that a pending change only refers to its next authorities.
+		if let Some(change) = PendingChange::<T>::get() {
+			let next_len = change.next_authorities.len();
+			frame_support::ensure!(
+				change.leader_order.iter().all(|index| (*index as usize) < next_len),
+				"Pending leader order refers to an authority that does not exist.",
+			);
+		}
+
+		// Check that the tenure of a disabled authority fell through to an enabled one.
+		let disabled = Self::disabled_authorities();
+		let leader = Self::slot_leader(current_slot).ok_or("Failed to determine slot leader")?;
+		frame_support::ensure!(
+			!disabled.contains(&leader) || disabled.len() == authorities_len,
+			"Current slot leader is disabled and should not be attempting to author blocks.",
 		);
 
 		Ok(())
@@ -339,36 +859,85 @@ impl<T: Config> OneSessionHandler<T::AccountId> for Pallet<T> {
 	where
 		I: Iterator<Item = (&'a T::AccountId, T::AuthorityId)>,
 	{
-		let authorities = validators.map(|(_, k)| k).collect::<Vec<_>>();
+		let (accounts, authorities): (Vec<_>, Vec<_>) =
+			validators.map(|(who, k)| (who.clone(), k)).unzip();
 		Self::initialize_authorities(&authorities);
+		LeaderOrder::<T>::put(Self::select_leaders(T::ValidatorSet::session_index(), &accounts));
 	}
 
-	fn on_new_session<'a, I: 'a>(changed: bool, validators: I, _queued_validators: I)
+	fn on_new_session<'a, I: 'a>(changed: bool, validators: I, queued_validators: I)
 	where
 		I: Iterator<Item = (&'a T::AccountId, T::AuthorityId)>,
 	{
-		// instant changes
-		if changed {
-			let next_authorities = validators.map(|(_, k)| k).collect::<Vec<_>>();
-			let last_authorities = Authorities::<T>::get();
-			if last_authorities != next_authorities {
-				if next_authorities.len() as u32 > T::MaxAuthorities::get() {
-					log::warn!(
-						target: LOG_TARGET,
-						"next authorities list larger than {}, truncating",
-						T::MaxAuthorities::get(),
-					);
-				}
-				let bounded = <BoundedVec<_, T::MaxAuthorities>>::truncate_from(next_authorities);
-				Self::change_authorities(bounded);
+		// the session rotates before this block's slot is noted in `on_initialize`.
+		let session_start =
+			Self::current_slot_from_digests().unwrap_or_else(|| CurrentSlot::<T>::get());
+		SessionStartSlot::<T>::put(session_start);
+
+		// authority indices may change with the new session.
+		let _ = SessionLiveness::<T>::clear(T::MaxAuthorities::get(), None);
+
+		let now = frame_system::Pallet::<T>::block_number();
+		let session_index = T::ValidatorSet::session_index();
+
+		// the authorities scheduled at the start of the last session take over,
+		// unless the rotation was estimated late and they already did.
+		if let Some(change) = PendingChange::<T>::take() {
+			if change.effective_at > now {
+				log::warn!(
+					target: LOG_TARGET,
+					"Enacting authority change scheduled for block {:?} at block {:?}",
+					change.effective_at,
+					now,
+				);
 			}
+			Self::enact_change(change);
+		}
+
+		// instant changes, for validators that were not scheduled
+		let (mut accounts, next_authorities): (Vec<_>, Vec<_>) =
+			validators.map(|(who, k)| (who.clone(), k)).unzip();
+		if changed && Authorities::<T>::get() != next_authorities {
+			if next_authorities.len() as u32 > T::MaxAuthorities::get() {
+				log::warn!(
+					target: LOG_TARGET,
+					"next authorities list larger than {}, truncating",
+					T::MaxAuthorities::get(),
+				);
+			}
+			let bounded = <BoundedVec<_, T::MaxAuthorities>>::truncate_from(next_authorities);
+			Self::change_authorities(bounded);
+
+			// the leaders of truncated authorities are never selected.
+			accounts.truncate(Self::authorities_len());
+			LeaderOrder::<T>::put(Self::select_leaders(session_index, &accounts));
 		}
+
+		// the validators queued for the next session take over when it starts.
+		let (mut queued_accounts, queued_authorities): (Vec<_>, Vec<_>) =
+			queued_validators.map(|(who, k)| (who.clone(), k)).unzip();
+		queued_accounts.truncate(T::MaxAuthorities::get() as usize);
+		let leader_order = Self::select_leaders(session_index.saturating_add(1), &queued_accounts);
+		let effective_at =
+			T::NextSessionRotation::estimate_next_session_rotation(now.saturating_add(One::one()))
+				.0
+				.unwrap_or_else(Bounded::max_value);
+		Self::schedule_change(
+			BoundedVec::truncate_from(queued_authorities),
+			leader_order,
+			effective_at,
+		);
+	}
+
+	fn on_before_session_ending() {
+		Self::report_unresponsive();
 	}
 
 	fn on_disabled(i: u32) {
 		let log = DigestItem::Consensus(
 			SPIN_ENGINE_ID,
-			ConsensusLog::<T::AuthorityId>::OnDisabled(i as AuthorityIndex).encode(),
+			ConsensusLog::<T::AuthorityId, BlockNumberFor<T>>::OnDisabled(i as AuthorityIndex)
+				.encode(),
 		);
 
 		<frame_system::Pallet<T>>::deposit_log(log);
@@ -380,11 +949,12 @@ impl<T: Config> FindAuthor<u32> for Pallet<T> {
 	where
 		I: 'a + IntoIterator<Item = (ConsensusEngineId, &'a [u8])>,
 	{
-		for (id, mut data) in digests.into_iter() {
-			if id == SPIN_ENGINE_ID {
-				let slot = Slot::decode(&mut data).ok()?;
-				let author_index = *slot % Self::authorities_len() as u64;
-				return Some(author_index as u32);
+		for (id, data) in digests.into_iter() {
+			if let Some(pre_digest) = PreDigest::from_pre_runtime(id, data) {
+				return match pre_digest {
+					PreDigest::Primary(slot) => Self::slot_leader(slot),
+					PreDigest::Secondary { authority_index, .. } => Some(authority_index),
+				};
 			}
 		}
 
@@ -426,8 +996,7 @@ impl<T: Config> OnTimestampSet<T::Moment> for Pallet<T> {
 		let slot_duration = Self::slot_duration();
 		assert!(!slot_duration.is_zero(), "SPIN slot duration cannot be zero.");
 
-		let timestamp_slot = moment / slot_duration;
-		let timestamp_slot = Slot::from(timestamp_slot.saturated_into::<u64>());
+		let timestamp_slot = Self::slot_schedule().slot_at(moment.saturated_into());
 
 		assert_eq!(
 			CurrentSlot::<T>::get(),
@@ -0,0 +1,221 @@
+// Copyright (C) QF Network, 2025.
+// Copyright (C) Parity Technologies (UK) Ltd., until 2025.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tracking of the slots SPIN leaders missed.
+//!
+//! Every block accounts the slots elapsed since its parent to the authorities
+//! that led them, using the same leader selection as the client. Authorities
+//! that did not produce a single block in the slots they led during a session
+//! are reported as unresponsive when the session ends.
+
+use alloc::{vec, vec::Vec};
+use frame_support::{
+	traits::{Get, ValidatorSet, ValidatorSetWithIdentification},
+	weights::Weight,
+};
+use qfp_consensus_spin::{slot_leader, tenure_leader, AuthorityIndex, Slot};
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{Convert, SaturatedConversion, Zero},
+	Perbill, RuntimeDebug,
+};
+use sp_staking::{
+	offence::{Kind, Offence, ReportOffence},
+	SessionIndex,
+};
+
+use crate::{Config, Pallet, SessionLength, SessionLiveness, SessionStartSlot, LOG_TARGET};
+
+/// A type for representing the validator id in a session.
+pub type ValidatorId<T> = <<T as Config>::ValidatorSet as ValidatorSet<
+	<T as frame_system::Config>::AccountId,
+>>::ValidatorId;
+
+/// A tuple of (ValidatorId, Identification) where `Identification` is the full
+/// identification of `ValidatorId`.
+pub type IdentificationTuple<T> = (
+	ValidatorId<T>,
+	<<T as Config>::ValidatorSet as ValidatorSetWithIdentification<
+		<T as frame_system::Config>::AccountId,
+	>>::Identification,
+);
+
+/// An offence that is filed if a validator did not produce any block in the
+/// slots it led during a session.
+#[derive(RuntimeDebug, TypeInfo, Clone, PartialEq, Eq)]
+pub struct UnresponsivenessOffence<Offender> {
+	/// The current session index in which we report the unresponsive validators.
+	///
+	/// It acts as a time measure for unresponsiveness reports and effectively will always
+	/// point at the end of the session.
+	pub session_index: SessionIndex,
+	/// The size of the validator set in current session/era.
+	pub validator_set_count: u32,
+	/// Authorities that were unresponsive during the current session.
+	pub offenders: Vec<Offender>,
+}
+
+impl<Offender: Clone> Offence<Offender> for UnresponsivenessOffence<Offender> {
+	const ID: Kind = *b"spin:unresponsiv";
+	type TimeSlot = SessionIndex;
+
+	fn offenders(&self) -> Vec<Offender> {
+		self.offenders.clone()
+	}
+
+	fn session_index(&self) -> SessionIndex {
+		self.session_index
+	}
+
+	fn validator_set_count(&self) -> u32 {
+		self.validator_set_count
+	}
+
+	fn time_slot(&self) -> Self::TimeSlot {
+		self.session_index
+	}
+
+	// The formula is min((3 * (k - (n / 10 + 1))) / n, 1) * 0.07
+	// basically, 10% can be offline with no slash, but after that, it linearly climbs up to 7%
+	// when 13/30 are offline (around 5% when 1/3 are offline).
+	fn slash_fraction(&self, offenders: u32) -> Perbill {
+		if let Some(threshold) = offenders.checked_sub(self.validator_set_count / 10 + 1) {
+			let x = Perbill::from_rational(3 * threshold, self.validator_set_count);
+			x.saturating_mul(Perbill::from_percent(7))
+		} else {
+			Perbill::default()
+		}
+	}
+}
+
+/// Number of slots in `first..=last` led by each authority, given that the
+/// authorities in `order` lead `session_length` consecutive slots in turn.
+///
+/// Only authorities that led at least one slot are returned, by index.
+pub(crate) fn slots_per_leader(
+	first: u64,
+	last: u64,
+	session_length: u64,
+	order: &[AuthorityIndex],
+) -> Vec<(AuthorityIndex, u64)> {
+	let mut slots = vec![0u64; order.len()];
+	let rotation = session_length.saturating_mul(order.len() as u64);
+	let mut slot = first;
+
+	while slot <= last && !rotation.is_zero() {
+		let remaining = last - slot + 1;
+		let next = if slot % rotation == 0 && remaining >= rotation {
+			// whole rotations lead the same number of slots for every tenure.
+			let rotations = remaining / rotation;
+			slots.iter_mut().for_each(|s| *s = s.saturating_add(rotations * session_length));
+			slot.checked_add(rotations * rotation)
+		} else {
+			let tenure = slot / session_length;
+			let end = tenure
+				.saturating_add(1)
+				.saturating_mul(session_length)
+				.saturating_sub(1)
+				.min(last);
+			let position = (tenure % order.len() as u64) as usize;
+			slots[position] = slots[position].saturating_add(end - slot + 1);
+			end.checked_add(1)
+		};
+
+		match next {
+			Some(next) => slot = next,
+			None => break,
+		}
+	}
+
+	// an authority may lead more than one tenure of the rotation.
+	let mut led: Vec<(AuthorityIndex, u64)> = Vec::new();
+	for (leader, slots) in order.iter().zip(slots).filter(|(_, s)| !s.is_zero()) {
+		match led.binary_search_by_key(leader, |(index, _)| *index) {
+			Ok(i) => led[i].1 = led[i].1.saturating_add(slots),
+			Err(i) => led.insert(i, (*leader, slots)),
+		}
+	}
+
+	led
+}
+
+impl<T: Config> Pallet<T> {
+	/// Account the slots after `last_slot` up to `new_slot` to the authorities
+	/// that led them. Only `new_slot` has a block, which counts for its leader
+	/// if it was `primary`. All other slots were missed.
+	pub(crate) fn note_liveness(last_slot: Slot, new_slot: Slot, primary: bool) -> Weight {
+		let authorities = Self::authorities_len() as u64;
+		let session_length: u64 = SessionLength::<T>::get().saturated_into();
+		if authorities == 0 || session_length == 0 {
+			return T::DbWeight::get().reads(1);
+		}
+
+		// the first block of the chain has no slot before it and the slots missed
+		// before the first block of a session were led by the previous authorities.
+		let first = if last_slot.is_zero() {
+			*new_slot
+		} else {
+			(*last_slot + 1).max(*SessionStartSlot::<T>::get())
+		};
+
+		// tenures of disabled authorities are led by their stand-ins.
+		let disabled = Self::disabled_authorities();
+		let order = Self::leader_order();
+		let led = slots_per_leader(first, *new_slot, session_length, &order);
+		for (index, slots) in led.iter() {
+			let leader = tenure_leader(*index, authorities as u32, &disabled);
+			SessionLiveness::<T>::mutate(leader, |liveness| {
+				liveness.expected = liveness.expected.saturating_add(*slots as u32);
+			});
+		}
+
+		let leader = slot_leader(new_slot, session_length, &order, authorities as u32, &disabled);
+		if let Some(leader) = leader.filter(|_| primary) {
+			SessionLiveness::<T>::mutate(leader, |liveness| {
+				liveness.produced = liveness.produced.saturating_add(1);
+			});
+		}
+
+		let touched = led.len() as u64 + 1;
+		T::DbWeight::get().reads_writes(touched + 6, touched)
+	}
+
+	/// Report the authorities that missed every slot they led during the
+	/// ending session.
+	pub(crate) fn report_unresponsive() {
+		let session_index = T::ValidatorSet::session_index();
+		let validators = T::ValidatorSet::validators();
+		let validator_set_count = validators.len() as u32;
+
+		let offenders = validators
+			.into_iter()
+			.enumerate()
+			.filter(|(index, _)| {
+				SessionLiveness::<T>::get(*index as AuthorityIndex).is_unresponsive()
+			})
+			.filter_map(|(_, id)| {
+				<T::ValidatorSet as ValidatorSetWithIdentification<T::AccountId>>::IdentificationOf::convert(
+					id.clone(),
+				)
+				.map(|full_id| (id, full_id))
+			})
+			.collect::<Vec<IdentificationTuple<T>>>();
+
+		if offenders.is_empty() {
+			return;
+		}
+
+		log::info!(
+			target: LOG_TARGET,
+			"Reporting {} unresponsive authorities in session {}",
+			offenders.len(),
+			session_index,
+		);
+
+		let offence = UnresponsivenessOffence { session_index, validator_set_count, offenders };
+		if let Err(e) = T::ReportUnresponsiveness::report_offence(vec![], offence) {
+			log::warn!(target: LOG_TARGET, "Failed to report unresponsive authorities: {:?}", e);
+		}
+	}
+}
@@ -2,13 +2,26 @@
 
 #![cfg(test)]
 
-use crate as pallet_spin;
+use crate::{self as pallet_spin, LeaderSelection, RoundRobin, UnresponsivenessOffence};
 use frame_support::{
 	derive_impl, parameter_types,
-	traits::{ConstU32, ConstU64, DisabledValidators},
+	traits::{
+		ConstU32, ConstU64, DisabledValidators, EstimateNextSessionRotation, Randomness,
+		ValidatorSet, ValidatorSetWithIdentification,
+	},
+	weights::Weight,
 };
 use qfp_consensus_spin::{ed25519::AuthorityId, AuthorityIndex};
-use sp_runtime::{testing::UintAuthorityId, BuildStorage};
+use sp_core::H256;
+use sp_runtime::{
+	testing::UintAuthorityId,
+	traits::{BlakeTwo256, ConvertInto, Hash},
+	BuildStorage, Permill,
+};
+use sp_staking::{
+	offence::{OffenceError, ReportOffence},
+	SessionIndex,
+};
 
 type Block = frame_system::mocking::MockBlock<Test>;
 
@@ -62,6 +75,96 @@ impl DisabledValidators for MockDisabledValidators {
 	}
 }
 
+parameter_types! {
+	pub static Validators: Vec<u64> = Default::default();
+	pub static ReportedOffences: Vec<UnresponsivenessOffence<(u64, u64)>> = Default::default();
+}
+
+pub struct MockValidatorSet;
+
+impl ValidatorSet<u64> for MockValidatorSet {
+	type ValidatorId = u64;
+	type ValidatorIdOf = ConvertInto;
+
+	fn session_index() -> SessionIndex {
+		0
+	}
+
+	fn validators() -> Vec<u64> {
+		Validators::get()
+	}
+}
+
+impl ValidatorSetWithIdentification<u64> for MockValidatorSet {
+	type Identification = u64;
+	type IdentificationOf = ConvertInto;
+}
+
+pub struct MockOffenceHandler;
+
+impl ReportOffence<u64, (u64, u64), UnresponsivenessOffence<(u64, u64)>> for MockOffenceHandler {
+	fn report_offence(
+		_reporters: Vec<u64>,
+		offence: UnresponsivenessOffence<(u64, u64)>,
+	) -> Result<(), OffenceError> {
+		ReportedOffences::mutate(|offences| offences.push(offence));
+		Ok(())
+	}
+
+	fn is_known_offence(_offenders: &[(u64, u64)], _time_slot: &SessionIndex) -> bool {
+		false
+	}
+}
+
+parameter_types! {
+	pub static MockLeaderOrder: Option<Vec<AuthorityIndex>> = None;
+}
+
+/// Selects `MockLeaderOrder` if set, round-robin otherwise.
+pub struct MockLeaderSelection;
+
+impl LeaderSelection<u64> for MockLeaderSelection {
+	fn leader_order(
+		session_index: SessionIndex,
+		validators: &[u64],
+		max_tenures: u32,
+	) -> Vec<AuthorityIndex> {
+		MockLeaderOrder::get()
+			.unwrap_or_else(|| RoundRobin::leader_order(session_index, validators, max_tenures))
+	}
+}
+
+parameter_types! {
+	pub static SessionPeriod: u64 = 10;
+}
+
+/// Estimates sessions to rotate every `SessionPeriod` blocks.
+pub struct MockNextSessionRotation;
+
+impl EstimateNextSessionRotation<u64> for MockNextSessionRotation {
+	fn average_session_length() -> u64 {
+		SessionPeriod::get()
+	}
+
+	fn estimate_current_session_progress(now: u64) -> (Option<Permill>, Weight) {
+		let period = SessionPeriod::get();
+		(Some(Permill::from_rational(now % period, period)), Weight::zero())
+	}
+
+	fn estimate_next_session_rotation(now: u64) -> (Option<u64>, Weight) {
+		let period = SessionPeriod::get();
+		(Some(now.div_ceil(period) * period), Weight::zero())
+	}
+}
+
+pub struct TestRandomness;
+
+impl Randomness<H256, u64> for TestRandomness {
+	fn random(subject: &[u8]) -> (H256, u64) {
+		(BlakeTwo256::hash(subject), 0)
+	}
+}
+
 pub(super) const DEFAULT_SESSION_LENGTH: u64 = 4;
 impl pallet_spin::Config for Test {
 	type AuthorityId = AuthorityId;
@@ -69,10 +172,20 @@ impl pallet_spin::Config for Test {
 	type MaxAuthorities = ConstU32<10>;
 	type AllowMultipleBlocksPerSlot = AllowMultipleBlocksPerSlot;
 	type SlotDuration = ConstU64<SLOT_DURATION>;
+	type SlotDurationChangeDelay = ConstU64<4>;
 	type DefaultSessionLength = ConstU64<DEFAULT_SESSION_LENGTH>;
+	type NextSessionRotation = MockNextSessionRotation;
+	type LeaderSelection = MockLeaderSelection;
+	type MaxLeaderTenures = ConstU32<20>;
+	type FallbackAfterSlots = ConstU32<2>;
+	type KeyOwnerProof = sp_core::Void;
+	type EquivocationReportSystem = ();
+	type ValidatorSet = MockValidatorSet;
+	type ReportUnresponsiveness = MockOffenceHandler;
 }
 
 fn build_ext(authorities: Vec<u64>) -> sp_io::TestExternalities {
+	Validators::set(authorities.clone());
 	let mut storage = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
 	pallet_spin::GenesisConfig::<Test> {
 		authorities: authorities.into_iter().map(|a| UintAuthorityId(a).to_public_key()).collect(),
@@ -3,14 +3,100 @@
 #![cfg(test)]
 
 use super::pallet;
-use crate::mock::{
-	build_ext_and_execute_test, MockDisabledValidators, RuntimeOrigin, Spin, System, Test,
-	DEFAULT_SESSION_LENGTH,
+use crate::{
+	mock::{
+		build_ext_and_execute_test, MockDisabledValidators, MockLeaderOrder, ReportedOffences,
+		RuntimeOrigin, Spin, System, Test, TestRandomness, DEFAULT_SESSION_LENGTH,
+	},
+	Error, LeaderSelection, RandomShuffle, RoundRobin, StakeWeighted,
 };
 use codec::Encode;
-use frame_support::{assert_err, assert_ok, traits::OnInitialize};
-use qfp_consensus_spin::{Slot, SPIN_ENGINE_ID};
-use sp_runtime::{traits::BadOrigin, Digest, DigestItem};
+use frame_support::{
+	assert_err, assert_ok,
+	traits::{FindAuthor, OnInitialize, OnTimestampSet, OneSessionHandler},
+};
+use frame_system::pallet_prelude::HeaderFor;
+use qfp_consensus_spin::{
+	check_equivocation_proof,
+	ed25519::{AuthorityId, AuthoritySignature},
+	AuthorityLiveness, ConsensusLog, PendingChange, PreDigest, ScheduledChange, Slot, SlotSchedule,
+	SpinEquivocationProof, SPIN_ENGINE_ID,
+};
+use sp_core::{ed25519, Pair};
+use sp_runtime::{
+	traits::{BadOrigin, ConvertInto, Header as _},
+	Digest, DigestItem,
+};
+
+fn pre_digest(slot: u64) -> Digest {
+	Digest { logs: vec![DigestItem::PreRuntime(SPIN_ENGINE_ID, Slot::from(slot).encode())] }
+}
+
+fn produce_block(number: u64, slot: u64) {
+	System::initialize(&number, &System::parent_hash(), &pre_digest(slot));
+	Spin::on_initialize(number);
+	System::finalize();
+}
+
+fn produce_secondary_block(number: u64, slot: u64, authority_index: u32) {
+	let (engine_id, data) =
+		PreDigest::Secondary { slot: slot.into(), authority_index }.to_pre_runtime();
+	let digest = Digest { logs: vec![DigestItem::PreRuntime(engine_id, data)] };
+	System::initialize(&number, &System::parent_hash(), &digest);
+	Spin::on_initialize(number);
+	System::finalize();
+}
+
+fn session_keys(validators: &[(u64, AuthorityId)]) -> impl Iterator<Item = (&u64, AuthorityId)> {
+	validators.iter().map(|(i, k)| (i, k.clone()))
+}
+
+fn liveness(index: u32) -> AuthorityLiveness {
+	pallet::SessionLiveness::<Test>::get(index)
+}
+
+fn sealed_header(slot: u64, extrinsics_root: u8, pair: &ed25519::Pair) -> HeaderFor<Test> {
+	let header = HeaderFor::<Test>::new(
+		1,
+		[extrinsics_root; 32].into(),
+		Default::default(),
+		System::parent_hash(),
+		pre_digest(slot),
+	);
+	seal(header, pair)
+}
+
+/// A header of `slot` on top of `parent`, sealed by `pair`.
+fn sealed_child(parent: &HeaderFor<Test>, slot: u64, pair: &ed25519::Pair) -> HeaderFor<Test> {
+	let header = HeaderFor::<Test>::new(
+		parent.number + 1,
+		Default::default(),
+		Default::default(),
+		parent.hash(),
+		pre_digest(slot),
+	);
+	seal(header, pair)
+}
+
+fn seal(mut header: HeaderFor<Test>, pair: &ed25519::Pair) -> HeaderFor<Test> {
+	let signature = AuthoritySignature::from(pair.sign(header.hash().as_ref()));
+	header.digest_mut().push(DigestItem::Seal(SPIN_ENGINE_ID, signature.encode()));
+	header
+}
+
+fn equivocation_proof(
+	slot: u64,
+	first_header: HeaderFor<Test>,
+	second_header: HeaderFor<Test>,
+	pair: &ed25519::Pair,
+) -> SpinEquivocationProof<HeaderFor<Test>, AuthorityId> {
+	SpinEquivocationProof {
+		offender: AuthorityId::from(pair.public()),
+		slot: Slot::from(slot),
+		first_header,
+		second_header,
+	}
+}
 
 #[test]
 fn initial_values() {
@@ -35,24 +121,268 @@ fn session_length_works() {
 }
 
 #[test]
-#[should_panic(
-	expected = "Validator with index 1 is disabled and should not be attempting to author blocks."
-)]
-fn disabled_validators_cannot_author_blocks() {
+fn slot_duration_change_takes_effect_at_activation_slot() {
 	build_ext_and_execute_test(vec![0, 1, 2, 3], || {
-		// slot 1 should be authored by validator at index 1
-		let slot = Slot::from(1);
-		let pre_digest =
-			Digest { logs: vec![DigestItem::PreRuntime(SPIN_ENGINE_ID, slot.encode())] };
+		produce_block(1, 1);
 
-		System::reset_events();
-		System::initialize(&1, &System::parent_hash(), &pre_digest);
+		assert_err!(Spin::set_slot_duration(RuntimeOrigin::signed(1), 6, 10.into()), BadOrigin);
+		assert_err!(
+			Spin::set_slot_duration(RuntimeOrigin::root(), 1, 10.into()),
+			Error::<Test>::SlotDurationTooShort,
+		);
+		assert_err!(
+			Spin::set_slot_duration(RuntimeOrigin::root(), 6, 4.into()),
+			Error::<Test>::SlotDurationChangeTooSoon,
+		);
+		assert_ok!(Spin::set_slot_duration(RuntimeOrigin::root(), 6, 10.into()));
+
+		// slot 10 starts at 20 ms at the current duration of 2 ms.
+		let schedule =
+			SlotSchedule { start_slot: 10.into(), start_timestamp: 20, slot_duration: 6 };
+		assert_eq!(Spin::pending_slot_schedule(), Some(schedule));
+		assert_eq!(Spin::slot_duration(), 2);
+
+		produce_block(2, 9);
+		Spin::on_timestamp_set(19);
+		assert_eq!(Spin::slot_duration(), 2);
+
+		// slots keep being numbered from the activation slot on.
+		produce_block(3, 11);
+		Spin::on_timestamp_set(26);
+		assert_eq!(Spin::slot_duration(), 6);
+		assert_eq!(Spin::slot_schedule(), schedule);
+		assert_eq!(Spin::pending_slot_schedule(), None);
+	});
+}
+
+fn find_author(slot: u64) -> Option<u32> {
+	let slot = Slot::from(slot).encode();
+	<Spin as FindAuthor<u32>>::find_author(vec![(SPIN_ENGINE_ID, &slot[..])])
+}
+
+#[test]
+fn disabled_leader_tenure_falls_through() {
+	build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+		// slots 4 to 7 are led by the validator at index 1
+		assert_eq!(find_author(4), Some(1));
 
-		// let's disable the validator
 		MockDisabledValidators::disable_validator(1);
+		MockDisabledValidators::disable_validator(2);
+		assert_eq!(Spin::disabled_authorities(), vec![1, 2]);
 
-		// and we should not be able to initialize the block
-		Spin::on_initialize(1);
+		// the tenure falls through to the next enabled validator
+		assert_eq!(find_author(4), Some(3));
+		assert_eq!(find_author(8), Some(3));
+		assert_eq!(find_author(12), Some(3));
+		assert_eq!(find_author(16), Some(0));
+
+		// and the disabled validator no longer halts the chain
+		produce_block(1, 5);
+		assert_eq!(pallet::CurrentSlot::<Test>::get(), 5);
+		assert_ok!(Spin::do_try_state());
+	});
+}
+
+#[test]
+fn leaders_are_kept_when_all_are_disabled() {
+	build_ext_and_execute_test(vec![0, 1], || {
+		MockDisabledValidators::disable_validator(0);
+		MockDisabledValidators::disable_validator(1);
+
+		assert_eq!(find_author(0), Some(0));
+		assert_eq!(find_author(4), Some(1));
+	});
+}
+
+#[test]
+fn authorities_lead_in_index_order_by_default() {
+	build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+		assert!(pallet::LeaderOrder::<Test>::get().is_empty());
+		assert_eq!(Spin::leader_order(), vec![0, 1, 2, 3]);
+		assert_eq!(
+			<RoundRobin as LeaderSelection<u64>>::leader_order(0, &[7, 8, 9], 10),
+			vec![0, 1, 2],
+		);
+	});
+}
+
+#[test]
+fn aux_data_reports_session_parameters() {
+	build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+		MockDisabledValidators::disable_validator(2);
+
+		let aux_data = Spin::aux_data();
+		assert_eq!(aux_data.authorities, pallet::Authorities::<Test>::get().into_inner());
+		assert_eq!(aux_data.session_length, DEFAULT_SESSION_LENGTH);
+		assert_eq!(aux_data.session_index, 0);
+		assert_eq!(aux_data.leader_order, vec![0, 1, 2, 3]);
+		assert_eq!(aux_data.disabled, vec![2]);
+		// the genesis authorities are also the next ones until a session is queued.
+		assert_eq!(aux_data.next_authorities, aux_data.authorities);
+
+		let validators = aux_data
+			.authorities
+			.into_iter()
+			.enumerate()
+			.map(|(i, k)| (i as u64, k))
+			.collect::<Vec<_>>();
+		let queued = validators[..2].to_vec();
+		Spin::on_new_session(false, session_keys(&validators), session_keys(&queued));
+		assert_eq!(
+			Spin::aux_data().next_authorities,
+			queued.into_iter().map(|(_, k)| k).collect::<Vec<_>>(),
+		);
+	});
+}
+
+#[test]
+fn stake_weighted_leaders_lead_in_proportion_to_stake() {
+	// the stake of a validator is its account id.
+	let order = |validators: &[u64], max_tenures| {
+		StakeWeighted::<ConvertInto>::leader_order(0, validators, max_tenures)
+	};
+
+	// every validator leads a tenure, the 5 others are shared by stake.
+	assert_eq!(order(&[100, 100, 200], 8), vec![2, 0, 1, 2, 0, 1, 2]);
+	// without stake, or without room for the stake, leaders are taken in turn.
+	assert_eq!(order(&[0, 0, 0], 8), vec![0, 1, 2]);
+	assert_eq!(order(&[100, 100, 200], 2), vec![0, 1, 2]);
+}
+
+#[test]
+fn shuffled_leaders_differ_per_session() {
+	let validators = (0..10u64).collect::<Vec<_>>();
+	let order = |session_index| {
+		RandomShuffle::<Test, TestRandomness>::leader_order(session_index, &validators[..], 20)
+	};
+
+	let mut first = order(0);
+	assert_eq!(first, order(0));
+	assert!((1..10).any(|session_index| order(session_index) != first));
+
+	// every validator still leads exactly one tenure.
+	first.sort();
+	assert_eq!(first, (0..10).collect::<Vec<_>>());
+}
+
+#[test]
+fn leader_order_is_selected_at_new_session() {
+	build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+		// indices of unknown authorities are dropped.
+		MockLeaderOrder::set(Some(vec![2, 0, 7, 2, 1]));
+		let validators = pallet::Authorities::<Test>::get()
+			.into_iter()
+			.enumerate()
+			.map(|(i, k)| (i as u64, k))
+			.collect::<Vec<_>>();
+		let keys = || validators.iter().map(|(i, k)| (i, k.clone()));
+		// the order is selected for the queued validators and taken over by
+		// them when the next session starts.
+		Spin::on_new_session(false, keys(), keys());
+		assert_eq!(Spin::leader_order(), vec![0, 1, 2, 3]);
+		Spin::on_new_session(false, keys(), keys());
+		assert_eq!(Spin::leader_order(), vec![2, 0, 2, 1]);
+
+		assert_eq!(find_author(3), Some(2));
+		assert_eq!(find_author(4), Some(0));
+		assert_eq!(find_author(8), Some(2));
+		assert_eq!(find_author(12), Some(1));
+		assert_eq!(find_author(16), Some(2));
+		// the secondary author is the authority after the leader.
+		assert_eq!(Spin::secondary_slot_leader(4.into()), Some(1));
+
+		// slots are accounted to the leaders of the order.
+		produce_block(1, 1);
+		produce_block(2, 9);
+		assert_eq!(liveness(0), AuthorityLiveness { expected: 4, produced: 0 });
+		assert_eq!(liveness(2), AuthorityLiveness { expected: 5, produced: 2 });
+	});
+}
+
+fn spin_logs() -> Vec<Vec<u8>> {
+	System::digest()
+		.logs
+		.iter()
+		.filter_map(|log| log.as_consensus())
+		.filter(|(id, _)| *id == SPIN_ENGINE_ID)
+		.map(|(_, data)| data.to_vec())
+		.collect()
+}
+
+#[test]
+fn authority_change_is_scheduled_for_next_session() {
+	build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+		let validators = pallet::Authorities::<Test>::get()
+			.into_iter()
+			.enumerate()
+			.map(|(i, k)| (i as u64, k))
+			.collect::<Vec<_>>();
+		let queued = validators[..2].to_vec();
+		let next_authorities = queued.iter().map(|(_, k)| k.clone()).collect::<Vec<_>>();
+
+		System::initialize(&1, &System::parent_hash(), &pre_digest(1));
+		Spin::on_new_session(true, session_keys(&validators), session_keys(&queued));
+
+		// the next session is estimated to start at block 10.
+		let pending = PendingChange {
+			next_authorities: next_authorities.clone(),
+			leader_order: vec![0, 1],
+			effective_at: 10,
+		};
+		assert_eq!(Spin::pending_change(), Some(pending));
+		assert_eq!(
+			spin_logs(),
+			vec![ConsensusLog::<_, u64>::ScheduledChange(ScheduledChange {
+				next_authorities: next_authorities.clone(),
+				delay: 9,
+			})
+			.encode()],
+		);
+		assert_eq!(pallet::Authorities::<Test>::get().len(), 4);
+		System::finalize();
+
+		// the queued validators take over when the next session starts.
+		System::initialize(&10, &System::parent_hash(), &pre_digest(10));
+		Spin::on_new_session(true, session_keys(&queued), session_keys(&queued));
+		Spin::on_initialize(10);
+
+		assert_eq!(pallet::Authorities::<Test>::get().into_inner(), next_authorities);
+		assert_eq!(Spin::leader_order(), vec![0, 1]);
+		assert_eq!(
+			spin_logs(),
+			vec![ConsensusLog::<_, u64>::AuthoritiesChange(next_authorities.clone()).encode()],
+		);
+		// nothing changes with the session after.
+		assert_eq!(
+			Spin::pending_change().map(|change| change.next_authorities),
+			Some(next_authorities),
+		);
+	});
+}
+
+#[test]
+fn scheduled_change_is_enacted_if_the_session_rotates_late() {
+	build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+		let validators = pallet::Authorities::<Test>::get()
+			.into_iter()
+			.enumerate()
+			.map(|(i, k)| (i as u64, k))
+			.collect::<Vec<_>>();
+		let queued = validators[1..].to_vec();
+
+		System::initialize(&1, &System::parent_hash(), &pre_digest(1));
+		Spin::on_new_session(false, session_keys(&validators), session_keys(&queued));
+		System::finalize();
+
+		produce_block(9, 9);
+		assert_eq!(pallet::Authorities::<Test>::get().len(), 4);
+
+		// the client expects the next authorities from block 10 on.
+		produce_block(10, 10);
+		assert_eq!(pallet::Authorities::<Test>::get().len(), 3);
+		assert_eq!(Spin::leader_order(), vec![0, 1, 2]);
+		assert_eq!(Spin::pending_change(), None);
+		assert_eq!(Spin::aux_data().next_authorities, Spin::aux_data().authorities);
 	});
 }
 
@@ -118,3 +448,255 @@ fn pallet_always_rejects_decreasing_slot() {
 		Spin::on_initialize(2);
 	});
 }
+
+#[test]
+fn blocks_of_the_same_slot_are_accounted_once() {
+	build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+		crate::mock::AllowMultipleBlocksPerSlot::set(true);
+
+		produce_block(1, 1);
+		produce_block(2, 1);
+		produce_block(3, 1);
+		assert_eq!(pallet::CurrentSlot::<Test>::get(), Slot::from(1));
+		assert_eq!(liveness(0), AuthorityLiveness { expected: 1, produced: 1 });
+
+		produce_block(4, 2);
+		produce_block(5, 2);
+		assert_eq!(liveness(0), AuthorityLiveness { expected: 2, produced: 2 });
+	});
+}
+
+#[test]
+fn secondary_authorship_is_left_to_the_verifier() {
+	build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+		crate::mock::AllowMultipleBlocksPerSlot::set(true);
+
+		produce_block(1, 4);
+		// the first two slots of the tenure are reserved to the leader.
+		assert!(!Spin::secondary_authoring_allowed(9.into()));
+		// and only the secondary author of the slot may take over.
+		assert_eq!(Spin::secondary_slot_leader(10.into()), Some(3));
+
+		produce_block(2, 10);
+		// the secondary author cannot follow the leader within its slot.
+		assert!(!Spin::secondary_authoring_allowed(10.into()));
+
+		// blocks the verifier rejects do not panic the runtime, which may see
+		// other authorities than the verifier at a session boundary.
+		produce_secondary_block(3, 10, 0);
+		assert!(pallet::SecondaryAuthored::<Test>::get());
+	});
+}
+
+#[test]
+fn secondary_author_takes_over_after_empty_slots() {
+	build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+		produce_block(1, 4);
+
+		// slots 8 to 11 are led by authority 2, authority 3 is its secondary.
+		assert_eq!(Spin::secondary_slot_leader(8.into()), Some(3));
+		// the first two slots of the tenure are reserved to the leader.
+		assert!(!Spin::secondary_authoring_allowed(9.into()));
+		assert!(Spin::secondary_authoring_allowed(10.into()));
+
+		produce_secondary_block(2, 10, 3);
+		assert!(pallet::SecondaryAuthored::<Test>::get());
+		let (engine_id, data) =
+			PreDigest::Secondary { slot: 10.into(), authority_index: 3 }.to_pre_runtime();
+		assert_eq!(<Spin as FindAuthor<u32>>::find_author(vec![(engine_id, &data[..])]), Some(3));
+		// the secondary pre-digest is not read as the bare slot of a leader block.
+		assert_eq!(<Spin as FindAuthor<u32>>::find_author(vec![(SPIN_ENGINE_ID, &data[..])]), None);
+
+		// once taken over, the secondary author keeps the rest of the tenure.
+		produce_secondary_block(3, 11, 3);
+		assert_eq!(liveness(2), AuthorityLiveness { expected: 4, produced: 0 });
+		assert_eq!(liveness(3), AuthorityLiveness::default());
+
+		// but not the next tenure.
+		assert!(!Spin::secondary_authoring_allowed(12.into()));
+		produce_block(4, 12);
+		assert!(!pallet::SecondaryAuthored::<Test>::get());
+		assert!(!Spin::secondary_authoring_allowed(14.into()));
+		assert!(Spin::secondary_authoring_allowed(15.into()));
+	});
+}
+
+#[test]
+fn valid_equivocation_proof_is_accepted() {
+	build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+		let pair = ed25519::Pair::from_seed(&[1; 32]);
+
+		let proof =
+			equivocation_proof(7, sealed_header(7, 1, &pair), sealed_header(7, 2, &pair), &pair);
+		assert!(check_equivocation_proof(proof));
+	});
+}
+
+#[test]
+fn invalid_equivocation_proofs_are_rejected() {
+	build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+		let pair = ed25519::Pair::from_seed(&[1; 32]);
+		let other = ed25519::Pair::from_seed(&[2; 32]);
+
+		// the same header twice is not an equivocation
+		let header = sealed_header(7, 1, &pair);
+		assert!(!check_equivocation_proof(equivocation_proof(7, header.clone(), header, &pair)));
+
+		// headers must target the same slot
+		let proof =
+			equivocation_proof(7, sealed_header(7, 1, &pair), sealed_header(8, 2, &pair), &pair);
+		assert!(!check_equivocation_proof(proof));
+
+		// and it must be the slot of the proof
+		let proof =
+			equivocation_proof(8, sealed_header(7, 1, &pair), sealed_header(7, 2, &pair), &pair);
+		assert!(!check_equivocation_proof(proof));
+
+		// both headers must be sealed by the offender
+		let proof =
+			equivocation_proof(7, sealed_header(7, 1, &pair), sealed_header(7, 2, &other), &pair);
+		assert!(!check_equivocation_proof(proof));
+
+		// and unsealed headers are rejected
+		let mut unsealed = sealed_header(7, 2, &pair);
+		unsealed.digest_mut().pop();
+		let proof = equivocation_proof(7, sealed_header(7, 1, &pair), unsealed, &pair);
+		assert!(!check_equivocation_proof(proof));
+	});
+}
+
+#[test]
+fn blocks_a_leader_built_on_each_other_are_no_equivocation() {
+	build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+		let pair = ed25519::Pair::from_seed(&[1; 32]);
+		let parent = sealed_header(7, 1, &pair);
+		let child = sealed_child(&parent, 7, &pair);
+		let grandchild = sealed_child(&child, 7, &pair);
+
+		// a child in the slot of its parent is an equivocation while the leader
+		// may author a single block per slot.
+		crate::mock::AllowMultipleBlocksPerSlot::set(false);
+		let proof = equivocation_proof(7, parent.clone(), child.clone(), &pair);
+		assert!(Spin::check_equivocation_proof(proof));
+
+		crate::mock::AllowMultipleBlocksPerSlot::set(true);
+		let proof = equivocation_proof(7, parent.clone(), child.clone(), &pair);
+		assert!(!Spin::check_equivocation_proof(proof));
+		let proof = equivocation_proof(7, child.clone(), parent.clone(), &pair);
+		assert!(!Spin::check_equivocation_proof(proof));
+
+		// blocks further apart are not either, even once orphaned.
+		let proof = equivocation_proof(7, parent, grandchild, &pair);
+		assert!(!Spin::check_equivocation_proof(proof));
+
+		// forks in the slot still are.
+		let proof =
+			equivocation_proof(7, sealed_header(7, 1, &pair), sealed_header(7, 2, &pair), &pair);
+		assert!(Spin::check_equivocation_proof(proof));
+	});
+}
+
+#[test]
+fn new_session_notes_its_first_slot() {
+	build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+		assert_eq!(pallet::SessionStartSlot::<Test>::get(), 0u64);
+
+		System::initialize(&1, &System::parent_hash(), &pre_digest(5));
+		Spin::on_initialize(1);
+		System::finalize();
+
+		// the session rotates before `on_initialize` notes the slot of the block
+		System::initialize(&2, &System::parent_hash(), &pre_digest(9));
+		let validators = pallet::Authorities::<Test>::get()
+			.into_iter()
+			.enumerate()
+			.map(|(i, k)| (i as u64, k))
+			.collect::<Vec<_>>();
+		let keys = || validators.iter().map(|(i, k)| (i, k.clone()));
+		Spin::on_new_session(false, keys(), keys());
+		Spin::on_initialize(2);
+
+		assert_eq!(pallet::SessionStartSlot::<Test>::get(), 9u64);
+	});
+}
+
+#[test]
+fn slots_are_accounted_to_their_leaders() {
+	use crate::liveness::slots_per_leader;
+
+	// 3 authorities leading 2 slots each, slots 1 to 20.
+	assert_eq!(slots_per_leader(1, 20, 2, &[0, 1, 2]), vec![(0, 7), (1, 7), (2, 6)]);
+	// within a single tenure
+	assert_eq!(slots_per_leader(7, 7, 2, &[0, 1, 2]), vec![(0, 1)]);
+	// spanning many rotations
+	assert_eq!(
+		slots_per_leader(6, 6_000_005, 2, &[0, 1, 2]),
+		vec![(0, 2_000_000), (1, 2_000_000), (2, 2_000_000)],
+	);
+	// authorities leading more than one tenure of a rotation, in any order.
+	assert_eq!(slots_per_leader(0, 15, 2, &[2, 0, 2, 1]), vec![(0, 4), (1, 4), (2, 8)]);
+	assert_eq!(slots_per_leader(3, 5, 2, &[2, 0, 2, 1]), vec![(0, 1), (2, 2)]);
+}
+
+#[test]
+fn missed_slots_are_tracked() {
+	build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+		// the first block only accounts its own slot.
+		produce_block(1, 1);
+		assert_eq!(liveness(0), AuthorityLiveness { expected: 1, produced: 1 });
+
+		produce_block(2, 2);
+		assert_eq!(liveness(0), AuthorityLiveness { expected: 2, produced: 2 });
+
+		// slot 3 is missed by authority 0, slots 4 to 6 by authority 1.
+		produce_block(3, 7);
+		assert_eq!(liveness(0), AuthorityLiveness { expected: 3, produced: 2 });
+		assert_eq!(liveness(1), AuthorityLiveness { expected: 4, produced: 1 });
+		assert_eq!(liveness(2), AuthorityLiveness::default());
+		assert_eq!(liveness(1).score(), sp_runtime::Perbill::from_percent(25));
+
+		let reported = Spin::session_liveness();
+		assert_eq!(reported.len(), 4);
+		assert_eq!(reported[1].1, liveness(1));
+	});
+}
+
+#[test]
+fn disabled_leader_slots_are_accounted_to_stand_in() {
+	build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+		MockDisabledValidators::disable_validator(1);
+
+		produce_block(1, 3);
+		// slots 4 to 7 are led by authority 2 instead of the disabled authority 1.
+		produce_block(2, 6);
+		assert_eq!(liveness(1), AuthorityLiveness::default());
+		assert_eq!(liveness(2), AuthorityLiveness { expected: 3, produced: 1 });
+	});
+}
+
+#[test]
+fn unresponsive_authorities_are_reported_at_session_end() {
+	build_ext_and_execute_test(vec![0, 1, 2, 3], || {
+		produce_block(1, 4);
+		// authority 2 leads slots 8 to 11 but produces nothing.
+		produce_block(2, 12);
+		assert_eq!(liveness(2), AuthorityLiveness { expected: 4, produced: 0 });
+
+		Spin::on_before_session_ending();
+
+		let offences = ReportedOffences::get();
+		assert_eq!(offences.len(), 1);
+		assert_eq!(offences[0].offenders, vec![(2, 2)]);
+		assert_eq!(offences[0].validator_set_count, 4);
+
+		// the new session starts with a clean slate.
+		let validators = pallet::Authorities::<Test>::get()
+			.into_iter()
+			.enumerate()
+			.map(|(i, k)| (i as u64, k))
+			.collect::<Vec<_>>();
+		let keys = || validators.iter().map(|(i, k)| (i, k.clone()));
+		Spin::on_new_session(false, keys(), keys());
+		assert_eq!(liveness(2), AuthorityLiveness::default());
+	});
+}
@@ -7,7 +7,7 @@
 //! This implements the digests for SPIN, to allow the private
 //! `CompatibleDigestItem` trait to appear in public interfaces.
 
-use crate::SPIN_ENGINE_ID;
+use crate::{AuthorityIndex, PreDigest, SPIN_ENGINE_ID};
 use codec::{Codec, Encode};
 use sp_consensus_slots::Slot;
 use sp_runtime::generic::DigestItem;
@@ -23,8 +23,15 @@ pub trait CompatibleDigestItem<Signature>: Sized {
 	/// Construct a digest item which contains the slot number
 	fn spin_pre_digest(slot: Slot) -> Self;
 
+	/// Construct a digest item which contains the slot number and the index of
+	/// the secondary author that authored it.
+	fn spin_secondary_pre_digest(slot: Slot, authority_index: AuthorityIndex) -> Self;
+
 	/// If this item is an SPIN pre-digest, return the slot number
 	fn as_spin_pre_digest(&self) -> Option<Slot>;
+
+	/// If this item is an SPIN pre-digest, return it.
+	fn as_spin_full_pre_digest(&self) -> Option<PreDigest>;
 }
 
 impl<Signature> CompatibleDigestItem<Signature> for DigestItem
@@ -40,10 +47,21 @@ where
 	}
 
 	fn spin_pre_digest(slot: Slot) -> Self {
-		DigestItem::PreRuntime(SPIN_ENGINE_ID, slot.encode())
+		let (engine_id, data) = PreDigest::Primary(slot).to_pre_runtime();
+		DigestItem::PreRuntime(engine_id, data)
+	}
+
+	fn spin_secondary_pre_digest(slot: Slot, authority_index: AuthorityIndex) -> Self {
+		let (engine_id, data) = PreDigest::Secondary { slot, authority_index }.to_pre_runtime();
+		DigestItem::PreRuntime(engine_id, data)
 	}
 
 	fn as_spin_pre_digest(&self) -> Option<Slot> {
-		self.pre_runtime_try_to(&SPIN_ENGINE_ID)
+		self.as_spin_full_pre_digest().map(|pre_digest| pre_digest.slot())
+	}
+
+	fn as_spin_full_pre_digest(&self) -> Option<PreDigest> {
+		let (engine_id, data) = self.as_pre_runtime()?;
+		PreDigest::from_pre_runtime(engine_id, data)
 	}
 }
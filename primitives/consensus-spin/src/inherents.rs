@@ -53,6 +53,15 @@ impl InherentDataProvider {
 
 		Self { slot }
 	}
+
+	/// Creates the inherent data provider by calculating the slot from the
+	/// given `timestamp` and the slot `schedule` in effect at it.
+	pub fn from_timestamp_and_slot_schedule(
+		timestamp: sp_timestamp::Timestamp,
+		schedule: crate::SlotSchedule,
+	) -> Self {
+		Self { slot: schedule.slot_at(timestamp.as_millis()) }
+	}
 }
 
 #[cfg(feature = "std")]
@@ -9,16 +9,23 @@
 extern crate alloc;
 
 use alloc::vec::Vec;
-use codec::{Codec, Decode, Encode};
-use sp_runtime::ConsensusEngineId;
+use codec::{Codec, Decode, Encode, MaxEncodedLen};
+use scale_info::TypeInfo;
+use sp_application_crypto::{KeyTypeId, RuntimeAppPublic};
+use sp_runtime::{traits::Header, ConsensusEngineId, DigestItem, OpaqueValue, Perbill};
+
+use crate::digests::CompatibleDigestItem;
 
 pub mod digests;
 pub mod inherents;
 
+/// The `KeyTypeId` of SPIN authority keys.
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"spin");
+
 pub mod sr25519 {
 	mod app_sr25519 {
-		use sp_application_crypto::{app_crypto, key_types::AURA, sr25519};
-		app_crypto!(sr25519, AURA);
+		use sp_application_crypto::{app_crypto, sr25519};
+		app_crypto!(sr25519, crate::KEY_TYPE);
 	}
 
 	sp_application_crypto::with_pair! {
@@ -35,8 +42,8 @@ pub mod sr25519 {
 
 pub mod ed25519 {
 	mod app_ed25519 {
-		use sp_application_crypto::{app_crypto, ed25519, key_types::AURA};
-		app_crypto!(ed25519, AURA);
+		use sp_application_crypto::{app_crypto, ed25519};
+		app_crypto!(ed25519, crate::KEY_TYPE);
 	}
 
 	sp_application_crypto::with_pair! {
@@ -51,40 +58,498 @@ pub mod ed25519 {
 	pub type AuthorityId = app_ed25519::Public;
 }
 
+/// SPIN authority keys registered under the AURA `KeyTypeId`.
+///
+/// SPIN used to share the AURA key type before it got its own [`KEY_TYPE`].
+/// These definitions are only kept so that runtimes can migrate session keys
+/// registered before the switch.
+pub mod legacy {
+	pub mod sr25519 {
+		mod app_sr25519 {
+			use sp_application_crypto::{app_crypto, key_types::AURA, sr25519};
+			app_crypto!(sr25519, AURA);
+		}
+
+		/// A legacy SPIN authority identifier registered under the AURA key type.
+		pub type AuthorityId = app_sr25519::Public;
+	}
+}
+
 pub use sp_consensus_slots::{Slot, SlotDuration};
 
 /// The `ConsensusEngineId` of SPIN.
 pub const SPIN_ENGINE_ID: ConsensusEngineId = *b"spin";
 
+/// The `ConsensusEngineId` of the pre-runtime digests of blocks authored by a
+/// secondary author, see [`PreDigest`].
+pub const SPIN_SECONDARY_ENGINE_ID: ConsensusEngineId = *b"spn2";
+
 /// The index of an authority.
 pub type AuthorityIndex = u32;
 
 /// The length of the session.
 pub type SessionLength<BlockNumber> = BlockNumber;
 
-/// Auxilary data for SPIN
-pub type SpinAuxData<A, BlockNumber> = (Vec<A>, SessionLength<BlockNumber>);
+/// Auxilary data for SPIN.
+///
+/// The consensus parameters of the current session that determine who may
+/// author the next blocks.
+#[derive(Clone, Debug, PartialEq, Eq, Decode, Encode, TypeInfo)]
+pub struct SpinAuxData<AuthorityId, BlockNumber> {
+	/// The current set of authorities.
+	pub authorities: Vec<AuthorityId>,
+	/// The number of consecutive slots every authority leads.
+	pub session_length: SessionLength<BlockNumber>,
+	/// The index of the current session.
+	pub session_index: u32,
+	/// The order in which the authorities lead tenures, see [`slot_leader`].
+	pub leader_order: Vec<AuthorityIndex>,
+	/// The indices of the disabled authorities.
+	pub disabled: Vec<AuthorityIndex>,
+	/// The authorities scheduled to take over in the next session, the current
+	/// ones if no change is scheduled.
+	pub next_authorities: Vec<AuthorityId>,
+}
+
+/// A SPIN pre-runtime digest.
+///
+/// Blocks of the slot leader carry the bare slot under [`SPIN_ENGINE_ID`], the
+/// same way as before backup authoring existed. Blocks of a secondary author
+/// carry the slot and its authority index under [`SPIN_SECONDARY_ENGINE_ID`],
+/// so that the engine id tells both apart.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PreDigest {
+	/// The block was authored by the leader of the slot.
+	Primary(Slot),
+	/// The block was authored by the secondary author of the slot, because the
+	/// leader missed too many slots.
+	Secondary {
+		/// The slot of the block.
+		slot: Slot,
+		/// The index of the secondary author.
+		authority_index: AuthorityIndex,
+	},
+}
+
+impl PreDigest {
+	/// The slot of the block.
+	pub fn slot(&self) -> Slot {
+		match self {
+			Self::Primary(slot) => *slot,
+			Self::Secondary { slot, .. } => *slot,
+		}
+	}
+
+	/// Whether the block was authored by the leader of the slot.
+	pub fn is_primary(&self) -> bool {
+		matches!(self, Self::Primary(_))
+	}
+
+	/// The engine id and the payload of the pre-runtime digest.
+	pub fn to_pre_runtime(&self) -> (ConsensusEngineId, Vec<u8>) {
+		match self {
+			Self::Primary(slot) => (SPIN_ENGINE_ID, slot.encode()),
+			Self::Secondary { slot, authority_index } =>
+				(SPIN_SECONDARY_ENGINE_ID, (slot, authority_index).encode()),
+		}
+	}
+
+	/// Decode the pre-runtime digest with `engine_id` and `data`, if it is a
+	/// SPIN one.
+	pub fn from_pre_runtime(engine_id: ConsensusEngineId, mut data: &[u8]) -> Option<Self> {
+		match engine_id {
+			SPIN_ENGINE_ID => Slot::decode(&mut data).ok().map(Self::Primary),
+			SPIN_SECONDARY_ENGINE_ID => {
+				let (slot, authority_index) = Decode::decode(&mut data).ok()?;
+				Some(Self::Secondary { slot, authority_index })
+			},
+			_ => None,
+		}
+	}
+}
+
+/// A change of the authority set, scheduled to be enacted at the next session
+/// boundary.
+#[derive(Clone, Debug, PartialEq, Eq, Decode, Encode, TypeInfo)]
+pub struct ScheduledChange<AuthorityId, N> {
+	/// The authorities that take over.
+	pub next_authorities: Vec<AuthorityId>,
+	/// The number of blocks after the block that scheduled the change until
+	/// the first block authored by the next authorities.
+	pub delay: N,
+}
+
+/// A change of the authority set that is scheduled but not enacted yet.
+#[derive(Clone, Debug, PartialEq, Eq, Decode, Encode, TypeInfo)]
+pub struct PendingChange<AuthorityId, N> {
+	/// The authorities that take over.
+	pub next_authorities: Vec<AuthorityId>,
+	/// The order in which the next authorities lead tenures.
+	pub leader_order: Vec<AuthorityIndex>,
+	/// The number of the first block authored by the next authorities.
+	pub effective_at: N,
+}
 
 /// An consensus log item for SPIN.
 #[derive(Decode, Encode)]
-pub enum ConsensusLog<AuthorityId: Codec> {
+pub enum ConsensusLog<AuthorityId: Codec, N: Codec> {
 	/// The authorities have changed.
 	#[codec(index = 1)]
 	AuthoritiesChange(Vec<AuthorityId>),
 	/// Disable the authority with given index.
 	#[codec(index = 2)]
 	OnDisabled(AuthorityIndex),
+	/// A change of the authorities was scheduled.
+	#[codec(index = 3)]
+	ScheduledChange(ScheduledChange<AuthorityId, N>),
+}
+
+/// Index of the authority scheduled to lead `slot`.
+///
+/// The authorities in `order` lead `session_length` consecutive slots in turn,
+/// an authority may appear in `order` more than once. An empty `order` stands
+/// for every authority in index order. Returns `None` if there are no
+/// authorities, the session length is zero or `order` refers to an authority
+/// that does not exist.
+pub fn scheduled_leader(
+	slot: Slot,
+	session_length: u64,
+	order: &[AuthorityIndex],
+	authorities: u32,
+) -> Option<AuthorityIndex> {
+	if authorities == 0 || session_length == 0 {
+		return None;
+	}
+
+	let tenure = *slot / session_length;
+	let leader = if order.is_empty() {
+		(tenure % authorities as u64) as AuthorityIndex
+	} else {
+		order[(tenure % order.len() as u64) as usize]
+	};
+
+	(leader < authorities).then_some(leader)
+}
+
+/// Index of the authority leading `slot`.
+///
+/// This is the [`scheduled_leader`] of the slot, unless it is disabled. Returns
+/// `None` if there is no scheduled leader.
+pub fn slot_leader(
+	slot: Slot,
+	session_length: u64,
+	order: &[AuthorityIndex],
+	authorities: u32,
+	disabled: &[AuthorityIndex],
+) -> Option<AuthorityIndex> {
+	let leader = scheduled_leader(slot, session_length, order, authorities)?;
+	Some(tenure_leader(leader, authorities, disabled))
+}
+
+/// Index of the authority standing in for `leader` during its tenure.
+///
+/// The tenure of a disabled authority falls through to the next authority that
+/// is not disabled. If every authority is disabled, `leader` keeps its tenure.
+pub fn tenure_leader(
+	leader: AuthorityIndex,
+	authorities: u32,
+	disabled: &[AuthorityIndex],
+) -> AuthorityIndex {
+	(0..authorities as u64)
+		.map(|offset| ((leader as u64 + offset) % authorities as u64) as AuthorityIndex)
+		.find(|index| !disabled.contains(index))
+		.unwrap_or(leader)
+}
+
+/// Index of the secondary author of `slot`.
+///
+/// The secondary author is the next authority after the leader of `slot` that
+/// is not disabled. Returns `None` if there is no such authority.
+pub fn secondary_slot_leader(
+	slot: Slot,
+	session_length: u64,
+	order: &[AuthorityIndex],
+	authorities: u32,
+	disabled: &[AuthorityIndex],
+) -> Option<AuthorityIndex> {
+	let leader = slot_leader(slot, session_length, order, authorities, disabled)?;
+	let secondary = tenure_leader((leader + 1) % authorities, authorities, disabled);
+
+	(secondary != leader).then_some(secondary)
+}
+
+/// Whether the secondary author may author `slot` on top of a block authored
+/// at `parent_slot`.
+///
+/// The first `fallback_after` slots of a tenure and the `fallback_after` slots
+/// after a block of the leader are reserved to the leader. Once the secondary
+/// author authored a block, it may keep authoring for the rest of the tenure.
+/// A `fallback_after` of zero disables secondary authoring.
+pub fn secondary_authoring_allowed(
+	parent_slot: Slot,
+	parent_is_primary: bool,
+	slot: Slot,
+	session_length: u64,
+	fallback_after: u64,
+) -> bool {
+	if fallback_after == 0 || session_length == 0 {
+		return false;
+	}
+
+	let tenure_start = *slot - *slot % session_length;
+	let reserved_from = if *parent_slot < tenure_start {
+		tenure_start
+	} else if parent_is_primary {
+		parent_slot.saturating_add(1)
+	} else {
+		return true;
+	};
+
+	*slot >= reserved_from.saturating_add(fallback_after)
+}
+
+/// The duration of the slots from a slot on.
+///
+/// Slots keep being numbered from `start_slot` on when the slot duration
+/// changes, so slot numbers never decrease.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Decode, Encode, MaxEncodedLen, TypeInfo)]
+pub struct SlotSchedule {
+	/// The first slot of the schedule.
+	pub start_slot: Slot,
+	/// The timestamp the first slot starts at, in milliseconds.
+	pub start_timestamp: u64,
+	/// The duration of the slots, in milliseconds.
+	pub slot_duration: u64,
+}
+
+impl SlotSchedule {
+	/// The schedule of a chain that runs at `slot_duration` since genesis.
+	pub fn genesis(slot_duration: SlotDuration) -> Self {
+		Self {
+			start_slot: Slot::from(0),
+			start_timestamp: 0,
+			slot_duration: slot_duration.as_millis(),
+		}
+	}
+
+	/// The schedule that runs at `slot_duration` from `start_slot` on, as
+	/// numbered by this schedule.
+	pub fn switch_at(&self, start_slot: Slot, slot_duration: u64) -> Self {
+		Self { start_slot, start_timestamp: self.slot_start(start_slot), slot_duration }
+	}
+
+	/// The slot `timestamp` falls into.
+	///
+	/// Timestamps before the start of the schedule fall into its first slot.
+	pub fn slot_at(&self, timestamp: u64) -> Slot {
+		let elapsed = timestamp.saturating_sub(self.start_timestamp);
+		let slots = elapsed.checked_div(self.slot_duration).unwrap_or_default();
+
+		self.start_slot.saturating_add(slots)
+	}
+
+	/// The timestamp `slot` starts at.
+	pub fn slot_start(&self, slot: Slot) -> u64 {
+		(*slot)
+			.saturating_sub(*self.start_slot)
+			.saturating_mul(self.slot_duration)
+			.saturating_add(self.start_timestamp)
+	}
+}
+
+/// Slots an authority led and produced a block in during the current session.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Decode, Encode, MaxEncodedLen, TypeInfo)]
+pub struct AuthorityLiveness {
+	/// Number of slots the authority was the leader of.
+	pub expected: u32,
+	/// Number of those slots the authority produced a block in.
+	pub produced: u32,
+}
+
+impl AuthorityLiveness {
+	/// Share of the led slots the authority produced a block in.
+	///
+	/// An authority that did not lead any slot yet is considered fully live.
+	pub fn score(&self) -> Perbill {
+		if self.expected == 0 {
+			return Perbill::one();
+		}
+
+		Perbill::from_rational(self.produced, self.expected)
+	}
+
+	/// Whether the authority missed every slot it led.
+	pub fn is_unresponsive(&self) -> bool {
+		self.expected > 0 && self.produced == 0
+	}
+}
+
+/// An equivocation proof for multiple block authorships on the same slot (i.e.
+/// double vote).
+pub type SpinEquivocationProof<H, AuthorityId> =
+	sp_consensus_slots::EquivocationProof<H, AuthorityId>;
+
+/// An opaque type used to represent the key ownership proof at the runtime API
+/// boundary. The inner value is an encoded representation of the actual key
+/// ownership proof which will be parameterized when defining the runtime. At
+/// the runtime API boundary this type is unknown and as such we keep this
+/// opaque representation, implementors of the runtime API will have to make
+/// sure that all usages of `OpaqueKeyOwnershipProof` refer to the same type.
+pub type OpaqueKeyOwnershipProof = OpaqueValue;
+
+/// Verifies the equivocation proof by making sure that: both headers have
+/// different hashes, are targeting the same slot, and have valid signatures by
+/// the same authority.
+pub fn check_equivocation_proof<H, AuthorityId>(
+	proof: SpinEquivocationProof<H, AuthorityId>,
+) -> bool
+where
+	H: Header,
+	AuthorityId: RuntimeAppPublic,
+{
+	let find_pre_digest = |header: &H| {
+		header.digest().logs().iter().find_map(|log| {
+			<DigestItem as CompatibleDigestItem<AuthorityId::Signature>>::as_spin_pre_digest(log)
+		})
+	};
+
+	let verify_seal_signature = |mut header: H, offender: &AuthorityId| {
+		let seal = header.digest_mut().pop()?;
+		let signature =
+			<DigestItem as CompatibleDigestItem<AuthorityId::Signature>>::as_spin_seal(&seal)?;
+		let pre_hash = header.hash();
+
+		if !offender.verify(&pre_hash.as_ref(), &signature) {
+			return None;
+		}
+
+		Some(())
+	};
+
+	let verify_proof = || {
+		// we must have different headers for the equivocation to be valid
+		if proof.first_header.hash() == proof.second_header.hash() {
+			return None;
+		}
+
+		let first_slot = find_pre_digest(&proof.first_header)?;
+		let second_slot = find_pre_digest(&proof.second_header)?;
+
+		// both headers must be targeting the same slot and it must
+		// be the same as the one in the proof.
+		if proof.slot != first_slot || first_slot != second_slot {
+			return None;
+		}
+
+		verify_seal_signature(proof.first_header, &proof.offender)?;
+		verify_seal_signature(proof.second_header, &proof.offender)?;
+
+		Some(())
+	};
+
+	// NOTE: we isolate the verification code into an helper function that
+	// returns `Option<()>` so that we can use `?` to deal with any intermediate
+	// errors and discard the proof as invalid.
+	verify_proof().is_some()
 }
 
 sp_api::decl_runtime_apis! {
 	/// API necessary for block authorship with SPIN.
+	#[api_version(3)]
 	pub trait SpinApi<AuthorityId: Codec, BlockNumber: sp_runtime::traits::BlockNumber> {
 		/// Returns the slot duration for SPIN.
 		///
-		/// Currently, only the value provided by this type at genesis will be used.
+		/// This is the duration of the current slot schedule, see
+		/// [`SpinSlotScheduleApi`] for changes of it.
 		fn slot_duration() -> SlotDuration;
 
-		/// Return the current set of authorities.
+		/// Return the current set of authorities and the session length.
+		///
+		/// The authorities lead tenures in index order.
+		#[changed_in(2)]
+		fn aux_data() -> (Vec<AuthorityId>, SessionLength<BlockNumber>);
+
+		/// Return the current set of authorities, the session length and the
+		/// order in which the authorities lead tenures.
+		#[changed_in(3)]
+		fn aux_data() -> (Vec<AuthorityId>, SessionLength<BlockNumber>, Vec<AuthorityIndex>);
+
+		/// Return the consensus parameters of the current session.
 		fn aux_data() -> SpinAuxData<AuthorityId, BlockNumber>;
 	}
+
+	/// API to query when SPIN falls back to secondary authors.
+	pub trait SpinFallbackApi {
+		/// Return the number of empty slots after which the secondary author of
+		/// a slot may author it, see [`secondary_authoring_allowed`].
+		///
+		/// Zero means that secondary authoring is disabled.
+		fn fallback_after() -> u32;
+	}
+
+	/// API to query whether SPIN slot leaders may author several blocks per slot.
+	pub trait SpinMultiBlockApi {
+		/// Return whether the slot leader may build several blocks on top of
+		/// each other in its slot.
+		fn allow_multiple_blocks_per_slot() -> bool;
+	}
+
+	/// API to query the duration of SPIN slots over time.
+	pub trait SpinSlotScheduleApi {
+		/// Return the slot schedule in effect.
+		fn slot_schedule() -> SlotSchedule;
+
+		/// Return the slot schedule that takes over from its start slot on,
+		/// if a change of the slot duration is scheduled.
+		fn pending_slot_schedule() -> Option<SlotSchedule>;
+	}
+
+	/// API to query the scheduled changes of the SPIN authority set.
+	pub trait SpinScheduledChangeApi<AuthorityId: Codec, BlockNumber: Codec> {
+		/// Return the change of the authority set that is scheduled but not
+		/// enacted yet, if any.
+		///
+		/// Blocks from the effective block number of the change on are authored
+		/// by the next authorities, even if the runtime state of their parent
+		/// does not reflect the change yet.
+		fn pending_change() -> Option<PendingChange<AuthorityId, BlockNumber>>;
+	}
+
+	/// API to query how reliably the SPIN authorities produce blocks in their slots.
+	pub trait SpinLivenessApi<AuthorityId: Codec> {
+		/// Return the liveness of every authority in the current session.
+		fn session_liveness() -> Vec<(AuthorityId, AuthorityLiveness)>;
+	}
+
+	/// API necessary for reporting SPIN equivocations.
+	pub trait SpinEquivocationApi<AuthorityId: Codec> {
+		/// Submits an unsigned extrinsic to report an equivocation. The caller
+		/// must provide the equivocation proof and a key ownership proof
+		/// (should be obtained using `generate_key_ownership_proof`). The
+		/// extrinsic will be unsigned and should only be accepted for local
+		/// authorship (not to be broadcast to the network). This method returns
+		/// `None` when creation of the extrinsic fails, e.g. if equivocation
+		/// reporting is disabled for the given runtime (i.e. this method is
+		/// hardcoded to return `None`). Only useful in an offchain context.
+		fn submit_report_equivocation_unsigned_extrinsic(
+			equivocation_proof: SpinEquivocationProof<Block::Header, AuthorityId>,
+			key_owner_proof: OpaqueKeyOwnershipProof,
+		) -> Option<()>;
+
+		/// Generates a proof of key ownership for the given authority in the
+		/// current session. An example usage of this module is coupled with the
+		/// session historical module to prove that a given authority key is
+		/// tied to a given staking identity during a specific session. Proofs
+		/// of key ownership are necessary for submitting equivocation reports.
+		/// NOTE: even though the API takes a `slot` as parameter the current
+		/// implementations ignore this parameter and instead rely on this
+		/// method being called at the correct block height, i.e. any point at
+		/// which the given slot is live on-chain. Future implementations will
+		/// instead use indexed data through an offchain worker, not requiring
+		/// older states to be available.
+		fn generate_key_ownership_proof(
+			slot: Slot,
+			authority_id: AuthorityId,
+		) -> Option<OpaqueKeyOwnershipProof>;
+	}
 }
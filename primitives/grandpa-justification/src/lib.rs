@@ -0,0 +1,203 @@
+// Copyright (C) QF Network, 2025.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Verification of the GRANDPA justifications the SPIN bridge relies on.
+//!
+//! `pallet-spin-polkadot` accepts blocks of the fast chain once a justification
+//! proves that its GRANDPA authorities finalized them, and
+//! `pallet-spin-anchoring` accepts blocks of the relay chain the same way. They
+//! verify the justifications with [`verify_justification`] and follow the
+//! authority set changes with [`scheduled_change`].
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::collections::{btree_map::BTreeMap, btree_set::BTreeSet};
+use codec::{Decode, DecodeWithMemTracking, Encode};
+use finality_grandpa::Message as GrandpaMessage;
+use scale_info::TypeInfo;
+use sp_consensus_grandpa::{
+	AuthorityId, AuthorityList, AuthorityWeight, ConsensusLog, SetId, GRANDPA_ENGINE_ID,
+};
+use sp_runtime::{
+	traits::{ConstU32, Header as HeaderT, One},
+	BoundedVec,
+};
+
+/// Maximum number of ancestry headers in a justification.
+pub const MAX_VOTES_ANCESTRIES: u32 = 512;
+
+/// Identical to `sp_consensus_grandpa::GrandpaJustification` but with bounded
+/// `votes_ancestries` vector, so that the justifications of the GRANDPA RPC
+/// can be submitted as they are.
+#[derive(Clone, PartialEq, Eq, Encode, Decode, DecodeWithMemTracking, TypeInfo)]
+pub struct BoundedGrandpaJustification<H: HeaderT> {
+	pub round: u64,
+	pub commit: sp_consensus_grandpa::Commit<H>,
+	pub votes_ancestries: BoundedVec<H, ConstU32<MAX_VOTES_ANCESTRIES>>,
+}
+
+impl<H: HeaderT> core::fmt::Debug for BoundedGrandpaJustification<H> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("BoundedGrandpaJustification")
+			.field("round", &self.round)
+			.field("target_number", &self.commit.target_number)
+			.field("target_hash", &self.commit.target_hash)
+			.field("precommits", &self.commit.precommits.len())
+			.field("votes_ancestries", &self.votes_ancestries.len())
+			.finish()
+	}
+}
+
+/// Why a justification does not prove the finality of its commit target.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+	/// The justification has no precommits.
+	NoPrecommits,
+	/// The authority set has no weight.
+	EmptyAuthoritySet,
+	/// The weight of the authority set overflows.
+	ComputationOverflow,
+	/// A precommit is not for the commit target or a descendant of it
+	/// through the votes ancestries.
+	PrecommitIsNotCommitDescendant,
+	/// The votes ancestries contain duplicate headers or headers no
+	/// precommit descends through.
+	RedundantVotesAncestries,
+	/// A precommit is signed by a key outside the authority set.
+	UnknownAuthority,
+	/// A precommit signature is invalid.
+	BadSignature,
+	/// The precommits are signed by at most two thirds of the weight of the
+	/// authority set.
+	InsufficientWeight,
+}
+
+/// Verify that `justification` proves the finality of its commit target to
+/// the authority set `set_id` with `authorities`.
+///
+/// Every precommit must be for the commit target, or for a descendant of it
+/// through the votes ancestries, all of which must be on such a route. The
+/// authorities signing the precommits must hold more than two thirds of the
+/// weight of the set, as GRANDPA requires.
+pub fn verify_justification<H: HeaderT>(
+	justification: &BoundedGrandpaJustification<H>,
+	set_id: SetId,
+	authorities: &[(AuthorityId, AuthorityWeight)],
+) -> Result<(), Error> {
+	let commit = &justification.commit;
+	if commit.precommits.is_empty() {
+		return Err(Error::NoPrecommits);
+	}
+
+	let mut total_weight: u128 = 0;
+	for (_, weight) in authorities {
+		total_weight = total_weight
+			.checked_add(u128::from(*weight))
+			.ok_or(Error::ComputationOverflow)?;
+	}
+	if total_weight == 0 {
+		return Err(Error::EmptyAuthoritySet);
+	}
+
+	let mut ancestries = justification
+		.votes_ancestries
+		.iter()
+		.map(|header| (header.hash(), (header, false)))
+		.collect::<BTreeMap<_, _>>();
+	if ancestries.len() != justification.votes_ancestries.len() {
+		return Err(Error::RedundantVotesAncestries);
+	}
+
+	let mut seen = BTreeSet::new();
+	let mut signed_weight: u128 = 0;
+	for signed in &commit.precommits {
+		if !descends_from(
+			&mut ancestries,
+			(signed.precommit.target_hash, signed.precommit.target_number),
+			(commit.target_hash, commit.target_number),
+		) {
+			return Err(Error::PrecommitIsNotCommitDescendant);
+		}
+
+		let weight = authorities
+			.iter()
+			.find_map(|(id, weight)| (*id == signed.id).then_some(*weight))
+			.ok_or(Error::UnknownAuthority)?;
+
+		let signature_ok = sp_consensus_grandpa::check_message_signature(
+			&GrandpaMessage::Precommit(signed.precommit.clone()),
+			&signed.id,
+			&signed.signature,
+			justification.round,
+			set_id,
+		)
+		.is_valid();
+		if !signature_ok {
+			return Err(Error::BadSignature);
+		}
+
+		// an authority signing twice counts once.
+		if seen.insert(signed.id.clone()) {
+			signed_weight = signed_weight.saturating_add(u128::from(weight));
+		}
+	}
+
+	if !ancestries.values().all(|(_, used)| *used) {
+		return Err(Error::RedundantVotesAncestries);
+	}
+	if signed_weight.saturating_mul(3) <= total_weight.saturating_mul(2) {
+		return Err(Error::InsufficientWeight);
+	}
+	Ok(())
+}
+
+/// The authority set change signalled in the digest of `header`, with the
+/// delay after which it is enacted.
+///
+/// A forced change is taken like a scheduled one: it is only enacted once the
+/// current set finalizes it. If the current set has stalled, root has to
+/// override the authority set instead.
+pub fn scheduled_change<H: HeaderT>(header: &H) -> Option<(AuthorityList, H::Number)> {
+	header.digest().logs().iter().find_map(|log| {
+		match log.consensus_try_to::<ConsensusLog<H::Number>>(&GRANDPA_ENGINE_ID)? {
+			ConsensusLog::ScheduledChange(change) | ConsensusLog::ForcedChange(_, change) =>
+				Some((change.next_authorities, change.delay)),
+			_ => None,
+		}
+	})
+}
+
+/// Whether the block `(hash, number)` is `target` or descends from it through
+/// `ancestries`, the headers by hash with whether a route passed them.
+///
+/// Ancestries passed before are known to descend from `target`, so the route
+/// ends there.
+fn descends_from<H: HeaderT>(
+	ancestries: &mut BTreeMap<H::Hash, (&H, bool)>,
+	(mut hash, mut number): (H::Hash, H::Number),
+	(target_hash, target_number): (H::Hash, H::Number),
+) -> bool {
+	loop {
+		if hash == target_hash {
+			return number == target_number;
+		}
+		if number <= target_number {
+			return false;
+		}
+
+		let Some((header, used)) = ancestries.get_mut(&hash) else {
+			return false;
+		};
+		if *header.number() != number {
+			return false;
+		}
+		if core::mem::replace(used, true) {
+			return true;
+		}
+
+		hash = *header.parent_hash();
+		number -= One::one();
+	}
+}
@@ -0,0 +1,79 @@
+// Copyright (C) QF Network, 2025.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Primitives for the secure finality anchoring of SPIN.
+//!
+//! Blocks of the fast chain are instantly final once GRANDPA finalized them,
+//! and secure once they are anchored up to the watermark noted by the
+//! relayers.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use codec::{Codec, Decode, Encode, MaxEncodedLen};
+use scale_info::TypeInfo;
+
+/// The status of a registered relayer.
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode, MaxEncodedLen, TypeInfo)]
+pub struct RelayerStatus<AccountId, Balance, BlockNumber, Hash> {
+	/// The account of the relayer.
+	pub account: AccountId,
+	/// The amount held from the account while it is a relayer.
+	pub bond: Balance,
+	/// The number and hash of the block the relayer last attested as
+	/// anchored, until the watermark reaches it.
+	pub attestation: Option<(BlockNumber, Hash)>,
+}
+
+/// A block anchored by the relayers.
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode, MaxEncodedLen, TypeInfo)]
+pub struct AnchorRecord<BlockNumber, Hash> {
+	/// The number of the anchored block.
+	pub number: BlockNumber,
+	/// The hash of the anchored block.
+	pub hash: Hash,
+	/// The number of the block the anchor was noted in.
+	pub anchored_at: BlockNumber,
+	/// The hash of the anchor proof the watermark advanced on.
+	pub proof_ref: Hash,
+}
+
+sp_api::decl_runtime_apis! {
+	/// API to query the secure finality of the fast chain.
+	pub trait SpinAnchoringApi<AccountId: Codec, Balance: Codec, BlockNumber: Codec> {
+		/// Return the highest block number that is securely anchored.
+		fn secure_up_to() -> BlockNumber;
+
+		/// Return the registered relayers.
+		fn relayers() -> Vec<RelayerStatus<AccountId, Balance, BlockNumber, Block::Hash>>;
+
+		/// Return how many relayers must attest a block before the watermark
+		/// advances to it.
+		fn required_attestations() -> u32;
+
+		/// Return whether the block with `block_number` on the chain of this
+		/// block is securely anchored.
+		fn is_secure(block_number: BlockNumber) -> bool;
+
+		/// Return whether the block with `block_number` and `hash` is securely
+		/// anchored. Only the blocks this block still knows the hash of, the
+		/// last `BlockHashCount` ones, are told apart from the blocks of other
+		/// chains; older blocks are never reported anchored.
+		fn is_anchored(block_number: BlockNumber, hash: Block::Hash) -> bool;
+
+		/// Return the number of blocks this block is above the watermark.
+		fn anchor_lag() -> BlockNumber;
+
+		/// Return the earliest anchor still kept at or above `block_number`,
+		/// which anchors the block with `block_number` on its chain.
+		///
+		/// The anchor holds the hash of its own block only. To show that an
+		/// older block is anchored, clients check that it is an ancestor of
+		/// the anchored block, e.g. by the headers from one to the other.
+		fn anchor_covering(
+			block_number: BlockNumber,
+		) -> Option<AnchorRecord<BlockNumber, Block::Hash>>;
+	}
+}
@@ -0,0 +1,62 @@
+// Copyright (C) QF Network, 2025.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Primitives for the fastchain finality bridge on the parachain.
+//!
+//! Every finality proof the parachain accepts is an alive message of the
+//! fastchain. The bridge is operational while they keep coming and cools down
+//! once none arrived for the heartbeat timeout.
+//!
+//! The proofs are submitted by bonded relayers, and the bridge only advances to
+//! a fastchain block once enough of them attested it.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use codec::{Codec, Decode, Encode, MaxEncodedLen};
+use scale_info::TypeInfo;
+
+pub use qfp_spin_anchoring::RelayerStatus;
+
+/// The mode of the bridge.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Encode, Decode, MaxEncodedLen, TypeInfo)]
+pub enum Mode {
+	/// Finality proofs of the fastchain arrive within the heartbeat timeout.
+	#[default]
+	Operational,
+	/// No finality proof arrived within the heartbeat timeout. The bridge is
+	/// operational again once a fresh proof is accepted.
+	CoolDown,
+}
+
+sp_api::decl_runtime_apis! {
+	/// API to query the liveness of the fastchain finality bridge.
+	pub trait SpinPolkadotApi<
+		AccountId: Codec,
+		Balance: Codec,
+		AnchoredNumber: Codec,
+		AnchoredHash: Codec,
+		RelayBlockNumber: Codec,
+	> {
+		/// Return the mode of the bridge.
+		fn mode() -> Mode;
+
+		/// Return the relay-chain block number the last finality proof was
+		/// accepted at.
+		fn last_heartbeat() -> Option<RelayBlockNumber>;
+
+		/// Return the number of relay-chain blocks without a finality proof
+		/// after which the bridge cools down.
+		fn heartbeat_timeout() -> RelayBlockNumber;
+
+		/// Return the registered relayers with the fastchain blocks they
+		/// attested.
+		fn relayers() -> Vec<RelayerStatus<AccountId, Balance, AnchoredNumber, AnchoredHash>>;
+
+		/// Return how many relayers must attest a fastchain block before the
+		/// bridge advances to it.
+		fn required_attestations() -> u32;
+	}
+}
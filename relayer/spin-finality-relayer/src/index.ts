@@ -9,24 +9,21 @@ import type {
     ProviderInterfaceCallback,
 } from "@polkadot/rpc-provider/types";
 import { Bytes, Option, Struct } from "@polkadot/types-codec";
-import type { ITuple } from "@polkadot/types-codec/types";
-import type {
-    AuthorityId,
-    AuthorityWeight,
-    AuthorityList,
-    Header,
-} from "@polkadot/types/interfaces";
+import type { Header } from "@polkadot/types/interfaces";
 import type { RegistryTypes } from "@polkadot/types/types";
 import type { Compact, u64 } from "@polkadot/types-codec";
 import pino from "pino";
 
 const FASTCHAIN_WS = process.env.FASTCHAIN_WS ?? "ws://127.0.0.1:9944";
 const PARACHAIN_WS = process.env.PARACHAIN_WS ?? "ws://127.0.0.1:43217";
+const RELAYCHAIN_WS = process.env.RELAYCHAIN_WS ?? "ws://127.0.0.1:9900";
 const FASTCHAIN_SIGNER_URI = process.env.FASTCHAIN_SIGNER_URI ?? "//Bob";
 const PARACHAIN_SIGNER_URI = process.env.PARACHAIN_SIGNER_URI ?? "//Alice";
 const LOG_LEVEL = process.env.LOG_LEVEL ?? "info";
 const TX_TIMEOUT_MS = Number(process.env.TX_TIMEOUT_MS ?? 60_000);
 const API_CONNECT_TIMEOUT_MS = Number(process.env.API_CONNECT_TIMEOUT_MS ?? 10_000);
+const QUORUM_TIMEOUT_MS = Number(process.env.QUORUM_TIMEOUT_MS ?? 120_000);
+const QUORUM_POLL_INTERVAL_MS = 6_000;
 
 // Retry knobs (safe defaults)
 const TX_RETRY_MAX_ATTEMPTS = Number(process.env.TX_RETRY_MAX_ATTEMPTS ?? 2);
@@ -53,7 +50,6 @@ const logger = pino({
 let shutdownHandler: ((signal?: string) => Promise<void>) | null = null;
 let stopRequested = false;
 
-type AuthorityTuple = [authorityIdHex: string, weight: bigint];
 type Unsubscribe = () => void;
 type FastchainHeader = Omit<Header, "number"> & { number: Compact<u64> };
 type DecodedGrandpaJustification = {
@@ -103,12 +99,6 @@ function errorMessage(err: unknown): string {
     return String(err);
 }
 
-function isAuthoritySetMismatch(err: unknown): boolean {
-    const msg = errorMessage(err);
-    // matches: "submitFinalityProof-XXXX failed: spinPolkadot.AuthoritySetMismatch"
-    return msg.includes("AuthoritySetMismatch");
-}
-
 function isPriorityTooLow(err: unknown): boolean {
     const msg = errorMessage(err);
     return (
@@ -189,36 +179,6 @@ function makeSerialQueue() {
     return { run, drain };
 }
 
-// ---- helpers: authority comparison ----
-
-function normalizeAuthorities(authorities: AuthorityTuple[]): AuthorityTuple[] {
-    return [...authorities].sort(([idA], [idB]) => idA.localeCompare(idB));
-}
-
-function authorityTuplesEqual(
-    a: AuthorityTuple[],
-    b: AuthorityTuple[],
-): boolean {
-    const A = normalizeAuthorities(a);
-    const B = normalizeAuthorities(b);
-    if (A.length !== B.length) return false;
-    for (let i = 0; i < A.length; i++) {
-        if (A[i][0] !== B[i][0] || A[i][1] !== B[i][1]) return false;
-    }
-    return true;
-}
-
-// ---- helpers: GRANDPA authorities decoding (typed) ----
-
-function decodeAuthorityList(raw: AuthorityList): AuthorityTuple[] {
-    // AuthorityList = Vec<ITuple<[AuthorityId, AuthorityWeight]>>
-    const tuples = raw.toArray() as ITuple<[AuthorityId, AuthorityWeight]>[];
-    return tuples.map(([id, weight]) => [
-        id.toHex(),
-        BigInt(weight.toString()),
-    ]);
-}
-
 // ---- helpers: provider and justification typing ----
 
 type JustificationLike = Bytes | Uint8Array | HexString;
@@ -326,6 +286,16 @@ async function subscribeJustificationStream(
 async function fetchFinalityProofBytes(
     api: ApiPromise,
     header: FastchainHeader,
+): Promise<Uint8Array | null> {
+    return fetchFinalityProofAt(api, header.number.unwrap().toBigInt());
+}
+
+// The justification proving `blockNumber` final. For a block of a past
+// authority set, this is the justification of the block that handed the set
+// off, signed by that set.
+async function fetchFinalityProofAt(
+    api: ApiPromise,
+    blockNumber: bigint,
 ): Promise<Uint8Array | null> {
     const proveFinality = (
         api.rpc.grandpa as {
@@ -335,7 +305,7 @@ async function fetchFinalityProofBytes(
         }
     ).proveFinality;
 
-    const res = await proveFinality(header.number.unwrap().toBigInt());
+    const res = await proveFinality(blockNumber);
     if (res.isNone) return null;
     const unwrapped = res.unwrap();
     return unwrapped.justification.toU8a();
@@ -391,22 +361,6 @@ function describeJustification(notification: unknown): unknown {
 
 // ---- chain helpers ----
 
-async function fetchAuthorities(api: ApiPromise): Promise<AuthorityTuple[]> {
-    const raw =
-        (await api.call.grandpaApi.grandpaAuthorities()) as unknown as AuthorityList;
-    return normalizeAuthorities(decodeAuthorityList(raw));
-}
-
-async function fetchAuthoritiesAt(
-    api: ApiPromise,
-    atHash: HexString,
-): Promise<AuthorityTuple[]> {
-    const apiAt = await api.at(atHash);
-    const raw =
-        (await apiAt.call.grandpaApi.grandpaAuthorities()) as unknown as AuthorityList;
-    return normalizeAuthorities(decodeAuthorityList(raw));
-}
-
 async function fetchSetIdAt(
     api: ApiPromise,
     atHash: HexString,
@@ -415,13 +369,6 @@ async function fetchSetIdAt(
     return BigInt(setIdCodec.toString());
 }
 
-function formatAuthoritiesForParachain(authorities: AuthorityTuple[]) {
-    return normalizeAuthorities(authorities).map(([authorityId, weight]) => [
-        authorityId,
-        weight.toString(),
-    ]);
-}
-
 // Note: explicit nonce + fail-fast on dropped/usurped/invalid
 async function signAndSendAndWait(
     api: ApiPromise,
@@ -529,46 +476,195 @@ async function signAndSendAndWait(
     });
 }
 
-async function ensureAuthoritySet(
-    api: ApiPromise,
+async function fetchParachainSetId(api: ApiPromise): Promise<bigint | null> {
+    const current = (
+        await api.query.spinPolkadot.fastchainAuthoritySet()
+    ).toJSON() as null | { setId: number | string };
+    return current ? BigInt(current.setId) : null;
+}
+
+async function fetchLastFinalizedNumber(api: ApiPromise): Promise<bigint | null> {
+    const lastFinalized = (
+        await api.query.spinPolkadot.lastFinalized()
+    ).toJSON() as null | { number: number | string };
+    return lastFinalized ? BigInt(lastFinalized.number) : null;
+}
+
+// With several required attestations, an accepted proof may only record the
+// attestation of this relayer. Wait for the other relayers to complete the
+// quorum, until the parachain finalized fastchain block `number`.
+async function awaitAttestationQuorum(
+    parachain: ApiPromise,
+    signer: ReturnType<Keyring["addFromUri"]>,
+    number: bigint,
+) {
+    const account = parachain.createType("AccountId", signer.address).toString();
+    const deadline = Date.now() + QUORUM_TIMEOUT_MS;
+    for (;;) {
+        const lastFinalized = await fetchLastFinalizedNumber(parachain);
+        if (lastFinalized !== null && lastFinalized >= number) return;
+
+        const relayers = (
+            await parachain.query.spinPolkadot.relayers()
+        ).toJSON() as {
+            account: string;
+            attestation: null | [number | string, string];
+        }[];
+        const attestation = relayers.find(
+            (relayer) => relayer.account === account,
+        )?.attestation;
+        if (!attestation || BigInt(attestation[0]) !== number) {
+            throw new Error(
+                `Attestation of fastchain block ${number} is no longer pending`,
+            );
+        }
+        if (Date.now() >= deadline) {
+            throw new Error(
+                `No quorum attested fastchain block ${number} within ${QUORUM_TIMEOUT_MS}ms`,
+            );
+        }
+
+        logger.info(
+            { number: number.toString() },
+            "Waiting for other relayers to attest the hand-off block",
+        );
+        await sleep(QUORUM_POLL_INTERVAL_MS);
+    }
+}
+
+// The parachain only takes authority set changes from the digests of justified
+// fastchain headers. Until it trusts `setId`, forward the justification of the
+// block that handed off the set it trusts, together with that block's header.
+async function catchUpAuthoritySet(
+    fastchain: ApiPromise,
+    parachain: ApiPromise,
     signer: ReturnType<Keyring["addFromUri"]>,
     setId: bigint,
-    authorities: AuthorityTuple[],
 ) {
-    const currentRaw = await api.query.spinPolkadot.fastchainAuthoritySet();
-    const current = currentRaw.toJSON() as null | {
-        setId: number | string;
-        authorities: [string, string][];
-    };
+    for (;;) {
+        const currentSetId = await fetchParachainSetId(parachain);
+        if (currentSetId === null) {
+            throw new Error(
+                "Parachain authority set is not initialized; root has to set it",
+            );
+        }
+        if (currentSetId >= setId) return;
+
+        const lastFinalized = await fetchLastFinalizedNumber(parachain);
+        const from = lastFinalized !== null ? lastFinalized + 1n : 1n;
+
+        const proofU8a = await fetchFinalityProofAt(fastchain, from);
+        if (!proofU8a) {
+            throw new Error(`No finality proof for fastchain block ${from}`);
+        }
+        const justification = fastchain.registry.createType(
+            "GrandpaJustification",
+            proofU8a,
+        ) as unknown as DecodedGrandpaJustification;
+        const handoffHash =
+            justification.commit.targetHash.toHex() as HexString;
+        const handoffHeader = await fastchain.rpc.chain.getHeader(handoffHash);
 
-    const desired = formatAuthoritiesForParachain(authorities);
+        logger.info(
+            {
+                setId: currentSetId.toString(),
+                handoff: justification.commit.targetNumber.toString(),
+            },
+            "Handing off parachain authority set",
+        );
 
-    if (current) {
-        const currentSetId = BigInt(current.setId);
-        const existingTuples = normalizeAuthorities(
-            current.authorities.map(
-                ([idHex, weight]) => [idHex, BigInt(weight)] as AuthorityTuple,
-            ),
+        const label = `handoffAuthoritySet-${currentSetId.toString()}`;
+        const tx = parachain.tx.spinPolkadot.submitFinalityProof(
+            currentSetId.toString(),
+            proofU8a,
+            handoffHeader.toU8a(),
         );
-        if (
-            currentSetId === setId &&
-            authorityTuplesEqual(existingTuples, authorities)
-        ) {
-            return;
+        await withRetry(
+            label,
+            () => signAndSendAndWait(parachain, tx, signer, label),
+            { retryIf: isRetryableRpcError },
+        );
+        await awaitAttestationQuorum(
+            parachain,
+            signer,
+            BigInt(justification.commit.targetNumber.toString()),
+        );
+
+        if ((await fetchParachainSetId(parachain)) === currentSetId) {
+            throw new Error(
+                `Block ${handoffHash} did not hand off authority set ${currentSetId}`,
+            );
         }
     }
+}
 
-    logger.info(
-        { setId: setId.toString(), size: authorities.length },
-        "Updating parachain authority set",
-    );
+async function fetchRelayAuthoritySet(
+    api: ApiPromise,
+): Promise<{ setId: bigint; handedOffAt: bigint } | null> {
+    const current = (
+        await api.query.spinAnchoring.relayAuthoritySet()
+    ).toJSON() as null | {
+        setId: number | string;
+        handedOffAt: number | string;
+    };
+    return current
+        ? {
+              setId: BigInt(current.setId),
+              handedOffAt: BigInt(current.handedOffAt),
+          }
+        : null;
+}
 
-    const call = api.tx.spinPolkadot.setAuthoritySet(setId.toString(), desired);
-    await withRetry(
-        `setAuthoritySet-${setId.toString()}`,
-        () => signAndSendAndWait(api, call, signer, "setAuthoritySet"),
-        { retryIf: isRetryableRpcError },
-    );
+// The fastchain only takes relay chain authority set changes from the digests
+// of justified relay chain headers. Until it trusts `setId`, hand off the set
+// it trusts with the justification of the relay chain block that ended it.
+async function catchUpRelayAuthoritySet(
+    relaychain: ApiPromise,
+    fastchain: ApiPromise,
+    signer: ReturnType<Keyring["addFromUri"]>,
+    setId: bigint,
+) {
+    for (;;) {
+        const current = await fetchRelayAuthoritySet(fastchain);
+        if (current === null) {
+            throw new Error(
+                "Fastchain relay chain authority set is not initialized; root has to set it",
+            );
+        }
+        if (current.setId >= setId) return;
+
+        const from = current.handedOffAt + 1n;
+        const proofU8a = await fetchFinalityProofAt(relaychain, from);
+        if (!proofU8a) {
+            throw new Error(`No finality proof for relay chain block ${from}`);
+        }
+        const justification = relaychain.registry.createType(
+            "GrandpaJustification",
+            proofU8a,
+        ) as unknown as DecodedGrandpaJustification;
+        const handoffHash =
+            justification.commit.targetHash.toHex() as HexString;
+        const handoffHeader = await relaychain.rpc.chain.getHeader(handoffHash);
+
+        logger.info(
+            {
+                setId: current.setId.toString(),
+                handoff: justification.commit.targetNumber.toString(),
+            },
+            "Handing off fastchain relay chain authority set",
+        );
+
+        const label = `handOffRelayAuthoritySet-${current.setId.toString()}`;
+        const tx = fastchain.tx.spinAnchoring.handOffRelayAuthoritySet(
+            proofU8a,
+            handoffHeader.toU8a(),
+        );
+        await withRetry(
+            label,
+            () => signAndSendAndWait(fastchain, tx, signer, label),
+            { retryIf: isRetryableRpcError },
+        );
+    }
 }
 
 type Task = () => Promise<void>;
@@ -653,7 +749,7 @@ const fastchainCustomTypes = {
     },
 };
 
-type ChainName = "fastchain" | "parachain";
+type ChainName = "fastchain" | "parachain" | "relaychain";
 
 type SessionBreak = {
     chain: ChainName;
@@ -739,6 +835,7 @@ async function runRelayerSession() {
         types: fastchainCustomTypes,
     });
     const parachain = await connectApiWithRetry("parachain", PARACHAIN_WS);
+    const relaychain = await connectApiWithRetry("relaychain", RELAYCHAIN_WS);
 
     const keyring = new Keyring({ type: "sr25519" });
     const fastchainAccount = keyring.addFromUri(FASTCHAIN_SIGNER_URI, {
@@ -752,6 +849,7 @@ async function runRelayerSession() {
         {
             FASTCHAIN_WS,
             PARACHAIN_WS,
+            RELAYCHAIN_WS,
             fastchainSigner: fastchainAccount.address,
             parachainSigner: parachainAccount.address,
         },
@@ -759,14 +857,12 @@ async function runRelayerSession() {
     );
 
     const proofRunner = makeLatestRunner();
+    const anchorRunner = makeLatestRunner();
 
     // Serialize txs per chain/signer to prevent nonce collisions (fixes 1014 pool replacement)
     const parachainTxQ = makeSerialQueue();
     const fastchainTxQ = makeSerialQueue();
 
-    // Cache authorities by setId (authorities are stable within a set)
-    const authorityCache = new Map<bigint, AuthorityTuple[]>();
-
     const subscriptions: Unsubscribe[] = [];
     let shuttingDown = false;
 
@@ -786,6 +882,7 @@ async function runRelayerSession() {
 
         await Promise.allSettled([
             proofRunner.drain(),
+            anchorRunner.drain(),
             parachainTxQ.drain(),
             fastchainTxQ.drain(),
         ]);
@@ -793,6 +890,7 @@ async function runRelayerSession() {
         await Promise.allSettled([
             fastchain.disconnect(),
             parachain.disconnect(),
+            relaychain.disconnect(),
         ]);
 
         if (shutdownHandler === shutdown) {
@@ -802,35 +900,25 @@ async function runRelayerSession() {
 
     shutdownHandler = shutdown;
 
-    // Best-effort: prime parachain authority set to current fastchain set.
-    // (If it fails transiently, we continue; the next proof will fix it anyway.)
+    // The parachain authority set is bootstrapped by root; afterwards it
+    // follows the fastchain through the proofs this relayer submits.
     try {
-        const setId = BigInt(
-            (await fastchain.query.grandpa.currentSetId()).toString(),
-        );
-        const authorities = await fetchAuthorities(fastchain);
-        authorityCache.set(setId, authorities);
-
-        await parachainTxQ.run(async () => {
-            await ensureAuthoritySet(
-                parachain,
-                parachainAccount,
-                setId,
-                authorities,
+        if ((await fetchParachainSetId(parachain)) === null) {
+            logger.warn(
+                "Parachain authority set is not initialized; proofs will be rejected until root sets it",
             );
-        });
+        }
     } catch (err) {
         logger.warn(
             { err: formatError(err) },
-            "Initial authority set sync failed (will retry on next proof)",
+            "Failed to read the parachain authority set",
         );
     }
 
-    // Unified proof forwarding pipeline:
-    // - derive setId AT the target block hash
-    // - ensure parachain has that authority set
+    // Proof forwarding pipeline:
+    // - derive the setId that finalized the target block
+    // - hand the parachain authority set off up to that set
     // - submit proof
-    // - then noteAnchorVerified on fastchain
     const forwardProof = async (args: {
         upTo: bigint;
         targetHash: HexString;
@@ -838,14 +926,15 @@ async function runRelayerSession() {
     }) => {
         const { upTo, targetHash, proofU8a } = args;
 
-        // Derive the correct setId for THIS proof (fixes AuthoritySetMismatch)
-        const setIdAtTarget = await fetchSetIdAt(fastchain, targetHash);
-
-        let authorities = authorityCache.get(setIdAtTarget);
-        if (!authorities) {
-            authorities = await fetchAuthoritiesAt(fastchain, targetHash);
-            authorityCache.set(setIdAtTarget, authorities);
-        }
+        // The target header lets the parachain keep the verified state root
+        // and enact authority set changes signalled in its digest.
+        const targetHeader = await fastchain.rpc.chain.getHeader(targetHash);
+        // A block signalling a set change already reports the next set, so
+        // the set that finalized it is the one of its parent.
+        const setIdAtTarget = await fetchSetIdAt(
+            fastchain,
+            targetHeader.parentHash.toHex() as HexString,
+        );
 
         logger.info(
             {
@@ -859,12 +948,11 @@ async function runRelayerSession() {
 
         // Everything on parachain must be serialized to avoid nonce collisions
         await parachainTxQ.run(async () => {
-            // Ensure authority set matches what this proof requires
-            await ensureAuthoritySet(
+            await catchUpAuthoritySet(
+                fastchain,
                 parachain,
                 parachainAccount,
                 setIdAtTarget,
-                authorities!,
             );
 
             const label = `submitFinalityProof-${upTo.toString()}`;
@@ -872,6 +960,7 @@ async function runRelayerSession() {
                 const tx = parachain.tx.spinPolkadot.submitFinalityProof(
                     sid.toString(),
                     proofU8a,
+                    targetHeader.toU8a(),
                 );
 
                 // Diagnostics: compare decoded proof shape in both registries.
@@ -935,81 +1024,96 @@ async function runRelayerSession() {
                 );
             };
 
-            try {
-                await sendProof(setIdAtTarget);
-            } catch (err) {
-                if (!isAuthoritySetMismatch(err)) {
-                    throw err;
-                }
-
-                // Recovery path for edge cases:
-                // 1) refresh authorities at targetHash and retry
-                logger.warn(
-                    {
-                        err: formatError(err),
-                        setId: setIdAtTarget.toString(),
-                        targetHash,
-                    },
-                    "AuthoritySetMismatch: refreshing authority set at target hash and retrying once",
-                );
-
-                const freshSetId = await fetchSetIdAt(fastchain, targetHash);
-                const freshAuthorities = await fetchAuthoritiesAt(
-                    fastchain,
-                    targetHash,
-                );
-                authorityCache.set(freshSetId, freshAuthorities);
-
-                await ensureAuthoritySet(
-                    parachain,
-                    parachainAccount,
-                    freshSetId,
-                    freshAuthorities,
-                );
+            await sendProof(setIdAtTarget);
+        });
+    };
 
-                try {
-                    await sendProof(freshSetId);
-                    return;
-                } catch (err2) {
-                    if (!isAuthoritySetMismatch(err2)) throw err2;
+    // Anchoring pipeline, for each justified relay chain block:
+    // - read the parachain head in its state and the fastchain block the
+    //   parachain finalized as of that head
+    // - prove both with storage proofs
+    // - hand the fastchain relay chain authority set off up to the set that
+    //   finalized the relay chain block
+    // - then noteAnchorVerified on fastchain
+    const paraId = fastchain.consts.spinAnchoring.paraId.toString();
+    const anchorFromRelay = async (args: {
+        relayHash: HexString;
+        proofU8a: Uint8Array;
+    }) => {
+        const { relayHash, proofU8a } = args;
 
-                    // 2) last resort: try the parent hash set (transition boundary edge case)
-                    const header =
-                        await fastchain.rpc.chain.getHeader(targetHash);
-                    const parentHash = header.parentHash.toHex() as HexString;
+        const relayHeader = await relaychain.rpc.chain.getHeader(relayHash);
+        const setIdAtTarget = await fetchSetIdAt(
+            relaychain,
+            relayHeader.parentHash.toHex() as HexString,
+        );
 
-                    logger.warn(
-                        { err: formatError(err2), parentHash, targetHash },
-                        "AuthoritySetMismatch persists: trying parent hash authority set once",
-                    );
+        const paraHead = (await relaychain.query.paras.heads.at(
+            relayHash,
+            paraId,
+        )) as Option<Bytes>;
+        if (paraHead.isNone) {
+            logger.warn({ relayHash, paraId }, "No parachain head at relay block");
+            return;
+        }
+        const paraHeader = parachain.registry.createType(
+            "Header",
+            paraHead.unwrap().toU8a(true),
+        ) as Header;
+        const paraHash = paraHeader.hash.toHex() as HexString;
+
+        const lastFinalized = (
+            await parachain.query.spinPolkadot.lastFinalized.at(paraHash)
+        ).toJSON() as null | { number: number | string };
+        if (!lastFinalized) return;
+        const upTo = BigInt(lastFinalized.number);
+        const secureUpTo = BigInt(
+            (await fastchain.query.spinAnchoring.secureUpTo()).toString(),
+        );
+        if (upTo <= secureUpTo) {
+            logger.debug(
+                { upTo: upTo.toString(), secureUpTo: secureUpTo.toString() },
+                "Fastchain block already anchored",
+            );
+            return;
+        }
 
-                    const parentSetId = await fetchSetIdAt(
-                        fastchain,
-                        parentHash,
-                    );
-                    const parentAuthorities = await fetchAuthoritiesAt(
-                        fastchain,
-                        parentHash,
-                    );
-                    authorityCache.set(parentSetId, parentAuthorities);
+        const paraHeadProof = await relaychain.rpc.state.getReadProof(
+            [relaychain.query.paras.heads.key(paraId)],
+            relayHash,
+        );
+        const lastFinalizedProof = await parachain.rpc.state.getReadProof(
+            [parachain.query.spinPolkadot.lastFinalized.key()],
+            paraHash,
+        );
 
-                    await ensureAuthoritySet(
-                        parachain,
-                        parachainAccount,
-                        parentSetId,
-                        parentAuthorities,
-                    );
-                    await sendProof(parentSetId);
-                }
-            }
-        });
+        logger.info(
+            {
+                upTo: upTo.toString(),
+                relayNumber: relayHeader.number.toString(),
+                paraNumber: paraHeader.number.toString(),
+                setId: setIdAtTarget.toString(),
+            },
+            "Anchoring fastchain block",
+        );
 
-        // Fastchain txs also serialized (not strictly necessary here, but safe)
         await fastchainTxQ.run(async () => {
-            const label = `noteAnchorVerified-${upTo.toString()}`;
-            const tx = fastchain.tx.spinAnchoring.noteAnchorVerified(
-                upTo.toString(),
+            await catchUpRelayAuthoritySet(
+                relaychain,
+                fastchain,
+                fastchainAccount,
+                setIdAtTarget,
             );
+
+            const label = `noteAnchorVerified-${upTo.toString()}`;
+            // The fastchain verifies the relay chain justification and the
+            // storage proofs before counting the attestation.
+            const tx = fastchain.tx.spinAnchoring.noteAnchorVerified({
+                justification: proofU8a,
+                relayHeader: relayHeader.toU8a(),
+                paraHeadProof: paraHeadProof.proof,
+                lastFinalizedProof: lastFinalizedProof.proof,
+            });
             await withRetry(
                 label,
                 () =>
@@ -1019,6 +1123,36 @@ async function runRelayerSession() {
         });
     };
 
+    const unsubRelayJustifications = await subscribeJustificationStream(
+        relaychain,
+        (justification: Bytes | Uint8Array) => {
+            const proofU8a = justificationToU8a(justification);
+            if (!proofU8a) return;
+
+            let relayHash: HexString;
+            try {
+                relayHash = (
+                    relaychain.registry.createType(
+                        "GrandpaJustification",
+                        proofU8a,
+                    ) as unknown as DecodedGrandpaJustification
+                ).commit.targetHash.toHex() as HexString;
+            } catch (err) {
+                logger.warn(
+                    { err: formatError(err) },
+                    "Failed to decode relay chain GrandpaJustification",
+                );
+                return;
+            }
+
+            anchorRunner.enqueue(async () => {
+                await anchorFromRelay({ relayHash, proofU8a });
+            });
+        },
+    );
+    subscriptions.push(unsubRelayJustifications);
+    logger.info("Subscribed to relay chain GRANDPA justification stream");
+
     // Prefer justification stream. If unavailable, fallback to finalized heads + proveFinality.
     try {
         const unsubJustifications = await subscribeJustificationStream(
@@ -1103,6 +1237,7 @@ async function runRelayerSession() {
     const sessionBreak = await Promise.race([
         waitForApiBreak(fastchain, "fastchain"),
         waitForApiBreak(parachain, "parachain"),
+        waitForApiBreak(relaychain, "relaychain"),
     ]);
 
     if (!shuttingDown && !stopRequested) {
@@ -28,9 +28,10 @@ use alloc::vec::Vec;
 
 use polkadot_sdk::{staging_parachain_info as parachain_info, *};
 
-use cumulus_primitives_core::ParaId;
+use cumulus_primitives_core::{relay_chain::BlockNumber as RelayBlockNumber, ParaId};
 use frame_support::{
 	genesis_builder_helper::{build_state, get_preset},
+	traits::Get,
 	weights::Weight,
 };
 use pallet_aura::Authorities;
@@ -47,8 +48,8 @@ use sp_version::RuntimeVersion;
 // Local module imports
 use super::{
 	AccountId, Balance, Block, ConsensusHook, Executive, InherentDataExt, Nonce, ParachainSystem,
-	Runtime, RuntimeCall, RuntimeGenesisConfig, SessionKeys, System, TransactionPayment,
-	SLOT_DURATION, VERSION,
+	Runtime, RuntimeCall, RuntimeGenesisConfig, SessionKeys, SpinPolkadot, System,
+	TransactionPayment, SLOT_DURATION, VERSION,
 };
 
 // we move some impls outside so we can easily use them with `docify`.
@@ -238,6 +239,44 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl
+		qfp_spin_polkadot::SpinPolkadotApi<
+			Block,
+			AccountId,
+			Balance,
+			qf_runtime::BlockNumber,
+			qf_runtime::Hash,
+			RelayBlockNumber,
+		> for Runtime
+	{
+		fn mode() -> qfp_spin_polkadot::Mode {
+			SpinPolkadot::mode()
+		}
+
+		fn last_heartbeat() -> Option<RelayBlockNumber> {
+			SpinPolkadot::last_heartbeat()
+		}
+
+		fn heartbeat_timeout() -> RelayBlockNumber {
+			<Runtime as pallet_spin_polkadot::Config>::HeartbeatTimeout::get()
+		}
+
+		fn relayers() -> Vec<
+			qfp_spin_polkadot::RelayerStatus<
+				AccountId,
+				Balance,
+				qf_runtime::BlockNumber,
+				qf_runtime::Hash,
+			>,
+		> {
+			SpinPolkadot::relayers()
+		}
+
+		fn required_attestations() -> u32 {
+			SpinPolkadot::required_attestations()
+		}
+	}
+
 	#[cfg(feature = "try-runtime")]
 	impl frame_try_runtime::TryRuntime<Block> for Runtime {
 		fn on_runtime_upgrade(checks: frame_try_runtime::UpgradeCheckSelect) -> (Weight, Weight) {
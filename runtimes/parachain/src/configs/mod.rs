@@ -30,7 +30,7 @@ use polkadot_sdk::{staging_parachain_info as parachain_info, staging_xcm as xcm,
 use polkadot_sdk::{staging_xcm_builder as xcm_builder, staging_xcm_executor as xcm_executor};
 
 // Substrate and Polkadot dependencies
-use cumulus_pallet_parachain_system::RelayNumberMonotonicallyIncreases;
+use cumulus_pallet_parachain_system::{RelayNumberMonotonicallyIncreases, RelaychainDataProvider};
 use cumulus_primitives_core::{AggregateMessageOrigin, ParaId};
 use frame_support::{
 	derive_impl,
@@ -63,7 +63,8 @@ use super::{
 	MessageQueue, Nonce, PalletInfo, ParachainSystem, Runtime, RuntimeCall, RuntimeEvent,
 	RuntimeFreezeReason, RuntimeHoldReason, RuntimeOrigin, RuntimeTask, Session, SessionKeys,
 	System, WeightToFee, XcmpQueue, AVERAGE_ON_INITIALIZE_RATIO, EXISTENTIAL_DEPOSIT,
-	MAXIMUM_BLOCK_WEIGHT, MICRO_UNIT, MINUTES, NORMAL_DISPATCH_RATIO, SLOT_DURATION, VERSION,
+	MAXIMUM_BLOCK_WEIGHT, MICRO_UNIT, MINUTES, NORMAL_DISPATCH_RATIO,
+	RELAY_CHAIN_SLOT_DURATION_MILLIS, SLOT_DURATION, UNIT, VERSION,
 };
 use xcm_config::{RelayLocation, XcmOriginToTransactDispatchOrigin};
 
@@ -328,6 +329,23 @@ impl pallet_collator_selection::Config for Runtime {
 	type WeightInfo = ();
 }
 
+parameter_types! {
+	pub const RelayerBond: Balance = 1_000 * UNIT;
+	/// Ten minutes of relay-chain blocks.
+	pub const HeartbeatTimeout: cumulus_primitives_core::relay_chain::BlockNumber =
+		10 * 60_000 / RELAY_CHAIN_SLOT_DURATION_MILLIS;
+}
+
 impl pallet_spin_polkadot::Config for Runtime {
 	type AnchoredChainHeader = qf_runtime::Header;
+	/// Matches the GRANDPA authorities cap of the fastchain.
+	type MaxAuthorities = ConstU32<32>;
+	type RelayBlockNumberProvider = RelaychainDataProvider<Runtime>;
+	type HeartbeatTimeout = HeartbeatTimeout;
+	type Currency = Balances;
+	type RuntimeHoldReason = RuntimeHoldReason;
+	type RelayerBond = RelayerBond;
+	type MaxRelayers = ConstU32<16>;
+	type Slash = ();
+	type WeightInfo = ();
 }
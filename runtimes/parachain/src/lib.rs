@@ -11,6 +11,7 @@ pub mod apis;
 mod benchmarks;
 pub mod configs;
 mod genesis_config_presets;
+mod migrations;
 mod weights;
 
 extern crate alloc;
@@ -99,7 +100,10 @@ pub type UncheckedExtrinsic =
 ///
 /// This can be a tuple of types, each implementing `OnRuntimeUpgrade`.
 #[allow(unused_parens)]
-type Migrations = ();
+type Migrations = (
+	migrations::pallet_spin_polkadot_bounded_storage_migration::MigrateToBoundedStorage,
+	migrations::pallet_spin_polkadot_relayers_migration::MigrateToRelayerSet,
+);
 
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
@@ -173,7 +177,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: alloc::borrow::Cow::Borrowed("qf-para"),
 	impl_name: alloc::borrow::Cow::Borrowed("theqfnetwork"),
 	authoring_version: 1,
-	spec_version: 3,
+	spec_version: 4,
 	impl_version: 0,
 	apis: apis::RUNTIME_API_VERSIONS,
 	transaction_version: 1,
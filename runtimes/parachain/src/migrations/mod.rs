@@ -0,0 +1,2 @@
+pub mod pallet_spin_polkadot_bounded_storage_migration;
+pub mod pallet_spin_polkadot_relayers_migration;
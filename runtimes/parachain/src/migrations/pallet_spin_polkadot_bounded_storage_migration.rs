@@ -0,0 +1,158 @@
+/// Migration to the bounded storage of `pallet_spin_polkadot`.
+///
+/// Bounded authority lists encode like the unbounded ones, so the authority set
+/// and a pending change are kept unless they have more authorities than
+/// `MaxAuthorities`. An oversized authority set is dropped and root has to set
+/// it anew. The last justification is kept without its votes ancestries, or
+/// dropped if it has more precommits than `MaxAuthorities`.
+///
+/// The migration runs once, taking the pallet from storage version 0 to 1.
+use polkadot_sdk::*;
+
+use codec::Decode;
+use frame_support::{
+	migrations::VersionedMigration, traits::UncheckedOnRuntimeUpgrade, weights::Weight, BoundedVec,
+};
+use pallet_spin_polkadot::{
+	AnchoredNumberOf, AuthoritySetData, BoundedGrandpaJustification, FastchainAuthoritySet,
+	LastJustification, PendingAuthoritySet, PendingAuthoritySetChange, StoredJustification,
+};
+use sp_consensus_grandpa::{AuthorityList, SetId};
+
+#[cfg(feature = "try-runtime")]
+use alloc::vec::Vec;
+#[cfg(feature = "try-runtime")]
+use codec::Encode;
+#[cfg(feature = "try-runtime")]
+use frame_support::ensure;
+#[cfg(feature = "try-runtime")]
+use sp_runtime::TryRuntimeError;
+
+use crate::Runtime;
+
+const LOG_TARGET: &str = "runtime::migrations::spin_polkadot_bounded_storage";
+
+/// The authority set as it was stored before the authority lists were bounded.
+#[derive(Decode)]
+struct LegacyAuthoritySetData {
+	set_id: SetId,
+	authorities: AuthorityList,
+}
+
+/// A pending change as it was stored before the authority lists were bounded.
+#[derive(Decode)]
+struct LegacyPendingAuthoritySet {
+	authorities: AuthorityList,
+	enact_at: AnchoredNumberOf<Runtime>,
+}
+
+/// The justification as it was stored before it was bounded.
+type LegacyJustification = BoundedGrandpaJustification<qf_runtime::Header>;
+
+pub type MigrateToBoundedStorage = VersionedMigration<
+	0,
+	1,
+	UncheckedMigrateToBoundedStorage,
+	pallet_spin_polkadot::Pallet<Runtime>,
+	<Runtime as frame_system::Config>::DbWeight,
+>;
+
+pub struct UncheckedMigrateToBoundedStorage;
+
+impl UncheckedOnRuntimeUpgrade for UncheckedMigrateToBoundedStorage {
+	fn on_runtime_upgrade() -> Weight {
+		let authority_set = FastchainAuthoritySet::<Runtime>::translate(
+			|legacy: Option<LegacyAuthoritySetData>| {
+				let legacy = legacy?;
+				match BoundedVec::try_from(legacy.authorities) {
+					Ok(authorities) =>
+						Some(AuthoritySetData { set_id: legacy.set_id, authorities }),
+					Err(authorities) => {
+						log::warn!(
+							target: LOG_TARGET,
+							"Dropping authority set {} of {} authorities; root has to set it anew",
+							legacy.set_id,
+							authorities.len(),
+						);
+						None
+					},
+				}
+			},
+		);
+		if authority_set.is_err() {
+			log::error!(target: LOG_TARGET, "Failed to decode the legacy authority set");
+		}
+
+		let pending = PendingAuthoritySetChange::<Runtime>::translate(
+			|legacy: Option<LegacyPendingAuthoritySet>| {
+				let legacy = legacy?;
+				let authorities = BoundedVec::try_from(legacy.authorities).ok();
+				if authorities.is_none() {
+					log::warn!(target: LOG_TARGET, "Dropping oversized pending authority set");
+				}
+				Some(PendingAuthoritySet { authorities: authorities?, enact_at: legacy.enact_at })
+			},
+		);
+		if pending.is_err() {
+			log::error!(target: LOG_TARGET, "Failed to decode the legacy pending authority set");
+		}
+
+		// The last justification is informational only, so it is dropped when
+		// it cannot be kept.
+		let justification =
+			LastJustification::<Runtime>::translate(|legacy: Option<LegacyJustification>| {
+				legacy.and_then(|legacy| StoredJustification::bounded(&legacy))
+			});
+		if justification.is_err() {
+			LastJustification::<Runtime>::kill();
+		}
+
+		log::info!(
+			target: LOG_TARGET,
+			"Migrated to bounded storage: authority_set={}, pending_change={}, justification={}",
+			matches!(authority_set, Ok(Some(_))),
+			matches!(pending, Ok(Some(_))),
+			matches!(justification, Ok(Some(_))),
+		);
+
+		<Runtime as frame_system::Config>::DbWeight::get().reads_writes(3, 3)
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+		let set_id =
+			FastchainAuthoritySet::<Runtime>::get().map(|authority_set| authority_set.set_id);
+		log::info!(target: LOG_TARGET, "Pre-upgrade authority set id: {:?}", set_id);
+		Ok(set_id.encode())
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+		let set_id: Option<SetId> = Decode::decode(&mut state.as_slice())
+			.map_err(|_| "Failed to decode pre-upgrade authority set id")?;
+		let authority_set = FastchainAuthoritySet::<Runtime>::get();
+		log::info!(
+			target: LOG_TARGET,
+			"Post-upgrade authority set: set_id={:?}, authorities={:?}",
+			authority_set.as_ref().map(|authority_set| authority_set.set_id),
+			authority_set.as_ref().map(|authority_set| authority_set.authorities.len()),
+		);
+
+		ensure!(
+			authority_set.map_or(true, |authority_set| Some(authority_set.set_id) == set_id),
+			"authority set id changed by the migration"
+		);
+		ensure!(
+			!PendingAuthoritySetChange::<Runtime>::exists() ||
+				PendingAuthoritySetChange::<Runtime>::get().is_some(),
+			"pending authority set change does not decode after migration"
+		);
+		ensure!(
+			!LastJustification::<Runtime>::exists() ||
+				LastJustification::<Runtime>::get().is_some(),
+			"last justification does not decode after migration"
+		);
+
+		Ok(())
+	}
+}
@@ -0,0 +1,97 @@
+/// Migration to move the single trusted relayer of `pallet_spin_polkadot` into
+/// its bonded relayer set.
+///
+/// The relayer is registered like any other, so the relayer bond is held from
+/// its account. If it cannot afford the bond it is dropped and root has to
+/// register relayers anew.
+///
+/// The migration runs once, taking the pallet from storage version 1 to 2.
+use polkadot_sdk::*;
+
+use frame_support::{
+	migrations::VersionedMigration, storage_alias, traits::UncheckedOnRuntimeUpgrade,
+	weights::Weight,
+};
+
+#[cfg(feature = "try-runtime")]
+use alloc::vec::Vec;
+#[cfg(feature = "try-runtime")]
+use codec::{Decode, Encode};
+#[cfg(feature = "try-runtime")]
+use frame_support::ensure;
+#[cfg(feature = "try-runtime")]
+use sp_runtime::TryRuntimeError;
+
+use crate::{AccountId, Runtime, SpinPolkadot};
+
+const LOG_TARGET: &str = "runtime::migrations::spin_polkadot_relayers";
+
+/// The relayer as it was stored before the relayer set was introduced.
+#[storage_alias]
+type Relayer = StorageValue<SpinPolkadot, AccountId>;
+
+pub type MigrateToRelayerSet = VersionedMigration<
+	1,
+	2,
+	UncheckedMigrateToRelayerSet,
+	pallet_spin_polkadot::Pallet<Runtime>,
+	<Runtime as frame_system::Config>::DbWeight,
+>;
+
+pub struct UncheckedMigrateToRelayerSet;
+
+impl UncheckedOnRuntimeUpgrade for UncheckedMigrateToRelayerSet {
+	fn on_runtime_upgrade() -> Weight {
+		let db_weight = <Runtime as frame_system::Config>::DbWeight::get();
+		let Some(relayer) = Relayer::take() else {
+			log::info!(target: LOG_TARGET, "Skipping relayer migration; no legacy relayer");
+			return db_weight.reads(1);
+		};
+
+		match SpinPolkadot::do_add_relayer(relayer.clone()) {
+			Ok(()) => log::info!(
+				target: LOG_TARGET,
+				"Registered legacy relayer {:?} in the relayer set",
+				relayer,
+			),
+			Err(e) => log::warn!(
+				target: LOG_TARGET,
+				"Dropping legacy relayer {:?}, registering it failed: {:?}",
+				relayer,
+				e,
+			),
+		}
+		pallet_spin_polkadot::RequiredAttestations::<Runtime>::mutate(|required| {
+			*required = (*required).max(1)
+		});
+
+		// The legacy relayer, the relayer set, the hold of the bond and the
+		// required attestations.
+		db_weight.reads_writes(5, 5)
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+		let relayer = Relayer::get();
+		log::info!(target: LOG_TARGET, "Pre-upgrade legacy relayer: {:?}", relayer);
+		Ok(relayer.encode())
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+		let legacy: Option<AccountId> = Decode::decode(&mut state.as_slice())
+			.map_err(|_| "Failed to decode pre-upgrade legacy relayer")?;
+		let relayers = SpinPolkadot::relayers();
+		log::info!(
+			target: LOG_TARGET,
+			"Post-upgrade relayer set: relayers={}, legacy_registered={}",
+			relayers.len(),
+			legacy.as_ref().is_some_and(|legacy| relayers.iter().any(|r| r.account == *legacy)),
+		);
+
+		ensure!(!Relayer::exists(), "legacy relayer still stored after migration");
+		ensure!(SpinPolkadot::required_attestations() >= 1, "no attestations required");
+
+		Ok(())
+	}
+}
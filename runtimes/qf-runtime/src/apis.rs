@@ -31,7 +31,8 @@ use frame_support::{
 };
 use pallet_grandpa::AuthorityId as GrandpaId;
 use pallet_revive::impl_runtime_apis_plus_revive_traits;
-use qfp_consensus_spin::{sr25519::AuthorityId as SpinId, SpinAuxData};
+use qfp_consensus_spin::{sr25519::AuthorityId as SpinId, AuthorityLiveness, SpinAuxData};
+use qfp_spin_anchoring::{AnchorRecord, RelayerStatus};
 use sp_api::impl_runtime_apis;
 use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use sp_runtime::{
@@ -43,9 +44,9 @@ use sp_version::RuntimeVersion;
 
 // Local module imports
 use super::{
-	AccountId, Balance, Block, BlockNumber, Executive, Grandpa, InherentDataExt, Nonce, Runtime,
-	RuntimeCall, RuntimeGenesisConfig, SessionKeys, Spin, Staking, System, TransactionPayment,
-	VERSION,
+	AccountId, Balance, Block, BlockNumber, Executive, Grandpa, Hash, Historical, InherentDataExt,
+	Nonce, Runtime, RuntimeCall, RuntimeGenesisConfig, SessionKeys, Spin, SpinAnchoring, Staking,
+	System, TransactionPayment, VERSION,
 };
 
 impl_runtime_apis_plus_revive_traits!(
@@ -129,6 +130,96 @@ impl_runtime_apis_plus_revive_traits!(
 		}
 	}
 
+	impl qfp_consensus_spin::SpinFallbackApi<Block> for Runtime {
+		fn fallback_after() -> u32 {
+			Spin::fallback_after()
+		}
+	}
+
+	impl qfp_consensus_spin::SpinMultiBlockApi<Block> for Runtime {
+		fn allow_multiple_blocks_per_slot() -> bool {
+			Spin::allow_multiple_blocks_per_slot()
+		}
+	}
+
+	impl qfp_consensus_spin::SpinSlotScheduleApi<Block> for Runtime {
+		fn slot_schedule() -> qfp_consensus_spin::SlotSchedule {
+			Spin::slot_schedule()
+		}
+
+		fn pending_slot_schedule() -> Option<qfp_consensus_spin::SlotSchedule> {
+			Spin::pending_slot_schedule()
+		}
+	}
+
+	impl qfp_consensus_spin::SpinScheduledChangeApi<Block, SpinId, NumberFor<Block>> for Runtime {
+		fn pending_change() -> Option<qfp_consensus_spin::PendingChange<SpinId, NumberFor<Block>>> {
+			Spin::pending_change()
+		}
+	}
+
+	impl qfp_consensus_spin::SpinLivenessApi<Block, SpinId> for Runtime {
+		fn session_liveness() -> Vec<(SpinId, AuthorityLiveness)> {
+			Spin::session_liveness()
+		}
+	}
+
+	impl qfp_consensus_spin::SpinEquivocationApi<Block, SpinId> for Runtime {
+		fn submit_report_equivocation_unsigned_extrinsic(
+			equivocation_proof: qfp_consensus_spin::SpinEquivocationProof<
+				<Block as BlockT>::Header,
+				SpinId,
+			>,
+			key_owner_proof: qfp_consensus_spin::OpaqueKeyOwnershipProof,
+		) -> Option<()> {
+			let key_owner_proof = key_owner_proof.decode()?;
+
+			Spin::submit_unsigned_equivocation_report(equivocation_proof, key_owner_proof)
+		}
+
+		fn generate_key_ownership_proof(
+			_slot: qfp_consensus_spin::Slot,
+			authority_id: SpinId,
+		) -> Option<qfp_consensus_spin::OpaqueKeyOwnershipProof> {
+			use codec::Encode;
+			use frame_support::traits::KeyOwnerProofSystem;
+
+			Historical::prove((qfp_consensus_spin::KEY_TYPE, authority_id))
+				.map(|p| p.encode())
+				.map(qfp_consensus_spin::OpaqueKeyOwnershipProof::new)
+		}
+	}
+
+	impl qfp_spin_anchoring::SpinAnchoringApi<Block, AccountId, Balance, BlockNumber> for Runtime {
+		fn secure_up_to() -> BlockNumber {
+			SpinAnchoring::secure_up_to()
+		}
+
+		fn relayers() -> Vec<RelayerStatus<AccountId, Balance, BlockNumber, Hash>> {
+			SpinAnchoring::relayers()
+		}
+
+		fn required_attestations() -> u32 {
+			SpinAnchoring::required_attestations()
+		}
+
+		fn is_secure(block_number: BlockNumber) -> bool {
+			SpinAnchoring::is_secure(block_number)
+		}
+
+		fn is_anchored(block_number: BlockNumber, hash: Hash) -> bool {
+			SpinAnchoring::is_anchored(block_number, hash)
+		}
+
+		fn anchor_lag() -> BlockNumber {
+			SpinAnchoring::anchor_lag()
+		}
+
+		fn anchor_covering(block_number: BlockNumber) -> Option<AnchorRecord<BlockNumber, Hash>> {
+			SpinAnchoring::anchor_covering(block_number)
+		}
+	}
+
 	impl pallet_staking_runtime_api::StakingApi<Block, Balance, AccountId> for Runtime {
 		fn nominations_quota(balance: Balance) -> u32 {
 			Staking::api_nominations_quota(balance)
@@ -65,10 +65,12 @@ use crate::GENESIS_NEXT_ASSET_ID;
 
 // Local module imports
 use super::{
-	deposit, AccountId, Address, Balance, Balances, Block, BlockNumber, EthExtraImpl, Hash, Nonce,
-	PalletInfo, Runtime, RuntimeCall, RuntimeEvent, RuntimeFreezeReason, RuntimeHoldReason,
-	RuntimeOrigin, RuntimeTask, Session, SessionKeys, Signature, Spin, Staking, System, Timestamp,
-	Vesting, VoterList, EXISTENTIAL_DEPOSIT, SESSION_LENGTH, SLOT_DURATION, VERSION,
+	deposit, AccountId, Address, Balance, Balances, Block, BlockNumber, EthExtraImpl, Hash, Header,
+	Historical, Nonce, Offences, PalletInfo, Runtime, RuntimeCall, RuntimeEvent,
+	RuntimeFreezeReason, RuntimeHoldReason, RuntimeOrigin, RuntimeTask, Session, SessionKeys,
+	Signature, Spin, Staking, System, Timestamp, UncheckedExtrinsic, Vesting, VoterList,
+	EXISTENTIAL_DEPOSIT, FALLBACK_AFTER_SLOTS, MINUTES, SESSION_LENGTH, SLOT_DURATION,
+	SLOT_DURATION_CHANGE_DELAY, UNIT, VERSION,
 };
 
 const NORMAL_DISPATCH_RATIO: Perbill = Perbill::from_percent(75);
@@ -187,13 +189,30 @@ impl pallet_authorship::Config for Runtime {
 	type EventHandler = Staking;
 }
 
+parameter_types! {
+	/// Equivocation reports stay valid in the pool for as long as the offender's stake is bonded.
+	pub const ReportLongevity: u64 = BondingDuration::get() as u64 *
+		SessionsPerEra::get() as u64 *
+		SESSION_LENGTH * LEADER_TENURES_PER_SESSION as u64;
+}
+
 impl pallet_spin::Config for Runtime {
 	type AuthorityId = SpinId;
-	type DisabledValidators = ();
+	type DisabledValidators = Session;
 	type MaxAuthorities = ConstU32<32>;
 	type AllowMultipleBlocksPerSlot = ConstBool<false>;
 	type SlotDuration = pallet_spin::MinimumPeriodTimesTwo<Runtime>;
+	type SlotDurationChangeDelay = ConstU64<SLOT_DURATION_CHANGE_DELAY>;
 	type DefaultSessionLength = ConstU64<SESSION_LENGTH>;
+	type NextSessionRotation = Session;
+	type LeaderSelection = pallet_spin::RoundRobin;
+	type MaxLeaderTenures = ConstU32<128>;
+	type FallbackAfterSlots = ConstU32<FALLBACK_AFTER_SLOTS>;
+	type KeyOwnerProof = sp_session::MembershipProof;
+	type EquivocationReportSystem =
+		pallet_spin::EquivocationReportSystem<Self, Offences, Historical, ReportLongevity>;
+	type ValidatorSet = Historical;
+	type ReportUnresponsiveness = Offences;
 }
 
 pub const LEADER_TENURES_PER_SESSION: u32 = 30;
@@ -223,11 +242,40 @@ impl pallet_session::Config for Runtime {
 	type Keys = SessionKeys;
 	type DisablingStrategy = pallet_session::disabling::UpToLimitWithReEnablingDisablingStrategy;
 	type WeightInfo = pallet_session::weights::SubstrateWeight<Runtime>;
-	type SessionManager = Staking;
+	type SessionManager = pallet_session::historical::NoteHistoricalRoot<Self, Staking>;
 	type Currency = Balances;
 	type KeyDeposit = ();
 }
 
+impl pallet_session::historical::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type FullIdentification = sp_staking::Exposure<AccountId, Balance>;
+	type FullIdentificationOf = pallet_staking::DefaultExposureOf<Self>;
+}
+
+impl pallet_offences::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type IdentificationTuple = pallet_session::historical::IdentificationTuple<Self>;
+	type OnOffenceHandler = Staking;
+}
+
+impl<LocalCall> frame_system::offchain::CreateTransactionBase<LocalCall> for Runtime
+where
+	RuntimeCall: From<LocalCall>,
+{
+	type Extrinsic = UncheckedExtrinsic;
+	type RuntimeCall = RuntimeCall;
+}
+
+impl<LocalCall> frame_system::offchain::CreateBare<LocalCall> for Runtime
+where
+	RuntimeCall: From<LocalCall>,
+{
+	fn create_bare(call: RuntimeCall) -> UncheckedExtrinsic {
+		sp_runtime::generic::UncheckedExtrinsic::new_bare(call).into()
+	}
+}
+
 parameter_types! {
 	pub const MaxElectingVoters: u32 = 22_500;
 	/// We take the top 22500 nominators as electing voters and all of the validators as electable
@@ -303,7 +351,7 @@ impl pallet_staking::Config for Runtime {
 	type BondingDuration = BondingDuration;
 	type SlashDeferDuration = SlashDeferDuration;
 	type AdminOrigin = EnsureRoot<AccountId>;
-	type SessionInterface = ();
+	type SessionInterface = Self;
 	/// Defines how the total inflation per era is computed
 	/// and split between validators and the system
 	type EraPayout = pallet_staking::ConvertCurve<RewardCurve>;
@@ -732,7 +780,31 @@ impl pallet_utility::Config for Runtime {
 	type WeightInfo = pallet_utility::weights::SubstrateWeight<Runtime>;
 }
 
-impl pallet_spin_anchoring::Config for Runtime {}
+parameter_types! {
+	pub const RelayerBond: Balance = 1_000 * UNIT;
+	/// The id of the QF parachain on Polkadot, in storage so that test
+	/// networks can anchor to their own parachain.
+	pub storage AnchoringParaId: u32 = 3426;
+	pub const BridgePalletName: &'static str = "SpinPolkadot";
+}
+
+impl pallet_spin_anchoring::Config for Runtime {
+	// the relay chain and the parachain number and hash their blocks as this
+	// chain does.
+	type RelayChainHeader = Header;
+	type ParachainHeader = Header;
+	type ParaId = AnchoringParaId;
+	type BridgePalletName = BridgePalletName;
+	type MaxAuthorities = ConstU32<1_024>;
+	type Currency = Balances;
+	type RuntimeHoldReason = RuntimeHoldReason;
+	type RelayerBond = RelayerBond;
+	type MaxRelayers = ConstU32<16>;
+	type Slash = ();
+	type MaxAnchors = ConstU32<4096>;
+	type StallThreshold = ConstU32<{ 10 * MINUTES }>;
+	type WeightInfo = ();
+}
 
 parameter_types! {
 	pub UnvestedFundsAllowedWithdrawReasons: WithdrawReasons =
@@ -70,7 +70,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	// The version of the runtime specification. A full node will not attempt to use its native
 	// runtime in substitute for the on-chain Wasm runtime unless all of `spec_name`,
 	// `spec_version`, and `authoring_version` are the same between Wasm and native.
-	spec_version: 120,
+	spec_version: 129,
 	impl_version: 0,
 	apis: apis::RUNTIME_API_VERSIONS,
 	transaction_version: 10,
@@ -87,8 +87,9 @@ mod block_times {
 	/// Change this to adjust the block time.
 	pub const MILLI_SECS_PER_BLOCK: u64 = 100;
 
-	// NOTE: Currently it is not possible to change the slot duration after the
-	// chain has started. Attempting to do so will brick block production.
+	// NOTE: Changing this after the chain has started will brick block
+	// production. Schedule a new slot duration with `Spin::set_slot_duration`
+	// instead, it cannot be shorter than this one.
 	pub const SLOT_DURATION: u64 = MILLI_SECS_PER_BLOCK;
 }
 pub use block_times::*;
@@ -119,6 +120,14 @@ pub const EXISTENTIAL_DEPOSIT: Balance = MILLI_UNIT;
 /// Session length in blocks
 pub const SESSION_LENGTH: BlockNumber = 1 * MINUTES;
 
+/// Empty slots after which the secondary author may stand in for an offline
+/// leader, 3 seconds.
+pub const FALLBACK_AFTER_SLOTS: u32 = (3_000 / MILLI_SECS_PER_BLOCK) as u32;
+
+/// Slots between scheduling a change of the slot duration and its activation,
+/// 10 minutes at the genesis slot duration.
+pub const SLOT_DURATION_CHANGE_DELAY: u64 = 10 * MINUTES;
+
 /// Initial asset ID in pallet-assets.
 pub const GENESIS_NEXT_ASSET_ID: Option<u32> = Some(1);
 
@@ -210,7 +219,10 @@ pub type SignedPayload = generic::SignedPayload<RuntimeCall, TxExtension>;
 ///
 /// This can be a tuple of types, each implementing `OnRuntimeUpgrade`.
 #[allow(unused_parens)]
-type Migrations = ();
+type Migrations = (
+	migrations::pallet_session_spin_keys_migration::MigrateSpinSessionKeys,
+	migrations::pallet_spin_anchoring_relayers_migration::MigrateToRelayerSet,
+);
 
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
@@ -279,6 +291,10 @@ mod runtime {
 	pub type Staking = pallet_staking;
 	#[runtime::pallet_index(41)]
 	pub type VoterList = pallet_bags_list;
+	#[runtime::pallet_index(42)]
+	pub type Offences = pallet_offences;
+	#[runtime::pallet_index(43)]
+	pub type Historical = pallet_session::historical;
 
 	// Governance
 	#[runtime::pallet_index(51)]
@@ -1 +1,3 @@
+pub mod pallet_session_spin_keys_migration;
+pub mod pallet_spin_anchoring_relayers_migration;
 pub mod pallet_staking_voterlist_migration;
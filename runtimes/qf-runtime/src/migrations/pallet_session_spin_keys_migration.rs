@@ -0,0 +1,133 @@
+/// Migration to move the SPIN session keys from the AURA `KeyTypeId` to the
+/// dedicated SPIN one.
+///
+/// The encoding of `SessionKeys` does not change, but `pallet_session` indexes
+/// key owners by `(KeyTypeId, raw public key)`, so the ownership records of
+/// every registered SPIN key have to be re-mapped.
+use frame_support::{traits::OnRuntimeUpgrade, weights::Weight};
+use qfp_consensus_spin::{
+	legacy::sr25519::AuthorityId as LegacySpinId, sr25519::AuthorityId as SpinId, KEY_TYPE,
+};
+use sp_core::crypto::key_types::AURA;
+use sp_runtime::{impl_opaque_keys, BoundToRuntimeAppPublic};
+
+#[cfg(feature = "try-runtime")]
+use alloc::vec::Vec;
+#[cfg(feature = "try-runtime")]
+use codec::{Decode, Encode};
+#[cfg(feature = "try-runtime")]
+use frame_support::ensure;
+#[cfg(feature = "try-runtime")]
+use sp_runtime::TryRuntimeError;
+
+use crate::{Grandpa, Runtime, Session, SessionKeys};
+
+const LOG_TARGET: &str = "runtime::migrations::spin_keys";
+
+/// SPIN keys as they were bound before the dedicated key type was introduced.
+pub struct LegacySpin;
+
+impl BoundToRuntimeAppPublic for LegacySpin {
+	type Public = LegacySpinId;
+}
+
+impl_opaque_keys! {
+	pub struct LegacySessionKeys {
+		pub spin: LegacySpin,
+		pub grandpa: Grandpa,
+	}
+}
+
+pub struct MigrateSpinSessionKeys;
+
+impl MigrateSpinSessionKeys {
+	/// Number of validators whose SPIN key is still owned under the AURA key type.
+	fn legacy_key_owners() -> u32 {
+		pallet_session::NextKeys::<Runtime>::iter_values()
+			.filter(|keys| Session::key_owner(AURA, keys.spin.as_ref()).is_some())
+			.count() as u32
+	}
+
+	fn upgrade(_: crate::AccountId, old: LegacySessionKeys) -> SessionKeys {
+		SessionKeys {
+			spin: SpinId::from(sp_core::sr25519::Public::from(old.spin)),
+			grandpa: old.grandpa,
+		}
+	}
+}
+
+impl OnRuntimeUpgrade for MigrateSpinSessionKeys {
+	fn on_runtime_upgrade() -> Weight {
+		let db_weight = <Runtime as frame_system::Config>::DbWeight::get();
+		let validators = pallet_session::NextKeys::<Runtime>::iter_keys().count() as u64;
+		let legacy = Self::legacy_key_owners();
+
+		if legacy == 0 {
+			log::info!(
+				target: LOG_TARGET,
+				"Skipping SPIN session keys migration; no keys registered under AURA",
+			);
+			return db_weight.reads(validators.saturating_mul(2));
+		}
+
+		log::info!(
+			target: LOG_TARGET,
+			"Re-mapping SPIN session keys: validators={}, legacy_owners={}",
+			validators,
+			legacy,
+		);
+
+		Session::upgrade_keys::<LegacySessionKeys, _>(Self::upgrade);
+
+		log::info!(
+			target: LOG_TARGET,
+			"Finished SPIN session keys migration: legacy_owners={}",
+			Self::legacy_key_owners(),
+		);
+
+		// Per validator: the legacy check, `NextKeys` translation and the removal and
+		// insertion of both key owner records. Plus `QueuedKeys`.
+		db_weight.reads_writes(
+			validators.saturating_mul(3).saturating_add(1),
+			validators.saturating_mul(5).saturating_add(1),
+		)
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+		let validators = pallet_session::NextKeys::<Runtime>::iter_keys().count() as u32;
+		let legacy = Self::legacy_key_owners();
+		log::info!(
+			target: LOG_TARGET,
+			"Pre-upgrade SPIN session keys state: validators={}, legacy_owners={}",
+			validators,
+			legacy,
+		);
+		Ok((validators, legacy).encode())
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+		let (pre_validators, _): (u32, u32) = Decode::decode(&mut state.as_slice())
+			.map_err(|_| "Failed to decode pre-upgrade SPIN session keys state")?;
+		let validators = pallet_session::NextKeys::<Runtime>::iter_keys().count() as u32;
+		let owned = pallet_session::NextKeys::<Runtime>::iter()
+			.filter(|(who, keys)| {
+				Session::key_owner(KEY_TYPE, keys.spin.as_ref()).as_ref() == Some(who)
+			})
+			.count() as u32;
+
+		log::info!(
+			target: LOG_TARGET,
+			"Post-upgrade SPIN session keys state: validators={}, spin_owners={}",
+			validators,
+			owned,
+		);
+
+		ensure!(pre_validators == validators, "validator count changed during migration");
+		ensure!(Self::legacy_key_owners() == 0, "SPIN keys still owned under AURA after migration");
+		ensure!(owned == validators, "SPIN key owners missing after migration");
+
+		Ok(())
+	}
+}